@@ -17,8 +17,11 @@
 extern crate geofencing;
 
 use geofencing::coordinates::*;
+use geofencing::error::GeofenceError;
 use geofencing::geofencer::*;
+use geofencing::reporter::{AnsiReporter, Colour, Reporter};
 use std::io::stdin;
+use std::time::Duration;
 
 ///
 /// Retrieves command line input from the user
@@ -37,31 +40,43 @@ pub fn get_user_input(str: &mut String) {
 ///
 /// Methods to print user instruction
 ///
-fn user_display(_str: &str) {
-    display_bold(&format!("YOU HAVE SELECTED {}", _str), Colour::Black);
-    display_bold(
+fn user_display(reporter: &dyn Reporter, _str: &str) {
+    reporter.display_bold(&format!("YOU HAVE SELECTED {}", _str), Colour::Black);
+    reporter.display_bold(
         "PLACE THE FILE IN geofencing/data FOLDER AND PROVIDE THE FILE NAME",
         Colour::Black,
     );
-    display_bold("REFER TO README FILE FOR FILE STRUCTURE", Colour::Black);
+    reporter.display_bold("REFER TO README FILE FOR FILE STRUCTURE", Colour::Black);
 }
 
 ///
 /// Methods to print user instruction
 ///
-fn user_title(_str: &str, _desc: &str) {
-    display_underline(&format!("SIMULATION {}:-", _str), Colour::Purple);
-    display_underline(
+fn user_title(reporter: &dyn Reporter, _str: &str, _desc: &str) {
+    reporter.display_underline(&format!("SIMULATION {}:-", _str), Colour::Purple);
+    reporter.display_underline(
         &format!("GEO LOCATION SIMULATION FOR {}.", _desc),
         Colour::Purple,
     );
-    display_bold(
+    reporter.display_bold(
         "SELECT THE SHAPE THAT YOU WANT YOUR FENCE TO BE",
         Colour::Blue,
     );
-    display_bold("1. POLYGON  ", Colour::Blue);
-    display_bold("2. CIRCLE  ", Colour::Blue);
-    display_bold("3. TRIANGLE   ", Colour::Blue);
+    reporter.display_bold("1. POLYGON  ", Colour::Blue);
+    reporter.display_bold("2. CIRCLE  ", Colour::Blue);
+    reporter.display_bold("3. TRIANGLE   ", Colour::Blue);
+    reporter.display_bold("4. MULTIPOLYGON   ", Colour::Blue);
+    reporter.display_bold("5. CORRIDOR   ", Colour::Blue);
+    reporter.display_bold("6. RECTANGLE   ", Colour::Blue);
+}
+
+///
+/// Prints the error in red, if the fence file could not be used
+///
+fn report_error<T>(reporter: &dyn Reporter, result: Result<T, GeofenceError>) {
+    if let Err(error) = result {
+        reporter.display_bold(&format!("{}", error), Colour::Red);
+    }
 }
 
 ///
@@ -77,33 +92,64 @@ fn user_title(_str: &str, _desc: &str) {
 ///
 ///
 pub fn main() {
+    // Prints in colour, and waits for a second between the positions of the moving objects.
+    let reporter = AnsiReporter::new().with_delay(Duration::from_secs(1));
+
     // Simulation 1 :- Verify if a moving target (Intransit object)  is with in the Fence
 
-    user_title("1", "IN-TRANSIT OBJECTS");
+    user_title(&reporter, "1", "IN-TRANSIT OBJECTS");
     let mut input = String::new();
     get_user_input(&mut input);
     let mut int_value: i32 = input.trim().parse().unwrap_or(0);
     // let start = get_current_time();
     input = String::new();
     if int_value == 1 {
-        user_display("POLYGON");
+        user_display(&reporter, "POLYGON");
         get_user_input(&mut input);
         input.pop();
-        execute(Shape::Polygon, input.as_str(), true);
-
-
+        report_error(
+            &reporter,
+            execute(Shape::Polygon, input.as_str(), &reporter),
+        );
     } else if int_value == 2 {
-        user_display("CIRCLE");
+        user_display(&reporter, "CIRCLE");
         get_user_input(&mut input);
         input.pop();
-        execute(Shape::Circle, input.as_str(), true);
+        report_error(&reporter, execute(Shape::Circle, input.as_str(), &reporter));
     } else if int_value == 3 {
-        user_display("TRIANGLE");
+        user_display(&reporter, "TRIANGLE");
+        get_user_input(&mut input);
+        input.pop();
+        report_error(
+            &reporter,
+            execute(Shape::Triangle, input.as_str(), &reporter),
+        );
+    } else if int_value == 4 {
+        user_display(&reporter, "MULTIPOLYGON");
+        get_user_input(&mut input);
+        input.pop();
+        report_error(
+            &reporter,
+            execute(Shape::MultiPolygon, input.as_str(), &reporter),
+        );
+    } else if int_value == 5 {
+        user_display(&reporter, "CORRIDOR");
         get_user_input(&mut input);
         input.pop();
-        execute(Shape::Triangle, input.as_str(), true);
+        report_error(
+            &reporter,
+            execute(Shape::Corridor, input.as_str(), &reporter),
+        );
+    } else if int_value == 6 {
+        user_display(&reporter, "RECTANGLE");
+        get_user_input(&mut input);
+        input.pop();
+        report_error(
+            &reporter,
+            execute(Shape::Rectangle, input.as_str(), &reporter),
+        );
     } else {
-        display_bold(
+        reporter.display_bold(
             "THE SELECTION IS NOT IN THE LIST. PLEASE TRY AGAIN",
             Colour::Red,
         );
@@ -112,7 +158,7 @@ pub fn main() {
     //    println!("Time elapsed  is: {:?}", duration);
     // Simulation 2 :- Verify if a particular coordinate is with in the Fence
 
-    user_title("2", "PARTICULAR COORDINATE");
+    user_title(&reporter, "2", "PARTICULAR COORDINATE");
     input = String::new();
     get_user_input(&mut input);
     int_value = input.trim().parse().unwrap_or(0);
@@ -120,51 +166,72 @@ pub fn main() {
     let mut shape: Shape = Shape::Polygon;
     let mut str_shape: &str = "POLYGON";
     if int_value == 1 {
-        user_display(str_shape);
+        user_display(&reporter, str_shape);
         get_user_input(&mut filename);
         filename.pop();
     } else if int_value == 2 {
         str_shape = "CIRCLE";
         shape = Shape::Circle;
-        user_display(str_shape);
+        user_display(&reporter, str_shape);
         get_user_input(&mut filename);
         filename.pop();
     } else if int_value == 3 {
         str_shape = "TRIANGLE";
         shape = Shape::Triangle;
-        user_display(str_shape);
+        user_display(&reporter, str_shape);
+        get_user_input(&mut filename);
+        filename.pop();
+    } else if int_value == 4 {
+        str_shape = "MULTIPOLYGON";
+        shape = Shape::MultiPolygon;
+        user_display(&reporter, str_shape);
+        get_user_input(&mut filename);
+        filename.pop();
+    } else if int_value == 5 {
+        str_shape = "CORRIDOR";
+        shape = Shape::Corridor;
+        user_display(&reporter, str_shape);
+        get_user_input(&mut filename);
+        filename.pop();
+    } else if int_value == 6 {
+        str_shape = "RECTANGLE";
+        shape = Shape::Rectangle;
+        user_display(&reporter, str_shape);
         get_user_input(&mut filename);
         filename.pop();
     } else {
-        display_bold(
+        reporter.display_bold(
             "THE SELECTION IS NOT IN THE LIST. PLEASE TRY AGAIN",
             Colour::Red,
         );
     }
     if int_value != 0 {
-        display_underline(str_shape, Colour::Blue);
+        reporter.display_underline(str_shape, Colour::Blue);
         let mut done = false;
         while !done {
-            display(
+            reporter.display(
                 "Enter the Latitude (defaulted to 0 if incorrect value) ",
                 Colour::Purple,
             );
             input = String::new();
             get_user_input(&mut input);
-            let int_lat: f64 = input.trim().parse().unwrap_or(0.0) as f64;
-            display(
+            let int_lat: f64 = input.trim().parse().unwrap_or(0.0);
+            reporter.display(
                 "Enter the Longitude (defaulted to 0 if incorrect value)",
                 Colour::Purple,
             );
             input = String::new();
             get_user_input(&mut input);
-            let int_lon: f64 = input.trim().parse().unwrap_or(0.0) as f64;
-            display(
+            let int_lon: f64 = input.trim().parse().unwrap_or(0.0);
+            reporter.display(
                 "CHECKING IF THE GIVEN COORDINATE IS WITHIN THE FENCE ",
                 Colour::Purple,
             );
-            contains(shape, filename.as_str(), int_lat, int_lon);
-            display(
+            report_error(
+                &reporter,
+                contains(shape, filename.as_str(), int_lat, int_lon, &reporter),
+            );
+            reporter.display(
                 "Do you want to continue?. Y to continue or any other key to exit ",
                 Colour::Purple,
             );
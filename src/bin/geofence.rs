@@ -0,0 +1,522 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// The geofence command checks positions and tracks against fence files from the command line,
+/// so that the geofencing crate can be scripted in pipelines:
+///   geofence check <fence> <lat> <lon>    position of the coordinate in each fence
+///   geofence track <fence> <track>        position of every fix of every track in each fence
+///   geofence validate <fence>             checks that the fences of the file are valid fences
+///   geofence convert <fence> --to <fmt>   writes the fences as geojson, or as csv with a wkt column
+/// The format of the files is taken from their extension (.json, .geojson, .kml, .kmz, .wkt,
+/// .csv, .gpx, .nmea). The results are written as text, json or csv, and the command exits
+/// with 1 when a file cannot be read and with 2 when the arguments are wrong.
+/// --validate rejects or repairs the polygon fences that have defects, such as crossing edges.
+///
+extern crate csv;
+extern crate geofencing;
+extern crate serde;
+extern crate serde_json;
+
+use geofencing::coordinates::*;
+use geofencing::csv_loader::{read_fences_file, read_tracks_file, FenceColumns, TrackColumns};
+use geofencing::error::GeofenceError;
+use geofencing::evaluation::{evaluate, Evaluation};
+use geofencing::fence::Fence;
+use geofencing::geofencer::read_fence_file;
+use geofencing::geojson::{
+    fences_to_geojson, read_geojson, read_geojson_file, write_geojson, GeoJson,
+};
+use geofencing::gpx::read_gpx_file;
+use geofencing::kml::read_kml_file;
+use geofencing::nmea::NmeaParser;
+use geofencing::validity::Validation;
+use geofencing::wkt::{read_wkt, write_wkt, Geometry};
+use serde::de::DeserializeOwned;
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "Usage:
+  geofence check <fence> <lat> <lon> [options]
+  geofence track <fence> <track> [options]
+  geofence validate <fence> [options]
+  geofence convert <fence> --to <geojson|csv> [options]
+
+Options:
+  --shape <polygon|circle|triangle|multipolygon|corridor|rectangle>
+                                  checks the shape of a .json fence file of this crate
+  --format <text|json|csv>        format of the results, text by default
+  --validate <off|reject|repair>  rejects or repairs the fences that have defects
+  --columns <file>                json file with the column names of .csv files
+  --output <file>                 writes to the file instead of the standard output
+  --help                          prints this message";
+
+/// Errors of the command, the exit code tells them apart.
+enum CliError {
+    /// The arguments are wrong, exits with 2.
+    Usage(String),
+    /// A file could not be read or written, exits with 1.
+    Geofence(GeofenceError),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Geofence(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(reason) => write!(f, "{}", reason),
+            CliError::Geofence(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<GeofenceError> for CliError {
+    fn from(error: GeofenceError) -> CliError {
+        CliError::Geofence(error)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> CliError {
+        CliError::Geofence(error.into())
+    }
+}
+
+impl From<csv::Error> for CliError {
+    fn from(error: csv::Error) -> CliError {
+        CliError::Geofence(error.into())
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(error: serde_json::Error) -> CliError {
+        CliError::Geofence(error.into())
+    }
+}
+
+fn usage<T>(reason: String) -> Result<T, CliError> {
+    Err(CliError::Usage(reason))
+}
+
+/// Format of the results written by check and track.
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Command line arguments, split in to the command, its positional arguments and the options.
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    shape: Option<Shape>,
+    format: Format,
+    validation: Validation,
+    to: Option<String>,
+    columns: Option<String>,
+    output: Option<String>,
+}
+
+fn parse_args(args: Vec<String>) -> Result<Args, CliError> {
+    let mut args = args.into_iter();
+    let mut parsed = Args {
+        command: String::new(),
+        positional: Vec::new(),
+        shape: None,
+        format: Format::Text,
+        validation: Validation::Off,
+        to: None,
+        columns: None,
+        output: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |option: &str| match args.next() {
+            Some(value) => Ok(value),
+            None => usage(format!("{} needs a value", option)),
+        };
+        match arg.as_str() {
+            "--shape" => match value(&arg)?.parse() {
+                Ok(shape) => parsed.shape = Some(shape),
+                Err(error) => return usage(format!("--shape: {}", error)),
+            },
+            "--format" => {
+                parsed.format = match value(&arg)?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    format => return usage(format!("--format: unknown format '{}'", format)),
+                }
+            }
+            "--validate" => match value(&arg)?.parse() {
+                Ok(validation) => parsed.validation = validation,
+                Err(error) => return usage(format!("--validate: {}", error)),
+            },
+            "--to" => parsed.to = Some(value(&arg)?),
+            "--columns" => parsed.columns = Some(value(&arg)?),
+            "--output" => parsed.output = Some(value(&arg)?),
+            // Negative coordinates are positional arguments, not options.
+            option if option.starts_with("--") => {
+                return usage(format!("unknown option '{}'", option))
+            }
+            _ if parsed.command.is_empty() => parsed.command = arg,
+            _ => parsed.positional.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Returns the positional arguments, when the command has the expected number of them.
+fn positional<'a>(args: &'a Args, names: &[&str]) -> Result<&'a [String], CliError> {
+    if args.positional.len() != names.len() {
+        return usage(format!(
+            "{} needs the arguments <{}>",
+            args.command,
+            names.join("> <")
+        ));
+    }
+    Ok(&args.positional)
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Name of the file without the folder and the extension, used as the id of a fence or the
+/// vehicle of a track when the file has no names.
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Reads the column names of the .csv files from the --columns file, such as
+/// {"vehicle": "device_id", "timestamp": "ts"} for TrackColumns. The columns that are left out
+/// keep their default names.
+fn columns<T: DeserializeOwned + Default>(args: &Args) -> Result<T, CliError> {
+    match &args.columns {
+        Some(path) => Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?),
+        None => Ok(T::default()),
+    }
+}
+
+/// Contents of a .json file, which is either GeoJSON or a fence file of this crate.
+enum JsonFile {
+    GeoJson(GeoJson),
+    Fence(serde_json::Value),
+}
+
+/// Reads a .json file, GeoJSON files are told apart by their "type" field.
+fn read_json(path: &str) -> Result<JsonFile, CliError> {
+    let text = fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&text)?;
+    if value.get("type").is_some() {
+        Ok(JsonFile::GeoJson(read_geojson(text.as_bytes())?))
+    } else {
+        Ok(JsonFile::Fence(value))
+    }
+}
+
+/// Reads the fences of the file, along with their ids.
+fn read_fences(args: &Args, path: &str) -> Result<Vec<(String, Fence)>, CliError> {
+    let fences = match extension(path).as_str() {
+        "geojson" => read_geojson_file(path)?.fences,
+        "kml" => read_kml_file(path)?.fences,
+        #[cfg(feature = "kmz")]
+        "kmz" => geofencing::kml::read_kmz_file(path)?.fences,
+        "csv" => read_fences_file(path, &columns::<FenceColumns>(args)?)?,
+        "wkt" => {
+            let fence = read_wkt(&fs::read_to_string(path)?)?
+                .into_fence()
+                .ok_or_else(|| {
+                    GeofenceError::InvalidGeometry(format!(
+                        "the WKT of {} is not a polygon or multipolygon",
+                        path
+                    ))
+                })?;
+            vec![(file_stem(path), fence)]
+        }
+        "json" => match read_json(path)? {
+            JsonFile::GeoJson(geojson) => geojson.fences,
+            JsonFile::Fence(_) => vec![(file_stem(path), read_fence_file(args.shape, path)?)],
+        },
+        _ => return usage(format!("unknown fence file format '{}'", path)),
+    };
+    fences
+        .into_iter()
+        .map(|(id, fence)| Ok((id, args.validation.apply(fence)?)))
+        .collect()
+}
+
+/// Reads the NMEA sentences of the file, skipping the ones that cannot be parsed.
+fn read_nmea(path: &str) -> Result<Vec<Fix>, CliError> {
+    let mut parser = NmeaParser::new();
+    let mut fixes = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        fixes.extend(parser.parse_dated(&line?).unwrap_or_default());
+    }
+    fixes.extend(parser.finish());
+    Ok(fixes)
+}
+
+/// Reads the tracks of the file, along with their vehicles.
+fn read_tracks(args: &Args, path: &str) -> Result<Vec<(String, Vec<Fix>)>, CliError> {
+    let tracks = match extension(path).as_str() {
+        "gpx" => read_gpx_file(path)?.into_tracks(),
+        "nmea" => vec![(file_stem(path), read_nmea(path)?)],
+        "csv" => read_tracks_file(path, &columns::<TrackColumns>(args)?)?,
+        "geojson" => read_geojson_file(path)?.tracks,
+        "kml" => read_kml_file(path)?.tracks,
+        #[cfg(feature = "kmz")]
+        "kmz" => geofencing::kml::read_kmz_file(path)?.tracks,
+        "json" => match read_json(path)? {
+            JsonFile::GeoJson(geojson) => geojson.tracks,
+            // The moving coordinates of a fence file of this crate.
+            JsonFile::Fence(value) => {
+                let vehicle = value
+                    .get("vehicle")
+                    .and_then(|vehicle| vehicle.as_str())
+                    .map_or_else(|| file_stem(path), str::to_string);
+                let fixes = match value.get("moving_coordinate") {
+                    Some(fixes) => serde_json::from_value(fixes.clone())?,
+                    None => Vec::new(),
+                };
+                vec![(vehicle, fixes)]
+            }
+        },
+        _ => return usage(format!("unknown track file format '{}'", path)),
+    };
+    Ok(tracks)
+}
+
+/// Parses a latitude or longitude argument, instead of defaulting it to 0.
+fn coordinate(name: &str, value: &str) -> Result<f64, CliError> {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => usage(format!("the {} '{}' is not a number", name, value)),
+    }
+}
+
+fn location_name(location: Location) -> &'static str {
+    match location {
+        Location::Inside => "inside",
+        Location::OnBoundary => "on_boundary",
+        Location::Outside => "outside",
+    }
+}
+
+/// Writes the evaluations in the given format.
+fn write_evaluations(
+    out: &mut dyn Write,
+    evaluations: &[Evaluation],
+    format: Format,
+) -> Result<(), CliError> {
+    match format {
+        Format::Text => {
+            for evaluation in evaluations {
+                writeln!(out, "{}: {}", evaluation.fence_id, evaluation)?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, evaluations)?;
+            writeln!(out)?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record([
+                "vehicle",
+                "fence_id",
+                "lat",
+                "lon",
+                "timestamp",
+                "location",
+                "distance",
+            ])?;
+            for evaluation in evaluations {
+                let position = &evaluation.position;
+                writer.write_record([
+                    evaluation.vehicle.clone(),
+                    evaluation.fence_id.clone(),
+                    position.lat.to_string(),
+                    position.lon.to_string(),
+                    position
+                        .timestamp
+                        .map_or_else(String::new, |time| time.to_string()),
+                    location_name(evaluation.location).to_string(),
+                    evaluation.distance.to_string(),
+                ])?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn check(args: &Args, out: &mut dyn Write) -> Result<(), CliError> {
+    let arguments = positional(args, &["fence", "lat", "lon"])?;
+    let point = Fix::new(
+        coordinate("latitude", &arguments[1])?,
+        coordinate("longitude", &arguments[2])?,
+    );
+    let mut evaluations = Vec::new();
+    for (id, fence) in read_fences(args, &arguments[0])? {
+        evaluations.extend(evaluate(
+            fence.as_geofence(),
+            &id,
+            "point",
+            std::slice::from_ref(&point),
+        ));
+    }
+    write_evaluations(out, &evaluations, args.format)
+}
+
+fn track(args: &Args, out: &mut dyn Write) -> Result<(), CliError> {
+    let arguments = positional(args, &["fence", "track"])?;
+    let fences = read_fences(args, &arguments[0])?;
+    let mut evaluations = Vec::new();
+    for (vehicle, fixes) in read_tracks(args, &arguments[1])? {
+        for (id, fence) in &fences {
+            evaluations.extend(evaluate(fence.as_geofence(), id, &vehicle, &fixes));
+        }
+    }
+    write_evaluations(out, &evaluations, args.format)
+}
+
+fn validate(args: &Args, out: &mut dyn Write) -> Result<(), CliError> {
+    let arguments = positional(args, &["fence"])?;
+    let fences = read_fences(args, &arguments[0])?;
+    if fences.is_empty() {
+        return Err(
+            GeofenceError::InvalidGeometry(format!("{} has no fences", arguments[0])).into(),
+        );
+    }
+    let mut invalid = 0;
+    for (id, fence) in &fences {
+        let defects = fence.validate();
+        if defects.is_empty() {
+            writeln!(out, "{}: valid {} fence", id, fence.shape())?;
+        } else {
+            invalid += 1;
+            writeln!(out, "{}: invalid {} fence", id, fence.shape())?;
+            for defect in defects {
+                writeln!(out, "  {}", defect)?;
+            }
+        }
+    }
+    if invalid > 0 {
+        return Err(GeofenceError::InvalidGeometry(format!(
+            "{} of the {} fences of {} have defects",
+            invalid,
+            fences.len(),
+            arguments[0]
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn convert(args: &Args, out: &mut dyn Write) -> Result<(), CliError> {
+    let arguments = positional(args, &["fence"])?;
+    let fences = read_fences(args, &arguments[0])?;
+    match args.to.as_deref() {
+        Some("geojson") => write_geojson(out, &fences_to_geojson(&fences))?,
+        // The id and wkt columns read back by csv_loader::read_fences.
+        Some("csv") => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(["id", "wkt"])?;
+            for (id, fence) in &fences {
+                let wkt = write_wkt(&Geometry::from_fence(fence)?);
+                writer.write_record([id.as_str(), wkt.as_str()])?;
+            }
+            writer.flush()?;
+        }
+        Some(format) => return usage(format!("--to: unknown format '{}'", format)),
+        None => return usage("convert needs --to <geojson|csv>".to_string()),
+    }
+    Ok(())
+}
+
+/// The --output file, which is only created when the command first writes to it, so that a
+/// command that fails on its arguments leaves the file as it was.
+struct OutputFile {
+    path: String,
+    file: Option<File>,
+}
+
+impl OutputFile {
+    fn file(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            self.file = Some(File::create(&self.path)?);
+        }
+        Ok(self
+            .file
+            .as_mut()
+            .expect("the output file was just created"))
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    /// Creates the file of a command that wrote nothing, once it has succeeded.
+    fn flush(&mut self) -> io::Result<()> {
+        self.file()?.flush()
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), CliError> {
+    let args = parse_args(args)?;
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(OutputFile {
+            path: path.clone(),
+            file: None,
+        }),
+        None => Box::new(io::stdout()),
+    };
+    match args.command.as_str() {
+        "check" => check(&args, &mut *out)?,
+        "track" => track(&args, &mut *out)?,
+        "validate" => validate(&args, &mut *out)?,
+        "convert" => convert(&args, &mut *out)?,
+        "" => return usage("a command is needed".to_string()),
+        command => return usage(format!("unknown command '{}'", command)),
+    }
+    out.flush()?;
+    Ok(())
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(error) = run(args) {
+        eprintln!("geofence: {}", error);
+        if let CliError::Usage(_) = error {
+            eprintln!("{}", USAGE);
+        }
+        process::exit(error.exit_code());
+    }
+}
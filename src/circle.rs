@@ -4,28 +4,223 @@
 // distribution of this software for license terms.
 
 use coordinates::*;
-use std::env;
-use std::error::Error;
-use std::fs::File;
-use std::{thread, time};
+use crossing::circle_intersections;
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::{BoundaryDistance, Geofence};
+use geodesy;
+use geodesy::{Model, Unit};
+use geofencer::track;
+use loader::{data_path, read_circle_tracker_file};
+use reporter::{Colour, Reporter};
+use std::convert::TryFrom;
+use std::f64::consts::PI;
 
 /// The below function checks if the given coordinates latitude and longitude are inside the fence created with CircleCoordinates
-/// for the given radius.
+/// for the given radius, which is in latitude / longitude degrees (planar fence).
 /// It uses Pythagoras theorem to calculate if a given point is within the circular fence created
 /// The function the below parameters
 /// CircleCoordinates -> contains Coordinates required for creating circular fence
 /// Coordinates -> contains coordinates that for which location has to be determined.
-/// Returns bool -> True if the coordinates are inside the fence, False if it is outside.
+/// Returns Location -> Inside, OnBoundary if the coordinates are exactly on the circle, or Outside.
 ///
-fn contains(circle: &CircleCoordinates, point: &Coordinates) -> bool {
-    let mut b_return = false;
+fn locate(circle: &CircleCoordinates, point: &Coordinates) -> Location {
     // use Pythagoras theorem to figure out if the given latitude and longitude is within circle for the given radius
     // (x-center_x)^2 + (y - center_y)^2 < radius^2
     let distance = f64::powf(circle.lat - point.lat, 2.0) + f64::powf(circle.lon - point.lon, 2.0);
-    if distance <= (f64::powf(circle.rad, 2.0)) {
-        b_return = true;
+    compare(distance, f64::powf(circle.rad, 2.0))
+}
+
+/// The below function checks if the given coordinates are inside the circular fence, whose radius
+/// is given in real world units.
+/// It calculates the distance on the surface of the earth between the center of the fence and
+/// the coordinates, and compares it with the radius in meters.
+/// Returns Location -> Inside, OnBoundary if the coordinates are exactly on the circle, or Outside.
+///
+fn locate_geodesic(circle: &CircleCoordinates, point: &Coordinates, radius: f64) -> Location {
+    let center = Coordinates::new(circle.lat, circle.lon);
+    compare(
+        geodesy::distance(&center, point, circle.model.unwrap_or_default()),
+        radius,
+    )
+}
+
+/// Compares the distance from the center with the radius of the fence.
+fn compare(distance: f64, radius: f64) -> Location {
+    if distance < radius {
+        Location::Inside
+    } else if distance == radius {
+        Location::OnBoundary
+    } else {
+        Location::Outside
+    }
+}
+
+/// Circular fence built from a center coordinate and a radius.
+/// The fence is planar when the radius has no unit, and geodesic when the radius has a unit.
+#[derive(Debug, Clone)]
+pub struct CircleFence {
+    circle: CircleCoordinates,
+}
+
+impl CircleFence {
+    /// Creates the fence from the center and the radius.
+    /// Returns GeofenceError::InvalidGeometry if the radius is not a positive number.
+    pub fn new(circle: CircleCoordinates) -> Result<CircleFence, GeofenceError> {
+        if !(circle.rad.is_finite() && circle.rad > 0.0) {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "the radius of a circle fence must be positive, but {} was supplied",
+                circle.rad
+            )));
+        }
+        Ok(CircleFence { circle })
+    }
+
+    /// Creates the fence from the center coordinates and the radius.
+    pub fn from_center(center: &Coordinates, rad: f64) -> Result<CircleFence, GeofenceError> {
+        CircleFence::new(CircleCoordinates::new(center.lat, center.lon, rad))
+    }
+
+    /// Creates a geodesic fence from the center coordinates and the radius in the given unit,
+    /// evaluated on the surface of the earth with the given model.
+    pub fn geodesic(
+        center: &Coordinates,
+        rad: f64,
+        unit: Unit,
+        model: Model,
+    ) -> Result<CircleFence, GeofenceError> {
+        CircleFence::new(CircleCoordinates::geodesic(
+            center.lat, center.lon, rad, unit, model,
+        ))
+    }
+
+    /// Returns the center and the radius of the fence.
+    pub fn circle(&self) -> &CircleCoordinates {
+        &self.circle
+    }
+}
+
+impl TryFrom<CircleCoordinates> for CircleFence {
+    type Error = GeofenceError;
+
+    fn try_from(circle: CircleCoordinates) -> Result<CircleFence, GeofenceError> {
+        CircleFence::new(circle)
+    }
+}
+
+impl Geofence for CircleFence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        match self.circle.radius_in_meters() {
+            Some(radius) => locate_geodesic(&self.circle, point, radius),
+            None => locate(&self.circle, point),
+        }
+    }
+
+    /// For a geodesic fence the box encloses the circle on the surface of the earth, see
+    /// geodesy::circle_bounding_box.
+    fn bounding_box(&self) -> BoundingBox {
+        if let Some(radius) = self.circle.radius_in_meters() {
+            let center = Coordinates::new(self.circle.lat, self.circle.lon);
+            return geodesy::circle_bounding_box(
+                &center,
+                radius,
+                self.circle.model.unwrap_or_default(),
+            );
+        }
+        BoundingBox::new(
+            self.circle.lat - self.circle.rad,
+            self.circle.lon - self.circle.rad,
+            self.circle.lat + self.circle.rad,
+            self.circle.lon + self.circle.rad,
+        )
+    }
+
+    /// For a geodesic fence the area is the spherical cap area, in square meters.
+    fn area(&self) -> f64 {
+        match self.circle.radius_in_meters() {
+            Some(radius) => {
+                2.0 * PI
+                    * f64::powf(geodesy::EARTH_RADIUS, 2.0)
+                    * (1.0 - (radius / geodesy::EARTH_RADIUS).cos())
+            }
+            None => PI * f64::powf(self.circle.rad, 2.0),
+        }
+    }
+
+    /// For a geodesic fence the distance is in meters.
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        let center = Coordinates::new(self.circle.lat, self.circle.lon);
+        match self.circle.radius_in_meters() {
+            Some(radius) => {
+                (geodesy::distance(&center, point, self.circle.model.unwrap_or_default()) - radius)
+                    .max(0.0)
+            }
+            None => (distance(&center, point) - self.circle.rad).max(0.0),
+        }
+    }
+
+    /// For a geodesic fence the coordinates are projected on to a plane in meters around the
+    /// center of the fence, which is accurate for fences up to a few tens of kilometers. The
+    /// longitudes are taken the shorter way round from the center, across the antimeridian if
+    /// needed.
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        let center = Coordinates::new(self.circle.lat, self.circle.lon);
+        match self.circle.radius_in_meters() {
+            Some(radius) => {
+                let project = |point: &Coordinates| {
+                    Coordinates::new(
+                        (point.lat - center.lat) / geodesy::meters_to_lat_degrees(1.0),
+                        geodesy::lon_difference(center.lon, point.lon)
+                            / geodesy::meters_to_lon_degrees(1.0, center.lat),
+                    )
+                };
+                let origin = Coordinates::new(0.0, 0.0);
+                circle_intersections(&project(start), &project(end), &origin, radius)
+            }
+            None => circle_intersections(start, end, &center, self.circle.rad),
+        }
+    }
+
+    /// For a geodesic fence the distance is in meters, and the nearest point is found on a
+    /// plane in meters around the center of the fence, going the shorter way round the earth
+    /// from the center to the coordinates.
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        let center = Coordinates::new(self.circle.lat, self.circle.lon);
+        // Degrees per unit of distance along the latitude and the longitude.
+        let geodesic = self.circle.radius_in_meters();
+        let (lat_scale, lon_scale, radius, from_center) = match geodesic {
+            Some(radius) => (
+                geodesy::meters_to_lat_degrees(1.0),
+                geodesy::meters_to_lon_degrees(1.0, center.lat),
+                radius,
+                geodesy::distance(&center, point, self.circle.model.unwrap_or_default()),
+            ),
+            None => (1.0, 1.0, self.circle.rad, distance(&center, point)),
+        };
+        let d_lat = (point.lat - center.lat) / lat_scale;
+        let d_lon = match geodesic {
+            Some(_) => geodesy::lon_difference(center.lon, point.lon),
+            None => point.lon - center.lon,
+        } / lon_scale;
+        let length = d_lat.hypot(d_lon);
+        // Every point of the border is as near to the center, the north one is taken.
+        let (d_lat, d_lon) = if length == 0.0 {
+            (1.0, 0.0)
+        } else {
+            (d_lat / length, d_lon / length)
+        };
+        let mut nearest = Coordinates::new(
+            center.lat + d_lat * radius * lat_scale,
+            center.lon + d_lon * radius * lon_scale,
+        );
+        if geodesic.is_some() {
+            nearest.lon = geodesy::wrap_lon(nearest.lon);
+        }
+        BoundaryDistance {
+            distance: from_center - radius,
+            nearest,
+        }
     }
-    b_return
 }
 
 /// The below function reads the incoming json file which is of the format
@@ -41,56 +236,32 @@ fn contains(circle: &CircleCoordinates, point: &Coordinates) -> bool {
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates
 /// Returns Result -> contains either MovingTrackerCircle struct if success or Error if any failure.
-fn read_movingtrackercircle_file(filename: &str) -> Result<MovingTrackerCircle, Box<dyn Error>> {
-    // Open the file in read-only mode.
-    let current_path = env::current_dir();
-    let mut path_buff = current_path.ok().unwrap();
-    path_buff.push("data");
-    path_buff.push(filename);
-    let actual_path = path_buff.to_str().unwrap();
-
-    // Read the JSON contents of the file as an instance of `MovingTrackerPolygon`.
-    let u = serde_json::from_reader(File::open(actual_path)?)?;
-
-    // Return the value as Result.
-    Ok(u)
-}
-/// The below function calls get_circle_json_info  retrieves MovingTrackerCircle struct
-///The function takes the below parameters
-/// filename -> contains the json file which contains the coordinates
-/// Returns MovingTrackerCircle
-fn get_circle_json_info(filename: &str) -> MovingTrackerCircle {
-    // Read the JSON contents of the file as an instance of `MovingTrackerPolygon`.
-
-    let json_structure = read_movingtrackercircle_file(filename);
-    match json_structure{
-        Ok(json_structure)=> json_structure,
-        Err(error) => {
-            panic!("Problem opening the json file. Check if the file is correct and has correct json values. The error is : {:?}", error)
-        }
-    }
+fn read_movingtrackercircle_file(filename: &str) -> Result<MovingTrackerCircle, GeofenceError> {
+    // Read the JSON contents of the file in the data folder as an instance of `MovingTrackerCircle`.
+    read_circle_tracker_file(data_path(filename))
 }
 
 /// The function does the below
 /// Reads the input file
 /// Creates circle fence based on the coordinates given in the input file
 /// Executes set of coordinates for which position has to be determined.
-/// Prints if the coordinates are within or outside the fence
+/// Prints if the coordinates are within or outside the fence, and returns the evaluation of each of them
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates
-
-pub fn execute_circle(filename: &str, delay: bool) -> String {
-    let second = time::Duration::from_millis(1000);
+/// reporter -> prints the fence and the evaluations
+pub fn execute_circle(
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
     //   let start = get_current_time();
-    let u = get_circle_json_info(filename);
-    let cor: CircleCoordinates = u.shape_coordinate;
-    let run: Vec<Coordinates> = u.moving_coordinate;
-    let mut ret_val = "".to_string();
-    display_bold(
+    let u = read_movingtrackercircle_file(filename)?;
+    let fence = CircleFence::new(u.shape_coordinate)?;
+    let cor = fence.circle();
+    reporter.display_bold(
         "Created Circular Fence, with the  coordinates",
         Colour::Blue,
     );
-    display(
+    reporter.display(
         &format!(
             "latitude {}, longitude {}, radius {}",
             cor.lat, cor.lon, cor.rad
@@ -98,32 +269,13 @@ pub fn execute_circle(filename: &str, delay: bool) -> String {
         Colour::Blue,
     );
 
-    display_bold("Tracking and Verifying, if the below moving objects position is within or outside the fence", Colour::Black);
-    for x in run {
-        let mut str = "is out of the fence";
-        if delay {
-            thread::sleep(second);
-        }
-        if contains(&cor, &x) {
-            str = "is inside the fence";
-        }
-        display(
-            &format!(
-                "The {} positioned at latitude {}, longitude {}, {} ",
-                u.vehicle, x.lat, x.lon, &str
-            ),
-            Colour::Black,
-        );
-        ret_val.push_str(
-            format!(
-                "The {} positioned at latitude {}, longitude {}, {} \n ",
-                u.vehicle, x.lat, x.lon, &str
-            )
-            .as_str(),
-        );
-    }
-    //  println!("The time taken for the execute method to perform this operation is {:?}", calculate_run_time(&start));
-    ret_val
+    Ok(track(
+        &fence,
+        filename,
+        &u.vehicle,
+        &u.moving_coordinate,
+        reporter,
+    ))
 }
 
 /// The function does the below
@@ -134,18 +286,24 @@ pub fn execute_circle(filename: &str, delay: bool) -> String {
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates which is necessary to create the fence
 /// lat and lon -> search coordinates
-
-pub fn contains_in_circle(filename: &str, lat: f64, lon: f64) -> bool {
+/// reporter -> prints the fence and the result
+pub fn contains_in_circle(
+    filename: &str,
+    lat: f64,
+    lon: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
     //  let start = get_current_time();
-    let u = get_circle_json_info(filename);
-    let cor: CircleCoordinates = u.shape_coordinate;
+    let u = read_movingtrackercircle_file(filename)?;
+    let fence = CircleFence::new(u.shape_coordinate)?;
+    let cor = fence.circle();
     let point: Coordinates = Coordinates::new(lat, lon);
     let mut retval = false;
-    display_bold(
+    reporter.display_bold(
         "Searching the vehicle in Circular Fence, which is built with coordinates",
         Colour::Blue,
     );
-    display(
+    reporter.display(
         &format!(
             "latitude {}, longitude {}, radius {}",
             cor.lat, cor.lon, cor.rad
@@ -154,11 +312,11 @@ pub fn contains_in_circle(filename: &str, lat: f64, lon: f64) -> bool {
     );
     //println!("******** Searching the vehicle in Circular Fence, which is built with coordinates, latitude {}, longitude {}, radius {} *********", cor.lat,cor.lon,cor.rad);
     let mut str = "is out of the fence";
-    if contains(&cor, &point) {
+    if fence.contains(&point) {
         str = "is inside the fence";
         retval = true
     }
-    display(
+    reporter.display(
         &format!(
             "The {} positioned at latitude {}, longitude {}, {} ",
             u.vehicle, lat, lon, &str
@@ -167,5 +325,5 @@ pub fn contains_in_circle(filename: &str, lat: f64, lon: f64) -> bool {
     );
     // println!("The time taken for the execute method to perform this operation is {:?}", calculate_run_time(&start));
     //println!("The {} positioned at latitude {}, longitude {}, {} ",u.vehicle, lat,lon, &str);
-    retval
+    Ok(retval)
 }
@@ -7,12 +7,18 @@
 /// Structs and Functions that are commonly used across multiple module will reside in this
 /// library
 ///
-extern crate ansi_term;
+extern crate robust;
 extern crate serde;
 extern crate serde_derive;
 extern crate serde_json;
 
-use self::serde_derive::Deserialize;
+use self::serde_derive::{Deserialize, Serialize};
+use error::GeofenceError;
+use geodesy::{Model, Unit};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 //use std::time::Instant;
 //use std::time::Duration;
 
@@ -22,17 +28,66 @@ pub enum Shape {
     Polygon,
     Circle,
     Triangle,
+    MultiPolygon,
+    Corridor,
+    Rectangle,
 }
 
-pub enum Colour {
-    Red,
-    Blue,
-    Yellow,
-    Purple,
-    Black,
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Shape::Polygon => write!(f, "Polygon"),
+            Shape::Circle => write!(f, "Circle"),
+            Shape::Triangle => write!(f, "Triangle"),
+            Shape::MultiPolygon => write!(f, "MultiPolygon"),
+            Shape::Corridor => write!(f, "Corridor"),
+            Shape::Rectangle => write!(f, "Rectangle"),
+        }
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Parses the name of the shape, ignoring the case, as it is written in the "shape" field of
+/// the json files.
+impl FromStr for Shape {
+    type Err = GeofenceError;
+
+    fn from_str(shape: &str) -> Result<Shape, GeofenceError> {
+        match shape.trim().to_ascii_lowercase().as_str() {
+            "polygon" => Ok(Shape::Polygon),
+            "circle" => Ok(Shape::Circle),
+            "triangle" => Ok(Shape::Triangle),
+            "multipolygon" => Ok(Shape::MultiPolygon),
+            "corridor" => Ok(Shape::Corridor),
+            "rectangle" => Ok(Shape::Rectangle),
+            _ => Err(GeofenceError::Parse(format!("unknown shape '{}'", shape))),
+        }
+    }
+}
+
+/// Position of a coordinate with respect to a fence.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Location {
+    Inside,
+    OnBoundary,
+    Outside,
+}
+
+/// Rule used to decide if a coordinate is inside a fence made of several rings
+/// (polygons with holes and multipolygons), from the winding numbers of the rings around it.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FillRule {
+    /// Inside when the coordinate is enclosed by an odd number of rings.
+    /// Holes are excluded irrespective of the direction of their coordinates.
+    #[default]
+    EvenOdd,
+    /// Inside when the rings wind around the coordinate a non zero number of times.
+    /// Holes are only excluded when they go in the opposite direction of the outer ring.
+    NonZero,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct Coordinates {
     pub lat: f64,
     pub lon: f64,
@@ -42,85 +97,399 @@ impl Coordinates {
     pub fn new(lat: f64, lon: f64) -> Coordinates {
         Coordinates { lat, lon }
     }
-    pub fn display(self) {
-        println!(
-            "Coordinates Latitude {} and Longitude {} ",
-            self.lat, self.lon
-        );
+}
+
+/// Position of a tracked vehicle, as reported by its device.
+/// Only the latitude and longitude are required, the rest of the values are optional.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct Fix {
+    pub lat: f64,
+    pub lon: f64,
+    /// Time of the fix, in seconds since the Unix epoch (1970-01-01T00:00:00Z).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<f64>,
+    /// Horizontal accuracy of the fix, in meters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+    /// Speed over ground, in meters per second.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+    /// Direction of travel, in degrees clockwise from true north.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<f64>,
+    /// Altitude above the mean sea level, in meters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<f64>,
+}
+
+impl Fix {
+    pub fn new(lat: f64, lon: f64) -> Fix {
+        Fix {
+            lat,
+            lon,
+            ..Fix::default()
+        }
+    }
+
+    /// Sets the time of the fix, in seconds since the Unix epoch.
+    pub fn with_timestamp(mut self, timestamp: f64) -> Fix {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Returns the latitude and longitude of the fix.
+    pub fn coordinates(&self) -> Coordinates {
+        Coordinates::new(self.lat, self.lon)
+    }
+
+    /// Returns the time of the fix, or None if the fix has no timestamp.
+    pub fn time(&self) -> Option<SystemTime> {
+        self.timestamp
+            .filter(|timestamp| timestamp.is_finite() && *timestamp >= 0.0)
+            .map(|timestamp| UNIX_EPOCH + Duration::from_secs_f64(timestamp))
     }
 }
 
-#[derive(Deserialize, Debug)]
+impl From<Coordinates> for Fix {
+    fn from(point: Coordinates) -> Fix {
+        Fix::new(point.lat, point.lon)
+    }
+}
+
+/// Sorts the fixes by their timestamp, so that uploads that arrived out of sequence are
+/// tracked in the order they were recorded. Fixes without a timestamp are placed first,
+/// and the sort is stable so fixes with the same timestamp keep their order.
+pub fn sort_by_time(fixes: &mut [Fix]) {
+    fixes.sort_by(|fix1, fix2| {
+        fix1.timestamp
+            .partial_cmp(&fix2.timestamp)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Returns the number of days from 1970-01-01 to the given date of the proleptic
+/// Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parses an ISO 8601 date and time, such as 2020-05-01T10:15:30Z or
+/// 2020-05-01T15:45:30.5+05:30, in to seconds since the unix epoch, which is the timestamp of a Fix.
+/// A date and time without a time zone is taken as UTC.
+pub fn parse_timestamp(text: &str) -> Result<f64, GeofenceError> {
+    let error = || GeofenceError::Parse(format!("'{}' is not an ISO 8601 date and time", text));
+    let (date, time) = text.trim().split_once(['T', 't', ' ']).ok_or_else(error)?;
+    let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0.0)
+    } else if let Some(pos) = time.rfind(['+', '-']) {
+        let sign = if time[pos..].starts_with('-') {
+            -1.0
+        } else {
+            1.0
+        };
+        let zone = time[pos + 1..].replace(':', "");
+        let hours: f64 = zone
+            .get(..2)
+            .and_then(|hours| hours.parse().ok())
+            .ok_or_else(error)?;
+        let minutes: f64 = match zone.get(2..) {
+            Some("") | None => 0.0,
+            Some(minutes) => minutes.parse().map_err(|_| error())?,
+        };
+        (&time[..pos], sign * (hours * 3600.0 + minutes * 60.0))
+    } else {
+        (time, 0.0)
+    };
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|part| part.parse().map_err(|_| error()))
+        .collect::<Result<Vec<i64>, GeofenceError>>()?;
+    let time: Vec<f64> = time
+        .split(':')
+        .map(|part| part.parse().map_err(|_| error()))
+        .collect::<Result<Vec<f64>, GeofenceError>>()?;
+    if date.len() != 3
+        || time.len() != 3
+        || !(1..=12).contains(&date[1])
+        || !(1..=31).contains(&date[2])
+        || !(0.0..24.0).contains(&time[0])
+        || !(0.0..60.0).contains(&time[1])
+        || !(0.0..61.0).contains(&time[2])
+    {
+        return Err(error());
+    }
+    let days = days_from_civil(date[0], date[1], date[2]) as f64;
+    Ok(days * 86_400.0 + time[0] * 3600.0 + time[1] * 60.0 + time[2] - offset)
+}
+
+/// Center and radius of a circular fence.
+/// When the unit is not given, the radius is in latitude / longitude degrees and the circle is
+/// evaluated on a plane. When the unit is given, the radius is in that unit and the circle is
+/// evaluated on the surface of the earth, using the given model (a sphere by default).
+#[derive(Deserialize, Debug, Clone)]
 pub struct CircleCoordinates {
     pub lat: f64,
     pub lon: f64,
     pub rad: f64,
+    #[serde(default)]
+    pub unit: Option<Unit>,
+    #[serde(default)]
+    pub model: Option<Model>,
 }
 
 impl CircleCoordinates {
     pub fn new(lat: f64, lon: f64, rad: f64) -> CircleCoordinates {
-        CircleCoordinates { lat, lon, rad }
+        CircleCoordinates {
+            lat,
+            lon,
+            rad,
+            unit: None,
+            model: None,
+        }
+    }
+
+    /// Creates the circle with the radius given in real world units,
+    /// evaluated on the surface of the earth with the given model.
+    pub fn geodesic(lat: f64, lon: f64, rad: f64, unit: Unit, model: Model) -> CircleCoordinates {
+        CircleCoordinates {
+            lat,
+            lon,
+            rad,
+            unit: Some(unit),
+            model: Some(model),
+        }
+    }
+
+    /// Returns the radius in meters, or None if the circle is planar.
+    pub fn radius_in_meters(&self) -> Option<f64> {
+        self.unit.map(|unit| unit.to_meters(self.rad))
+    }
+}
+
+/// Path and half width of a corridor fence, such as the planned route of a truck.
+/// The half width is the distance allowed on either side of the path, in meters unless the
+/// unit is given.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CorridorCoordinates {
+    pub path: Vec<Coordinates>,
+    pub half_width: f64,
+    #[serde(default)]
+    pub unit: Option<Unit>,
+}
+
+impl CorridorCoordinates {
+    pub fn new(path: Vec<Coordinates>, half_width: f64) -> CorridorCoordinates {
+        CorridorCoordinates {
+            path,
+            half_width,
+            unit: None,
+        }
     }
-    pub fn display(self) {
-        println!(
-            "Circle Coordinates Latitude {} , Longitude {} and Radius {} ",
-            self.lat, self.lon, self.rad
+
+    /// Returns the half width in meters.
+    pub fn half_width_in_meters(&self) -> f64 {
+        self.unit.unwrap_or(Unit::Meters).to_meters(self.half_width)
+    }
+}
+
+/// Latitude and longitude limits of a rectangular fence. The box crosses the antimeridian
+/// when min_lon is greater than max_lon, and then goes east from min_lon to max_lon.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct RectangleCoordinates {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+/// Smallest latitude / longitude aligned box that encloses a fence.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64) -> BoundingBox {
+        BoundingBox {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+        }
+    }
+
+    /// Builds the bounding box enclosing all the given coordinates.
+    pub fn from_points(points: &[Coordinates]) -> BoundingBox {
+        let mut bbox = BoundingBox::new(
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
         );
+        for point in points {
+            bbox.min_lat = bbox.min_lat.min(point.lat);
+            bbox.min_lon = bbox.min_lon.min(point.lon);
+            bbox.max_lat = bbox.max_lat.max(point.lat);
+            bbox.max_lon = bbox.max_lon.max(point.lon);
+        }
+        bbox
+    }
+
+    /// Returns true if the given coordinates are inside (or on the border of) the box.
+    pub fn contains(&self, point: &Coordinates) -> bool {
+        point.lat >= self.min_lat
+            && point.lat <= self.max_lat
+            && point.lon >= self.min_lon
+            && point.lon <= self.max_lon
     }
 }
 
+/// Returns the orientation of point3 with respect to the line going from point1 to point2,
+/// using latitude as x and longitude as y.
+/// The result is positive if the three points turn counter clockwise, negative if they turn
+/// clockwise and exactly 0 if they are on the same line. The sign is always exact, as the
+/// calculation uses adaptive precision arithmetic.
+pub fn orientation(point1: &Coordinates, point2: &Coordinates, point3: &Coordinates) -> f64 {
+    robust::orient2d(
+        robust::Coord {
+            x: point1.lat,
+            y: point1.lon,
+        },
+        robust::Coord {
+            x: point2.lat,
+            y: point2.lon,
+        },
+        robust::Coord {
+            x: point3.lat,
+            y: point3.lon,
+        },
+    )
+}
+
+/// Returns the planar distance between two coordinates.
+pub fn distance(point1: &Coordinates, point2: &Coordinates) -> f64 {
+    (point1.lat - point2.lat).hypot(point1.lon - point2.lon)
+}
+
+/// Returns the point of the line segment that starts at point1 and ends at point2,
+/// which is nearest to the given point.
+pub fn nearest_point_on_segment(
+    point: &Coordinates,
+    point1: &Coordinates,
+    point2: &Coordinates,
+) -> Coordinates {
+    let d_lat = point2.lat - point1.lat;
+    let d_lon = point2.lon - point1.lon;
+    let length = d_lat * d_lat + d_lon * d_lon;
+    if length == 0.0 {
+        return point1.clone();
+    }
+    // Project the point on to the segment and clamp it between both the ends.
+    let t = (((point.lat - point1.lat) * d_lat + (point.lon - point1.lon) * d_lon) / length)
+        .clamp(0.0, 1.0);
+    Coordinates::new(point1.lat + t * d_lat, point1.lon + t * d_lon)
+}
+
+/// Returns the planar distance between the given point and the line segment
+/// that starts at point1 and ends at point2.
+pub fn distance_to_segment(point: &Coordinates, point1: &Coordinates, point2: &Coordinates) -> f64 {
+    distance(point, &nearest_point_on_segment(point, point1, point2))
+}
+
+/// Returns the point of the edges nearest to the given point, along with its planar distance,
+/// or None when there are no edges.
+pub fn nearest_point_on_edges<'a, I>(point: &Coordinates, edges: I) -> Option<(Coordinates, f64)>
+where
+    I: IntoIterator<Item = (&'a Coordinates, &'a Coordinates)>,
+{
+    edges
+        .into_iter()
+        .map(|(point1, point2)| {
+            let nearest = nearest_point_on_segment(point, point1, point2);
+            let distance = distance(point, &nearest);
+            (nearest, distance)
+        })
+        .min_by(|(_, distance1), (_, distance2)| distance1.total_cmp(distance2))
+}
+
 #[derive(Deserialize, Debug)]
 pub struct MovingTracker {
     pub _comment: String,
     pub vehicle: String,
+    /// Filled in from the "shape" field, which is the tag of loader::FenceFile.
+    #[serde(default)]
     pub shape: String,
     pub shape_coordinate: Vec<Coordinates>,
-    pub moving_coordinate: Vec<Coordinates>,
+    /// Holes of a Polygon fence, each one is a list of coordinates.
+    #[serde(default)]
+    pub interior_coordinate: Vec<Vec<Coordinates>>,
+    #[serde(default)]
+    pub fill_rule: FillRule,
+    /// Positions of the vehicle, each one can optionally have the timestamp,
+    /// accuracy, speed, heading and altitude along with the latitude and longitude.
+    pub moving_coordinate: Vec<Fix>,
+}
+
+/// Outer ring and holes of one of the polygons in a MultiPolygon fence.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PolygonCoordinates {
+    pub exterior: Vec<Coordinates>,
+    #[serde(default)]
+    pub interior: Vec<Vec<Coordinates>>,
 }
 
 #[derive(Deserialize, Debug)]
-pub struct MovingTrackerCircle {
+pub struct MovingTrackerMultiPolygon {
     pub _comment: String,
     pub vehicle: String,
+    /// Filled in from the "shape" field, which is the tag of loader::FenceFile.
+    #[serde(default)]
     pub shape: String,
-    pub shape_coordinate: CircleCoordinates,
-    pub moving_coordinate: Vec<Coordinates>,
+    pub shape_coordinate: Vec<PolygonCoordinates>,
+    #[serde(default)]
+    pub fill_rule: FillRule,
+    pub moving_coordinate: Vec<Fix>,
 }
 
-pub fn display_underline(str: &str, colour: Colour) {
-    match colour {
-        Colour::Red => println!("{}", ansi_term::Colour::Red.bold().underline().paint(str)),
-        Colour::Yellow => println!(
-            "{}",
-            ansi_term::Colour::Yellow.bold().underline().paint(str)
-        ),
-        Colour::Blue => println!("{}", ansi_term::Colour::Blue.bold().underline().paint(str)),
-        Colour::Purple => println!(
-            "{}",
-            ansi_term::Colour::Purple.bold().underline().paint(str)
-        ),
-        Colour::Black => println!("{}", ansi_term::Colour::Black.bold().underline().paint(str)),
-    }
+#[derive(Deserialize, Debug)]
+pub struct MovingTrackerCircle {
+    pub _comment: String,
+    pub vehicle: String,
+    /// Filled in from the "shape" field, which is the tag of loader::FenceFile.
+    #[serde(default)]
+    pub shape: String,
+    pub shape_coordinate: CircleCoordinates,
+    pub moving_coordinate: Vec<Fix>,
 }
 
-pub fn display_bold(str: &str, colour: Colour) {
-    match colour {
-        Colour::Red => println!("{}", ansi_term::Colour::Red.bold().paint(str)),
-        Colour::Yellow => println!("{}", ansi_term::Colour::Yellow.bold().paint(str)),
-        Colour::Blue => println!("{}", ansi_term::Colour::Blue.bold().paint(str)),
-        Colour::Purple => println!("{}", ansi_term::Colour::Purple.bold().paint(str)),
-        Colour::Black => println!("{}", ansi_term::Colour::Black.bold().paint(str)),
-    }
+#[derive(Deserialize, Debug)]
+pub struct MovingTrackerCorridor {
+    pub _comment: String,
+    pub vehicle: String,
+    /// Filled in from the "shape" field, which is the tag of loader::FenceFile.
+    #[serde(default)]
+    pub shape: String,
+    pub shape_coordinate: CorridorCoordinates,
+    pub moving_coordinate: Vec<Fix>,
 }
 
-pub fn display(str: &str, colour: Colour) {
-    match colour {
-        Colour::Red => println!("{}", ansi_term::Colour::Red.paint(str)),
-        Colour::Yellow => println!("{}", ansi_term::Colour::Yellow.paint(str)),
-        Colour::Blue => println!("{}", ansi_term::Colour::Blue.paint(str)),
-        Colour::Purple => println!("{}", ansi_term::Colour::Purple.paint(str)),
-        Colour::Black => println!("{}", str),
-    }
+#[derive(Deserialize, Debug)]
+pub struct MovingTrackerRectangle {
+    pub _comment: String,
+    pub vehicle: String,
+    /// Filled in from the "shape" field, which is the tag of loader::FenceFile.
+    #[serde(default)]
+    pub shape: String,
+    pub shape_coordinate: RectangleCoordinates,
+    pub moving_coordinate: Vec<Fix>,
 }
 
 //pub fn get_current_time()->Instant{
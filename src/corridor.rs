@@ -0,0 +1,321 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// This module will create Corridor fence, the area within a half width in meters on either side
+/// of a path (a buffered polyline), such as the planned route of a truck. A vehicle that leaves
+/// the corridor has left its route.
+/// The segments of the path are great circle arcs, and the distances are measured on a sphere,
+/// so a coordinate is inside the fence when its cross track distance from the nearest segment
+/// is less than the half width.
+///
+use coordinates::*;
+use crossing::{circle_intersections, edge_intersections, point_at};
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::{BoundaryDistance, Geofence};
+use geodesy;
+use geofencer::track;
+use loader::{data_path, read_corridor_tracker_file};
+use reporter::{Colour, Reporter};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Corridor fence built from a path of at least 2 coordinates and a half width in meters.
+#[derive(Debug, Clone)]
+pub struct CorridorFence {
+    path: Vec<Coordinates>,
+    half_width: f64,
+}
+
+impl CorridorFence {
+    /// Creates the fence from the path and the half width in meters.
+    /// Returns GeofenceError::InvalidGeometry if there are less than 2 coordinates in the path,
+    /// or if the half width is not a positive number.
+    pub fn new(path: Vec<Coordinates>, half_width: f64) -> Result<CorridorFence, GeofenceError> {
+        if path.len() < 2 {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "a corridor fence needs at least 2 coordinates in its path, but {} were supplied",
+                path.len()
+            )));
+        }
+        if !(half_width.is_finite() && half_width > 0.0) {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "the half width of a corridor fence must be positive, but {} was supplied",
+                half_width
+            )));
+        }
+        Ok(CorridorFence { path, half_width })
+    }
+
+    /// Creates the fence from the path and the half width in the unit of the coordinates.
+    pub fn from_coordinates(corridor: CorridorCoordinates) -> Result<CorridorFence, GeofenceError> {
+        let half_width = corridor.half_width_in_meters();
+        CorridorFence::new(corridor.path, half_width)
+    }
+
+    /// Returns the coordinates of the path, the center line of the corridor.
+    pub fn path(&self) -> &[Coordinates] {
+        &self.path
+    }
+
+    /// Returns the half width of the corridor in meters.
+    pub fn half_width(&self) -> f64 {
+        self.half_width
+    }
+
+    /// Returns the length of the path in meters.
+    pub fn length(&self) -> f64 {
+        self.path
+            .windows(2)
+            .map(|pair| geodesy::haversine(&pair[0], &pair[1]))
+            .sum()
+    }
+
+    /// Returns the point of the path nearest to the given coordinates, its distance in meters,
+    /// and the segment of the path it is on.
+    fn nearest(&self, point: &Coordinates) -> (Coordinates, f64, usize) {
+        self.path
+            .windows(2)
+            .enumerate()
+            .map(|(segment, pair)| {
+                let (nearest, distance) = geodesy::nearest_point_on_arc(point, &pair[0], &pair[1]);
+                (nearest, distance, segment)
+            })
+            .min_by(|(_, distance1, _), (_, distance2, _)| distance1.total_cmp(distance2))
+            .unwrap_or((self.path[0].clone(), 0.0, 0))
+    }
+
+    /// Returns the point of the path nearest to the given coordinates, along with its distance
+    /// in meters.
+    pub fn nearest_on_centerline(&self, point: &Coordinates) -> (Coordinates, f64) {
+        let (nearest, distance, _) = self.nearest(point);
+        (nearest, distance)
+    }
+
+    /// Returns the distance in meters from the given coordinates to the path.
+    pub fn distance_to_centerline(&self, point: &Coordinates) -> f64 {
+        self.nearest(point).1
+    }
+}
+
+impl Geofence for CorridorFence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        let distance = self.distance_to_centerline(point);
+        if distance < self.half_width {
+            Location::Inside
+        } else if distance == self.half_width {
+            Location::OnBoundary
+        } else {
+            Location::Outside
+        }
+    }
+
+    /// The box of the arcs of the path widened by the half width. The arcs of long segments
+    /// bulge towards the poles, past the latitudes of their ends. The box spans every longitude
+    /// when the corridor goes across the antimeridian, as the box cannot go past 180.
+    fn bounding_box(&self) -> BoundingBox {
+        let bbox = BoundingBox::from_points(&self.path);
+        let (south, north) =
+            self.path
+                .windows(2)
+                .fold((bbox.min_lat, bbox.max_lat), |(south, north), segment| {
+                    let (arc_south, arc_north) =
+                        geodesy::arc_latitude_range(&segment[0], &segment[1]);
+                    (south.min(arc_south), north.max(arc_north))
+                });
+        let d_lat = geodesy::meters_to_lat_degrees(self.half_width);
+        let d_lon = geodesy::meters_to_lon_degrees(self.half_width, south.abs().max(north.abs()));
+        let (west, east) = (bbox.min_lon - d_lon, bbox.max_lon + d_lon);
+        let across = self
+            .path
+            .windows(2)
+            .any(|segment| (segment[1].lon - segment[0].lon).abs() > 180.0);
+        if across || west < -180.0 || east > 180.0 {
+            return BoundingBox::new(south - d_lat, -180.0, north + d_lat, 180.0);
+        }
+        BoundingBox::new(south - d_lat, west, north + d_lat, east)
+    }
+
+    /// Area in square meters, of a band along the path with round ends. The overlap at the
+    /// turns of the path is not taken out.
+    fn area(&self) -> f64 {
+        2.0 * self.half_width * self.length() + PI * self.half_width * self.half_width
+    }
+
+    /// The distance is in meters.
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        (self.distance_to_centerline(point) - self.half_width).max(0.0)
+    }
+
+    /// The coordinates are projected on to a plane in meters around the start of the segment,
+    /// where the border of the corridor is made of the sides of each segment of the path and
+    /// the circles around its vertices. On the plane the segments of the path are straight,
+    /// which puts the border about a meter away from the arcs for segments of a few kilometers.
+    /// The longitudes are taken the shorter way round from the start, so that a path near the
+    /// antimeridian stays next to it on the plane.
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        let lat_scale = geodesy::meters_to_lat_degrees(1.0);
+        let lon_scale = geodesy::meters_to_lon_degrees(1.0, start.lat);
+        let project = |point: &Coordinates| {
+            Coordinates::new(
+                (point.lat - start.lat) / lat_scale,
+                geodesy::lon_difference(start.lon, point.lon) / lon_scale,
+            )
+        };
+        let path: Vec<Coordinates> = self.path.iter().map(project).collect();
+        let (from, to) = (project(start), project(end));
+        let mut sides = Vec::new();
+        for pair in path.windows(2) {
+            let (d_lat, d_lon) = (pair[1].lat - pair[0].lat, pair[1].lon - pair[0].lon);
+            let length = d_lat.hypot(d_lon);
+            if length == 0.0 {
+                continue;
+            }
+            // Offset of the sides from the segment, at right angles to it.
+            let (n_lat, n_lon) = (
+                -d_lon / length * self.half_width,
+                d_lat / length * self.half_width,
+            );
+            for sign in &[1.0, -1.0] {
+                let offset = |point: &Coordinates| {
+                    Coordinates::new(point.lat + sign * n_lat, point.lon + sign * n_lon)
+                };
+                sides.push((offset(&pair[0]), offset(&pair[1])));
+            }
+        }
+        let mut fractions = edge_intersections(
+            &from,
+            &to,
+            sides.iter().map(|(point1, point2)| (point1, point2)),
+        );
+        for vertex in &path {
+            fractions.extend(circle_intersections(&from, &to, vertex, self.half_width));
+        }
+        // A side or a circle can be inside the band of another segment of the path, where it is
+        // not a part of the border.
+        fractions.retain(|&fraction| {
+            let point = point_at(&from, &to, fraction);
+            let distance = path
+                .windows(2)
+                .map(|pair| distance_to_segment(&point, &pair[0], &pair[1]))
+                .fold(f64::INFINITY, f64::min);
+            distance >= self.half_width * (1.0 - 1e-9)
+        });
+        fractions
+    }
+
+    /// The distance is in meters. The nearest point is the half width away from the nearest
+    /// point of the path, towards the coordinates, or at right angles to the path when the
+    /// coordinates are on it.
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        let (center, distance, segment) = self.nearest(point);
+        let direction = if distance > 0.0 {
+            geodesy::bearing(&center, point)
+        } else {
+            geodesy::bearing(&self.path[segment], &self.path[segment + 1]) + FRAC_PI_2
+        };
+        BoundaryDistance {
+            distance: distance - self.half_width,
+            nearest: geodesy::destination(&center, direction, self.half_width),
+        }
+    }
+}
+
+/// The below function reads the incoming json file which is of the format
+///{
+//  "shape": "Corridor",
+//  "vehicle": "truck",
+//  "shape_coordinate":{"path": [{"lat": 45.5,"lon": -122.7},{"lat": 45.5,"lon": -122.6}],
+//                      "half_width": 200.0, "unit": "meters"},
+//  "moving_coordinate": [{"lat":45.5 ,"lon": -122.65}]
+//}
+/// shape_coordinate -> contains the path and the half width which are used to create the corridor fence
+/// unit -> optional, the unit of the half width, meters by default
+/// moving_coordinate -> contains series of lat, lon for which location has to be determined.
+/// maps it to the MovingTrackerCorridor struct and returns the result to the caller.
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates
+/// Returns Result -> contains either MovingTrackerCorridor struct if success or Error if any failure.
+fn read_movingtracker_file(filename: &str) -> Result<MovingTrackerCorridor, GeofenceError> {
+    read_corridor_tracker_file(data_path(filename))
+}
+
+/// The function does the below
+/// Reads the input file
+/// Creates corridor fence based on the path and half width given in the input file
+/// Executes set of coordinates for which position has to be determined.
+/// Prints if the coordinates are within or outside the fence, and returns the evaluation of each of them
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates
+/// reporter -> prints the fence and the evaluations
+///
+pub fn execute_corridor(
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = CorridorFence::from_coordinates(u.shape_coordinate)?;
+    reporter.display_bold(
+        &format!(
+            "Created Corridor Fence, {} meters either side of the path",
+            fence.half_width()
+        ),
+        Colour::Blue,
+    );
+    reporter.display(&format!("{:?}", fence.path()), Colour::Blue);
+    Ok(track(
+        &fence,
+        filename,
+        &u.vehicle,
+        &u.moving_coordinate,
+        reporter,
+    ))
+}
+
+/// The function does the below
+/// Reads the input file
+/// Creates corridor fence based on the path and half width given in the input file
+/// checks if the given coordinates in the function paramater is within the corridor fence.
+/// Prints if the coordinates are within or outside the fence
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates which is necessary to create the fence
+/// lat and lon -> search coordinates
+/// reporter -> prints the fence and the result
+pub fn contains_in_corridor(
+    filename: &str,
+    lat: f64,
+    lon: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = CorridorFence::from_coordinates(u.shape_coordinate)?;
+    let point: Coordinates = Coordinates::new(lat, lon);
+    let mut retval = false;
+    reporter.display_bold(
+        &format!(
+            "Searching the vehicle in Corridor Fence, {} meters either side of the path",
+            fence.half_width()
+        ),
+        Colour::Blue,
+    );
+    reporter.display(&format!("{:?}", fence.path()), Colour::Blue);
+    let mut str = "is out of the fence";
+    if fence.contains(&point) {
+        str = "is inside the fence";
+        retval = true;
+    }
+    reporter.display(
+        &format!(
+            "The {} positioned at latitude {}, longitude {}, {} meters from the path, {} ",
+            u.vehicle,
+            lat,
+            lon,
+            fence.distance_to_centerline(&point).round(),
+            &str
+        ),
+        Colour::Black,
+    );
+    Ok(retval)
+}
@@ -0,0 +1,231 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Treats two consecutive fixes of a vehicle as a straight segment, and finds where the segment
+/// crosses the border of a fence. A vehicle that is sampled every minute can cut through the
+/// corner of a small fence with both of its fixes outside the fence, which checking the fixes on
+/// their own misses.
+/// Each crossing has the point where the segment meets the border, and the time at that point,
+/// interpolated between the timestamps of the two fixes.
+///
+use coordinates::*;
+use fence::Geofence;
+use geodesy;
+
+/// Fractions closer than this are taken to be the same point of the segment.
+const EPSILON: f64 = 1e-12;
+
+/// Direction in which the segment crosses the border of the fence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CrossingKind {
+    Enter,
+    Exit,
+}
+
+/// Point where the segment between two fixes crosses the border of a fence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossing {
+    pub kind: CrossingKind,
+    /// Point of the border where the segment crosses it.
+    pub point: Coordinates,
+    /// Position of the point along the segment, 0 at the first fix and 1 at the second fix.
+    pub fraction: f64,
+    /// Time of the crossing, in seconds since the Unix epoch, interpolated between the
+    /// timestamps of the fixes. None when either of the fixes has no timestamp.
+    pub timestamp: Option<f64>,
+}
+
+/// Returns the point at the given fraction of the segment from start to end.
+pub fn point_at(start: &Coordinates, end: &Coordinates, fraction: f64) -> Coordinates {
+    Coordinates::new(
+        start.lat + (end.lat - start.lat) * fraction,
+        start.lon + (end.lon - start.lon) * fraction,
+    )
+}
+
+/// Returns the fractions of the segment from start to end at which it meets the edges, with
+/// latitude and longitude taken as planar coordinates.
+/// When the segment runs along an edge, the fractions of the ends of the edge are returned.
+pub fn edge_intersections<'a, I>(start: &Coordinates, end: &Coordinates, edges: I) -> Vec<f64>
+where
+    I: IntoIterator<Item = (&'a Coordinates, &'a Coordinates)>,
+{
+    let cross = |x1: f64, y1: f64, x2: f64, y2: f64| x1 * y2 - y1 * x2;
+    let (dx, dy) = (end.lon - start.lon, end.lat - start.lat);
+    let mut fractions = Vec::new();
+    for (point1, point2) in edges {
+        let (ex, ey) = (point2.lon - point1.lon, point2.lat - point1.lat);
+        let (px, py) = (point1.lon - start.lon, point1.lat - start.lat);
+        let denominator = cross(dx, dy, ex, ey);
+        if denominator != 0.0 {
+            let fraction = cross(px, py, ex, ey) / denominator;
+            let along_edge = cross(px, py, dx, dy) / denominator;
+            if (0.0..=1.0).contains(&fraction) && (0.0..=1.0).contains(&along_edge) {
+                fractions.push(fraction);
+            }
+        } else if cross(px, py, dx, dy) == 0.0 && (dx != 0.0 || dy != 0.0) {
+            // The edge is on the line of the segment.
+            let length = dx * dx + dy * dy;
+            for point in &[point1, point2] {
+                let fraction =
+                    ((point.lon - start.lon) * dx + (point.lat - start.lat) * dy) / length;
+                if (0.0..=1.0).contains(&fraction) {
+                    fractions.push(fraction);
+                }
+            }
+        }
+    }
+    fractions
+}
+
+/// Returns the fractions of the segment from start to end at which it meets the circle with
+/// the given center and radius, with latitude and longitude taken as planar coordinates.
+pub fn circle_intersections(
+    start: &Coordinates,
+    end: &Coordinates,
+    center: &Coordinates,
+    radius: f64,
+) -> Vec<f64> {
+    // |start + fraction * (end - start) - center| = radius is a quadratic equation.
+    let (dx, dy) = (end.lon - start.lon, end.lat - start.lat);
+    let (fx, fy) = (start.lon - center.lon, start.lat - center.lat);
+    let a = dx * dx + dy * dy;
+    let b = 2.0 * (fx * dx + fy * dy);
+    let c = fx * fx + fy * fy - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return Vec::new();
+    }
+    let root = discriminant.sqrt();
+    vec![(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+        .into_iter()
+        .filter(|fraction| (0.0..=1.0).contains(fraction))
+        .collect()
+}
+
+/// Returns the fractions of the segment from start to end at which it meets the border of the
+/// fence. The end of a segment that goes across the antimeridian is past 180, so the segment is
+/// also intersected a full turn round, for the part of it on the other side of 180.
+fn intersections(fence: &dyn Geofence, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+    let on_earth = |start: &Coordinates, end: &Coordinates, fraction: &f64| {
+        (0.0..=1.0).contains(fraction)
+            && (-180.0..=180.0).contains(&point_at(start, end, *fraction).lon)
+    };
+    let mut fractions: Vec<f64> = fence
+        .segment_intersections(start, end)
+        .into_iter()
+        .filter(|fraction| on_earth(start, end, fraction))
+        .collect();
+    if end.lon.abs() > 180.0 {
+        let turn = if end.lon > 180.0 { -360.0 } else { 360.0 };
+        let start = Coordinates::new(start.lat, start.lon + turn);
+        let end = Coordinates::new(end.lat, end.lon + turn);
+        fractions.extend(
+            fence
+                .segment_intersections(&start, &end)
+                .into_iter()
+                .filter(|fraction| on_earth(&start, &end, fraction)),
+        );
+    }
+    fractions
+}
+
+/// Returns the crossings of the border of the fence by the segment from one fix to the next,
+/// in the order they happen. Both fixes can be outside the fence, when the segment cuts
+/// through it, in which case there is an Enter followed by an Exit.
+/// The segment is a straight line in latitude / longitude, which goes the shorter way round
+/// the earth, across the antimeridian when the fixes are more than 180 degrees of longitude apart.
+/// A segment that starts on the border comes from the side it moves away from, so it is an
+/// Enter when it moves in to the fence and an Exit when it moves out of the fence.
+pub fn segment_crossings(fence: &dyn Geofence, from: &Fix, to: &Fix) -> Vec<Crossing> {
+    crossings(fence, from, to, None)
+}
+
+/// Returns the crossings of the segment, see segment_crossings. A segment that starts on the
+/// border is taken to be inside the fence before it starts when inside_before is true, and
+/// outside the fence when it is false.
+fn crossings(
+    fence: &dyn Geofence,
+    from: &Fix,
+    to: &Fix,
+    inside_before: Option<bool>,
+) -> Vec<Crossing> {
+    let start = from.coordinates();
+    let mut end = to.coordinates();
+    if end.lon - start.lon > 180.0 {
+        end.lon -= 360.0;
+    } else if end.lon - start.lon < -180.0 {
+        end.lon += 360.0;
+    }
+    let at = |fraction: f64| {
+        let point = point_at(&start, &end, fraction);
+        Coordinates::new(point.lat, geodesy::wrap_lon(point.lon))
+    };
+    let mut fractions = intersections(fence, &start, &end);
+    fractions.push(0.0);
+    fractions.push(1.0);
+    fractions.sort_by(|fraction1, fraction2| fraction1.total_cmp(fraction2));
+    fractions.dedup_by(|fraction1, fraction2| *fraction1 - *fraction2 < EPSILON);
+
+    // The segment is either inside or outside the fence between two consecutive fractions,
+    // which is checked at the middle of them.
+    let mut inside = match fence.locate(&start) {
+        Location::OnBoundary => inside_before.unwrap_or_else(|| {
+            fence.locate(&at((fractions[0] + fractions[1]) / 2.0)) != Location::Inside
+        }),
+        location => location != Location::Outside,
+    };
+    let mut crossings = Vec::new();
+    let mut crossing = |kind, fraction: f64| {
+        let timestamp = match (from.timestamp, to.timestamp) {
+            (Some(time1), Some(time2)) => Some(time1 + (time2 - time1) * fraction),
+            _ => None,
+        };
+        crossings.push(Crossing {
+            kind,
+            point: at(fraction),
+            fraction,
+            timestamp,
+        });
+    };
+    for pair in fractions.windows(2) {
+        let middle = fence.contains(&at((pair[0] + pair[1]) / 2.0));
+        if middle != inside {
+            let kind = if middle {
+                CrossingKind::Enter
+            } else {
+                CrossingKind::Exit
+            };
+            crossing(kind, pair[0]);
+            inside = middle;
+        }
+    }
+    // The segment can end on the border, after running outside the fence.
+    if fence.contains(&at(1.0)) && !inside {
+        crossing(CrossingKind::Enter, 1.0);
+    }
+    crossings
+}
+
+/// Returns true if any point of the segment from one fix to the next is in the fence.
+pub fn intersects(fence: &dyn Geofence, from: &Fix, to: &Fix) -> bool {
+    fence.contains(&from.coordinates()) || !segment_crossings(fence, from, to).is_empty()
+}
+
+/// Returns the crossings of the border of the fence along the whole track, taking each pair of
+/// consecutive fixes as a segment. A fix on the border that was reached by the previous segment
+/// is inside the fence, so only the first segment is judged by the direction it moves in when
+/// it starts on the border.
+pub fn track_crossings(fence: &dyn Geofence, fixes: &[Fix]) -> Vec<Crossing> {
+    fixes
+        .windows(2)
+        .enumerate()
+        .flat_map(|(index, pair)| {
+            let inside_before = if index == 0 { None } else { Some(true) };
+            crossings(fence, &pair[0], &pair[1], inside_before)
+        })
+        .collect()
+}
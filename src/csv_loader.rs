@@ -0,0 +1,324 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads tracks and fences from CSV files, such as telematics exports, with configurable column
+/// names. The rows are read one at a time, so files of several gigabytes can be streamed through
+/// the tracker (track_csv) without loading them in to memory.
+/// Track files have a row per position: vehicle, latitude, longitude and optionally the timestamp
+/// (seconds since the unix epoch or ISO 8601), speed, heading, altitude and accuracy.
+/// Fence files have either a WKT column with the geometry of each fence, or a row per vertex
+/// with the id of the fence it belongs to.
+///
+extern crate csv;
+extern crate serde_derive;
+
+use self::csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter};
+use self::serde_derive::Deserialize;
+use coordinates::*;
+use error::GeofenceError;
+use fence::Fence;
+use polygon::{open_ring, PolygonFence};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracker::{Event, Tracker};
+use wkt::read_wkt;
+
+/// Names of the columns of a track CSV file.
+/// vehicle, lat and lon are required, the other columns are only read when the file has them.
+/// The mapping can be kept in a json config, where any column that is left out has its default name.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TrackColumns {
+    pub vehicle: String,
+    pub lat: String,
+    pub lon: String,
+    pub timestamp: String,
+    pub speed: String,
+    pub heading: String,
+    pub altitude: String,
+    pub accuracy: String,
+    pub delimiter: char,
+}
+
+impl Default for TrackColumns {
+    fn default() -> TrackColumns {
+        TrackColumns {
+            vehicle: "vehicle".to_string(),
+            lat: "lat".to_string(),
+            lon: "lon".to_string(),
+            timestamp: "timestamp".to_string(),
+            speed: "speed".to_string(),
+            heading: "heading".to_string(),
+            altitude: "altitude".to_string(),
+            accuracy: "accuracy".to_string(),
+            delimiter: ',',
+        }
+    }
+}
+
+/// Names of the columns of a fence CSV file.
+/// When the file has the wkt column, each row is a fence. Otherwise each row is a vertex, and
+/// the vertices with the same id make up a polygon fence, in the order of the rows.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FenceColumns {
+    pub id: String,
+    pub wkt: String,
+    pub lat: String,
+    pub lon: String,
+    pub delimiter: char,
+}
+
+impl Default for FenceColumns {
+    fn default() -> FenceColumns {
+        FenceColumns {
+            id: "id".to_string(),
+            wkt: "wkt".to_string(),
+            lat: "lat".to_string(),
+            lon: "lon".to_string(),
+            delimiter: ',',
+        }
+    }
+}
+
+fn parse_error(record: &StringRecord, reason: String) -> GeofenceError {
+    let line = record.position().map_or(0, |position| position.line());
+    GeofenceError::Parse(format!("CSV line {}: {}", line, reason))
+}
+
+/// Creates the csv reader with the given delimiter, the first row is the header.
+fn csv_reader<R: Read>(reader: R, delimiter: char) -> Result<csv::Reader<R>, GeofenceError> {
+    if !delimiter.is_ascii() {
+        return Err(GeofenceError::Parse(format!(
+            "CSV: the delimiter '{}' is not an ascii character",
+            delimiter
+        )));
+    }
+    Ok(ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .trim(csv::Trim::All)
+        .from_reader(reader))
+}
+
+/// Returns the position of the column in the header.
+fn column(header: &StringRecord, name: &str) -> Option<usize> {
+    header.iter().position(|column| column == name)
+}
+
+/// Returns the position of the column in the header, which the file must have.
+fn required_column(header: &StringRecord, name: &str) -> Result<usize, GeofenceError> {
+    column(header, name)
+        .ok_or_else(|| GeofenceError::Parse(format!("CSV: the file has no '{}' column", name)))
+}
+
+/// Returns the number in the given column of the row, None when the column is empty.
+fn number(record: &StringRecord, pos: Option<usize>) -> Result<Option<f64>, GeofenceError> {
+    match pos.and_then(|pos| record.get(pos)) {
+        None | Some("") => Ok(None),
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| parse_error(record, format!("'{}' is not a number", value))),
+    }
+}
+
+/// Returns the number in the given column of the row, which must not be empty.
+fn required_number(record: &StringRecord, pos: usize) -> Result<f64, GeofenceError> {
+    number(record, Some(pos))?
+        .ok_or_else(|| parse_error(record, format!("column {} is empty", pos + 1)))
+}
+
+/// Positions of the columns of a track file.
+struct TrackIndexes {
+    vehicle: usize,
+    lat: usize,
+    lon: usize,
+    timestamp: Option<usize>,
+    speed: Option<usize>,
+    heading: Option<usize>,
+    altitude: Option<usize>,
+    accuracy: Option<usize>,
+}
+
+/// Streams the rows of a track CSV file, as the vehicle and the fix of each row.
+pub struct TrackRecords<R: Read> {
+    records: StringRecordsIntoIter<R>,
+    indexes: TrackIndexes,
+}
+
+impl<R: Read> TrackRecords<R> {
+    fn read_record(&self, record: &StringRecord) -> Result<(String, Fix), GeofenceError> {
+        let indexes = &self.indexes;
+        let vehicle = record.get(indexes.vehicle).unwrap_or_default().to_string();
+        let mut fix = Fix::new(
+            required_number(record, indexes.lat)?,
+            required_number(record, indexes.lon)?,
+        );
+        fix.speed = number(record, indexes.speed)?;
+        fix.heading = number(record, indexes.heading)?;
+        fix.altitude = number(record, indexes.altitude)?;
+        fix.accuracy = number(record, indexes.accuracy)?;
+        fix.timestamp = match indexes.timestamp.and_then(|pos| record.get(pos)) {
+            None | Some("") => None,
+            Some(time) => Some(match time.parse() {
+                Ok(seconds) => seconds,
+                Err(_) => parse_timestamp(time)
+                    .map_err(|_| parse_error(record, format!("'{}' is not a timestamp", time)))?,
+            }),
+        };
+        Ok((vehicle, fix))
+    }
+}
+
+impl<R: Read> Iterator for TrackRecords<R> {
+    type Item = Result<(String, Fix), GeofenceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(error) => return Some(Err(error.into())),
+        };
+        Some(self.read_record(&record))
+    }
+}
+
+/// Reads the header of the track CSV from the reader, and returns the rows as a stream.
+/// Returns GeofenceError::Parse if the vehicle, lat or lon column is missing.
+pub fn read_track_records<R: Read>(
+    reader: R,
+    columns: &TrackColumns,
+) -> Result<TrackRecords<R>, GeofenceError> {
+    let mut reader = csv_reader(reader, columns.delimiter)?;
+    let header = reader.headers()?.clone();
+    let indexes = TrackIndexes {
+        vehicle: required_column(&header, &columns.vehicle)?,
+        lat: required_column(&header, &columns.lat)?,
+        lon: required_column(&header, &columns.lon)?,
+        timestamp: column(&header, &columns.timestamp),
+        speed: column(&header, &columns.speed),
+        heading: column(&header, &columns.heading),
+        altitude: column(&header, &columns.altitude),
+        accuracy: column(&header, &columns.accuracy),
+    };
+    Ok(TrackRecords {
+        records: reader.into_records(),
+        indexes,
+    })
+}
+
+/// Reads the track CSV file at the given path, and returns the rows as a stream.
+pub fn read_track_records_file<P: AsRef<Path>>(
+    path: P,
+    columns: &TrackColumns,
+) -> Result<TrackRecords<File>, GeofenceError> {
+    read_track_records(File::open(path)?, columns)
+}
+
+/// Reads the track CSV from the reader, and returns the track of each vehicle, in the order the
+/// vehicles first appear in the file. The fixes of a vehicle are kept in the order of the rows.
+/// The whole file is loaded in to memory, use read_track_records or track_csv to stream it.
+pub fn read_tracks<R: Read>(
+    reader: R,
+    columns: &TrackColumns,
+) -> Result<Vec<(String, Vec<Fix>)>, GeofenceError> {
+    let mut tracks: Vec<(String, Vec<Fix>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for record in read_track_records(reader, columns)? {
+        let (vehicle, fix) = record?;
+        let pos = *positions.entry(vehicle.clone()).or_insert_with(|| {
+            tracks.push((vehicle, Vec::new()));
+            tracks.len() - 1
+        });
+        tracks[pos].1.push(fix);
+    }
+    Ok(tracks)
+}
+
+/// Reads the track CSV file at the given path, and returns the track of each vehicle.
+pub fn read_tracks_file<P: AsRef<Path>>(
+    path: P,
+    columns: &TrackColumns,
+) -> Result<Vec<(String, Vec<Fix>)>, GeofenceError> {
+    read_tracks(File::open(path)?, columns)
+}
+
+/// Streams the rows of the track CSV through the tracker, one row at a time, and returns the
+/// Enter, Exit and Dwell events of all the vehicles.
+pub fn track_csv<R: Read>(
+    reader: R,
+    columns: &TrackColumns,
+    tracker: &mut Tracker,
+) -> Result<Vec<Event>, GeofenceError> {
+    let mut events = Vec::new();
+    for record in read_track_records(reader, columns)? {
+        let (vehicle, fix) = record?;
+        events.extend(tracker.update_fix(&vehicle, &fix));
+    }
+    Ok(events)
+}
+
+/// Reads the fence CSV from the reader, and returns the fences along with their ids, in the
+/// order they first appear in the file. A vertex repeated at the end to close the ring of a
+/// fence is dropped, as in WKT and GeoJSON.
+/// Returns GeofenceError::InvalidGeometry if a WKT geometry is not a polygon or multipolygon,
+/// or if a fence has less than 3 vertices.
+pub fn read_fences<R: Read>(
+    reader: R,
+    columns: &FenceColumns,
+) -> Result<Vec<(String, Fence)>, GeofenceError> {
+    let mut reader = csv_reader(reader, columns.delimiter)?;
+    let header = reader.headers()?.clone();
+    let id = required_column(&header, &columns.id)?;
+    if let Some(wkt) = column(&header, &columns.wkt) {
+        let mut fences = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            let fence_id = record.get(id).unwrap_or_default().to_string();
+            let fence = read_wkt(record.get(wkt).unwrap_or_default())?
+                .into_fence()
+                .ok_or_else(|| {
+                    GeofenceError::InvalidGeometry(format!(
+                        "the WKT of fence '{}' is not a polygon or multipolygon",
+                        fence_id
+                    ))
+                })?;
+            fences.push((fence_id, fence));
+        }
+        return Ok(fences);
+    }
+
+    let lat = required_column(&header, &columns.lat)?;
+    let lon = required_column(&header, &columns.lon)?;
+    let mut vertices: Vec<(String, Vec<Coordinates>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let fence_id = record.get(id).unwrap_or_default().to_string();
+        let point = Coordinates::new(
+            required_number(&record, lat)?,
+            required_number(&record, lon)?,
+        );
+        let pos = *positions.entry(fence_id.clone()).or_insert_with(|| {
+            vertices.push((fence_id, Vec::new()));
+            vertices.len() - 1
+        });
+        vertices[pos].1.push(point);
+    }
+    vertices
+        .into_iter()
+        .map(|(fence_id, points)| Ok((fence_id, PolygonFence::new(open_ring(points))?.into())))
+        .collect()
+}
+
+/// Reads the fence CSV file at the given path, and returns the fences along with their ids.
+pub fn read_fences_file<P: AsRef<Path>>(
+    path: P,
+    columns: &FenceColumns,
+) -> Result<Vec<(String, Fence)>, GeofenceError> {
+    read_fences(File::open(path)?, columns)
+}
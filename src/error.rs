@@ -0,0 +1,83 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Error type returned by the public functions of this crate, instead of panicking,
+/// so that a bad fence file does not bring down the caller.
+///
+use coordinates::Shape;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum GeofenceError {
+    /// A file or stream could not be opened, read or written, for example a fence file,
+    /// a track file or the output of the command.
+    Io(io::Error),
+    /// The fence file is not valid json or does not have the expected structure.
+    Json(serde_json::Error),
+    /// The csv file could not be read, for example a row with a missing column.
+    Csv(csv::Error),
+    /// The fence file describes a different shape than the one requested,
+    /// for example a circle file passed as Shape::Polygon.
+    ShapeMismatch { expected: Shape, found: String },
+    /// The coordinates cannot form a proper fence,
+    /// for example a polygon with less than 3 coordinates.
+    InvalidGeometry(String),
+    /// The text or binary encoding of a fence or track could not be parsed,
+    /// for example malformed WKT.
+    Parse(String),
+}
+
+impl fmt::Display for GeofenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeofenceError::Io(error) => write!(f, "I/O error: {}", error),
+            GeofenceError::Json(error) => write!(
+                f,
+                "Problem reading the json file, check if it has correct json values: {}",
+                error
+            ),
+            GeofenceError::Csv(error) => write!(f, "Problem reading the csv file: {}", error),
+            GeofenceError::ShapeMismatch { expected, found } => write!(
+                f,
+                "The json file describes a {} fence, but a {} fence was expected",
+                found, expected
+            ),
+            GeofenceError::InvalidGeometry(reason) => write!(f, "Invalid fence: {}", reason),
+            GeofenceError::Parse(reason) => write!(f, "Problem parsing the fence: {}", reason),
+        }
+    }
+}
+
+impl Error for GeofenceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GeofenceError::Io(error) => Some(error),
+            GeofenceError::Json(error) => Some(error),
+            GeofenceError::Csv(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GeofenceError {
+    fn from(error: io::Error) -> GeofenceError {
+        GeofenceError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for GeofenceError {
+    fn from(error: serde_json::Error) -> GeofenceError {
+        GeofenceError::Json(error)
+    }
+}
+
+impl From<csv::Error> for GeofenceError {
+    fn from(error: csv::Error) -> GeofenceError {
+        GeofenceError::Csv(error)
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Structured result of checking the positions of a vehicle against a fence, returned by the
+/// execute functions instead of formatted sentences. The results can be serialized (for example
+/// to json with serde_json), and printing them is left to the reporter (see reporter::Reporter).
+///
+extern crate serde_derive;
+
+use self::serde_derive::Serialize;
+use coordinates::*;
+use fence::Geofence;
+use std::fmt;
+
+/// Position of a vehicle evaluated against a fence.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub vehicle: String,
+    pub fence_id: String,
+    pub position: Fix,
+    /// Inside, on the border of (on_boundary) or outside the fence.
+    pub location: Location,
+    /// Signed distance from the position to the border of the fence, negative inside and
+    /// positive outside the fence (see Geofence::boundary_distance). It is in latitude /
+    /// longitude degrees, and in meters for the fences whose size is given in meters
+    /// (geodesic circles and corridors).
+    pub distance: f64,
+}
+
+impl Evaluation {
+    /// Returns true if the position is inside (or on the border of) the fence.
+    pub fn is_inside(&self) -> bool {
+        self.location != Location::Outside
+    }
+}
+
+/// Formats the evaluation as the sentence printed by the simulator, for example
+/// "The car positioned at latitude 1, longitude 1, is inside the fence".
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self.location {
+            Location::Inside => "is inside the fence",
+            Location::OnBoundary => "is on the border of the fence",
+            Location::Outside => "is out of the fence",
+        };
+        write!(
+            f,
+            "The {} positioned at latitude {}, longitude {}, {}",
+            self.vehicle, self.position.lat, self.position.lon, status
+        )
+    }
+}
+
+/// Evaluates each position of the vehicle against the fence, in order.
+pub fn evaluate(
+    fence: &dyn Geofence,
+    fence_id: &str,
+    vehicle: &str,
+    positions: &[Fix],
+) -> Vec<Evaluation> {
+    positions
+        .iter()
+        .map(|position| {
+            let point = position.coordinates();
+            Evaluation {
+                vehicle: vehicle.to_string(),
+                fence_id: fence_id.to_string(),
+                position: position.clone(),
+                location: fence.locate(&point),
+                distance: fence.boundary_distance(&point).distance,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,229 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// The Geofence trait is the common interface implemented by every fence shape
+/// (CircleFence, TriangleFence, PolygonFence, MultiPolygonFence, CorridorFence and
+/// RectangleFence), so that fences of different shapes can be stored and queried together,
+/// for example in a Vec<Box<dyn Geofence>>.
+/// The Fence enum holds a fence of any shape, for fences that are read from files where
+/// the shape is only known at run time.
+///
+use circle::CircleFence;
+use coordinates::*;
+use corridor::CorridorFence;
+use error::GeofenceError;
+use geodesy;
+use geodesy::Model;
+use multipolygon::MultiPolygonFence;
+use polygon::PolygonFence;
+use rectangle::RectangleFence;
+use triangle::TriangleFence;
+use validity::Defect;
+
+pub trait Geofence {
+    /// Returns if the given coordinates are inside, outside or on the border of the fence.
+    fn locate(&self, point: &Coordinates) -> Location;
+
+    /// Returns true if the given coordinates are inside (or on the border of) the fence.
+    fn contains(&self, point: &Coordinates) -> bool {
+        self.locate(point) != Location::Outside
+    }
+
+    /// Returns the smallest latitude / longitude aligned box that encloses the fence.
+    fn bounding_box(&self) -> BoundingBox;
+
+    /// Returns the area covered by the fence, in the same units as its coordinates.
+    fn area(&self) -> f64;
+
+    /// Returns the distance from the given coordinates to the fence.
+    /// The distance is 0 when the coordinates are inside the fence.
+    fn distance_to(&self, point: &Coordinates) -> f64;
+
+    /// Returns the fractions of the segment from start to end (0 at start and 1 at end) at
+    /// which it meets the border of the fence, in any order.
+    /// Used by crossing::segment_crossings to find where a vehicle entered or left the fence.
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64>;
+
+    /// Returns the signed distance from the given coordinates to the border of the fence,
+    /// negative inside and positive outside the fence, along with the nearest point of the border.
+    /// The distance is in the same units as the coordinates of the fence, and in meters for
+    /// circular fences whose radius is in meters.
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance;
+
+    /// Returns the signed distance in meters from the given coordinates to the border of the
+    /// fence, measured on the earth with the given model to the nearest point of the border.
+    fn boundary_distance_meters(&self, point: &Coordinates, model: Model) -> BoundaryDistance {
+        let boundary = self.boundary_distance(point);
+        let meters = geodesy::distance(point, &boundary.nearest, model);
+        BoundaryDistance {
+            distance: if boundary.distance < 0.0 {
+                -meters
+            } else {
+                meters
+            },
+            nearest: boundary.nearest,
+        }
+    }
+}
+
+/// Signed distance from a point to the border of a fence, negative when the point is inside
+/// the fence, 0 on the border and positive outside.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundaryDistance {
+    pub distance: f64,
+    /// Point of the border nearest to the point.
+    pub nearest: Coordinates,
+}
+
+impl BoundaryDistance {
+    /// Creates the signed distance from the unsigned distance to the nearest point of the
+    /// border, negative when the point is inside the fence.
+    pub fn new(distance: f64, nearest: Coordinates, inside: bool) -> BoundaryDistance {
+        BoundaryDistance {
+            distance: if inside { -distance } else { distance },
+            nearest,
+        }
+    }
+}
+
+/// Fence of any of the supported shapes.
+#[derive(Debug, Clone)]
+pub enum Fence {
+    Polygon(PolygonFence),
+    Circle(CircleFence),
+    Triangle(TriangleFence),
+    MultiPolygon(MultiPolygonFence),
+    Corridor(CorridorFence),
+    Rectangle(RectangleFence),
+}
+
+impl Fence {
+    /// Returns the shape of the fence.
+    pub fn shape(&self) -> Shape {
+        match self {
+            Fence::Polygon(_) => Shape::Polygon,
+            Fence::Circle(_) => Shape::Circle,
+            Fence::Triangle(_) => Shape::Triangle,
+            Fence::MultiPolygon(_) => Shape::MultiPolygon,
+            Fence::Corridor(_) => Shape::Corridor,
+            Fence::Rectangle(_) => Shape::Rectangle,
+        }
+    }
+
+    /// Returns the fence as a Geofence trait object.
+    pub fn as_geofence(&self) -> &dyn Geofence {
+        match self {
+            Fence::Polygon(fence) => fence,
+            Fence::Circle(fence) => fence,
+            Fence::Triangle(fence) => fence,
+            Fence::MultiPolygon(fence) => fence,
+            Fence::Corridor(fence) => fence,
+            Fence::Rectangle(fence) => fence,
+        }
+    }
+
+    /// Returns the defects of the rings of the fence, see validity::Defect.
+    /// Circular, corridor and rectangular fences have no rings, so they have no defects.
+    pub fn validate(&self) -> Vec<Defect> {
+        match self {
+            Fence::Polygon(fence) => fence.validate(),
+            Fence::Circle(_) => Vec::new(),
+            Fence::Triangle(fence) => fence.validate(),
+            Fence::MultiPolygon(fence) => fence.validate(),
+            Fence::Corridor(_) | Fence::Rectangle(_) => Vec::new(),
+        }
+    }
+
+    /// Returns the repaired fence. A polygon stays a polygon unless it has to be split in to
+    /// several polygons. A triangle with defects cannot be repaired, and returns
+    /// GeofenceError::InvalidGeometry.
+    pub fn repair(&self) -> Result<Fence, GeofenceError> {
+        match self {
+            Fence::Polygon(fence) => {
+                let repaired = fence.repair()?;
+                if repaired.polygons().len() == 1 {
+                    return Ok(repaired.polygons()[0].clone().into());
+                }
+                Ok(repaired.into())
+            }
+            Fence::Triangle(fence) => match fence.validate().first() {
+                Some(defect) => Err(GeofenceError::InvalidGeometry(format!(
+                    "the triangle fence cannot be repaired, {}",
+                    defect
+                ))),
+                None => Ok(self.clone()),
+            },
+            Fence::Circle(_) | Fence::Corridor(_) | Fence::Rectangle(_) => Ok(self.clone()),
+            Fence::MultiPolygon(fence) => Ok(fence.repair()?.into()),
+        }
+    }
+}
+
+impl Geofence for Fence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        self.as_geofence().locate(point)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.as_geofence().bounding_box()
+    }
+
+    fn area(&self) -> f64 {
+        self.as_geofence().area()
+    }
+
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        self.as_geofence().distance_to(point)
+    }
+
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        self.as_geofence().segment_intersections(start, end)
+    }
+
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        self.as_geofence().boundary_distance(point)
+    }
+
+    fn boundary_distance_meters(&self, point: &Coordinates, model: Model) -> BoundaryDistance {
+        self.as_geofence().boundary_distance_meters(point, model)
+    }
+}
+
+impl From<PolygonFence> for Fence {
+    fn from(fence: PolygonFence) -> Fence {
+        Fence::Polygon(fence)
+    }
+}
+
+impl From<CircleFence> for Fence {
+    fn from(fence: CircleFence) -> Fence {
+        Fence::Circle(fence)
+    }
+}
+
+impl From<TriangleFence> for Fence {
+    fn from(fence: TriangleFence) -> Fence {
+        Fence::Triangle(fence)
+    }
+}
+
+impl From<MultiPolygonFence> for Fence {
+    fn from(fence: MultiPolygonFence) -> Fence {
+        Fence::MultiPolygon(fence)
+    }
+}
+
+impl From<CorridorFence> for Fence {
+    fn from(fence: CorridorFence) -> Fence {
+        Fence::Corridor(fence)
+    }
+}
+
+impl From<RectangleFence> for Fence {
+    fn from(fence: RectangleFence) -> Fence {
+        Fence::Rectangle(fence)
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Distances on the surface of the earth, used by the fences whose size is given in real world
+/// units (meters, kilometers, miles ...) instead of latitude / longitude degrees.
+/// The earth can either be treated as a sphere (haversine formula) or as the
+/// WGS84 ellipsoid (Vincenty's inverse formula).
+///
+extern crate serde_derive;
+
+use self::serde_derive::{Deserialize, Serialize};
+use coordinates::{BoundingBox, Coordinates};
+use error::GeofenceError;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::str::FromStr;
+
+/// Mean radius of the earth in meters.
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Semi-major axis of the WGS84 ellipsoid in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// Flattening of the WGS84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Smallest radius of curvature of the WGS84 ellipsoid, along the meridian at the equator.
+/// A distance in meters spans the most degrees on a sphere of this radius.
+const WGS84_MIN_RADIUS: f64 = WGS84_A * (1.0 - WGS84_F) * (1.0 - WGS84_F);
+
+/// Units in which the radius (or width) of a geographic fence can be given.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    #[serde(alias = "m")]
+    Meters,
+    #[serde(alias = "km")]
+    Kilometers,
+    #[serde(alias = "mi")]
+    Miles,
+    #[serde(alias = "nmi")]
+    NauticalMiles,
+    #[serde(alias = "ft")]
+    Feet,
+}
+
+impl Unit {
+    /// Converts the value given in this unit to meters.
+    pub fn to_meters(self, value: f64) -> f64 {
+        match self {
+            Unit::Meters => value,
+            Unit::Kilometers => value * 1000.0,
+            Unit::Miles => value * 1609.344,
+            Unit::NauticalMiles => value * 1852.0,
+            Unit::Feet => value * 0.3048,
+        }
+    }
+}
+
+/// Parses the unit from the same names that are accepted in the json files.
+impl FromStr for Unit {
+    type Err = GeofenceError;
+
+    fn from_str(unit: &str) -> Result<Unit, GeofenceError> {
+        match unit.trim().to_ascii_lowercase().as_str() {
+            "meters" | "m" => Ok(Unit::Meters),
+            "kilometers" | "km" => Ok(Unit::Kilometers),
+            "miles" | "mi" => Ok(Unit::Miles),
+            "nautical_miles" | "nmi" => Ok(Unit::NauticalMiles),
+            "feet" | "ft" => Ok(Unit::Feet),
+            _ => Err(GeofenceError::Parse(format!("unknown unit '{}'", unit))),
+        }
+    }
+}
+
+/// Shape of the earth used to calculate the distances.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Model {
+    /// Earth is a sphere, distances are calculated with the haversine formula.
+    #[default]
+    Sphere,
+    /// Earth is the WGS84 ellipsoid, distances are calculated with Vincenty's formula.
+    Ellipsoid,
+}
+
+/// Parses the model from the same names that are accepted in the json files.
+impl FromStr for Model {
+    type Err = GeofenceError;
+
+    fn from_str(model: &str) -> Result<Model, GeofenceError> {
+        match model.trim().to_ascii_lowercase().as_str() {
+            "sphere" => Ok(Model::Sphere),
+            "ellipsoid" => Ok(Model::Ellipsoid),
+            _ => Err(GeofenceError::Parse(format!("unknown model '{}'", model))),
+        }
+    }
+}
+
+/// Returns the great circle distance in meters between the two coordinates, using the haversine formula.
+pub fn haversine(point1: &Coordinates, point2: &Coordinates) -> f64 {
+    let lat1 = point1.lat.to_radians();
+    let lat2 = point2.lat.to_radians();
+    let d_lat = lat2 - lat1;
+    let d_lon = (point2.lon - point1.lon).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+}
+
+/// Returns the distance in meters between the two coordinates on the WGS84 ellipsoid,
+/// using Vincenty's inverse formula.
+/// Returns None when the formula does not converge, which happens for nearly antipodal points.
+pub fn vincenty(point1: &Coordinates, point2: &Coordinates) -> Option<f64> {
+    let b = WGS84_A * (1.0 - WGS84_F);
+    let l = (point2.lon - point1.lon).to_radians();
+    let u1 = ((1.0 - WGS84_F) * point1.lat.to_radians().tan()).atan();
+    let u2 = ((1.0 - WGS84_F) * point2.lat.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // Both the coordinates are the same point.
+            return Some(0.0);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        // On the equator cos_sq_alpha is 0, and so is cos_2sigma_m.
+        let cos_2sigma_m = if cos_sq_alpha != 0.0 {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        } else {
+            0.0
+        };
+        let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * WGS84_F
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+        if (lambda - previous).abs() < 1e-12 {
+            let u_sq = cos_sq_alpha * (WGS84_A * WGS84_A - b * b) / (b * b);
+            let big_a =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - big_b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+            return Some(b * big_a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// Returns the distance in meters between the two coordinates for the given model of the earth.
+/// Falls back to the haversine formula when Vincenty's formula does not converge.
+pub fn distance(point1: &Coordinates, point2: &Coordinates, model: Model) -> f64 {
+    match model {
+        Model::Sphere => haversine(point1, point2),
+        Model::Ellipsoid => vincenty(point1, point2).unwrap_or_else(|| haversine(point1, point2)),
+    }
+}
+
+/// Returns the number of latitude degrees that span the given meters on the earth.
+pub fn meters_to_lat_degrees(meters: f64) -> f64 {
+    (meters / EARTH_RADIUS) * 180.0 / PI
+}
+
+/// Returns the number of longitude degrees that span the given meters at the given latitude.
+/// Near the poles a single meter can span every longitude, so the result is capped at 180.
+pub fn meters_to_lon_degrees(meters: f64, lat: f64) -> f64 {
+    let cos_lat = lat.to_radians().cos();
+    if cos_lat <= f64::EPSILON {
+        return 180.0;
+    }
+    (meters_to_lat_degrees(meters) / cos_lat).min(180.0)
+}
+
+/// Returns the longitude moved in to the range from -180 to 180.
+pub fn wrap_lon(lon: f64) -> f64 {
+    if (-180.0..=180.0).contains(&lon) {
+        lon
+    } else {
+        (lon + 540.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+/// Returns the difference in degrees from the first longitude to the second, going round the
+/// shorter way, from -180 up to but not including 180.
+pub fn lon_difference(lon1: f64, lon2: f64) -> f64 {
+    (lon2 - lon1 + 540.0).rem_euclid(360.0) - 180.0
+}
+
+/// Returns the box enclosing every coordinate within the given meters of the center, for the
+/// given model of the earth. The box is slightly larger than the circle, so that no coordinate
+/// of the circle is left out of it. It spans every longitude when the circle goes over a pole
+/// or across the antimeridian, as the box cannot go past 180.
+pub fn circle_bounding_box(center: &Coordinates, meters: f64, model: Model) -> BoundingBox {
+    let radius = match model {
+        Model::Sphere => EARTH_RADIUS,
+        Model::Ellipsoid => WGS84_MIN_RADIUS,
+    };
+    // Angle between the center and the circle, padded for the rounding of the distances.
+    let angle = meters / radius * (1.0 + 1e-6) + 1e-9;
+    let lat = center.lat.to_radians();
+    let (south, north) = (lat - angle, lat + angle);
+    if south <= -FRAC_PI_2 || north >= FRAC_PI_2 {
+        return BoundingBox::new(
+            south.to_degrees().max(-90.0),
+            -180.0,
+            north.to_degrees().min(90.0),
+            180.0,
+        );
+    }
+    // The circle is widest east and west of the center where it touches the meridians at
+    // asin(sin(angle) / cos(lat)), which is a little north or south of the center.
+    let d_lon = (angle.sin() / lat.cos()).min(1.0).asin().to_degrees();
+    let (west, east) = (center.lon - d_lon, center.lon + d_lon);
+    if west < -180.0 || east > 180.0 {
+        return BoundingBox::new(south.to_degrees(), -180.0, north.to_degrees(), 180.0);
+    }
+    BoundingBox::new(south.to_degrees(), west, north.to_degrees(), east)
+}
+
+/// Returns the initial bearing of the great circle from point1 to point2, in radians clockwise
+/// from the north.
+pub fn bearing(point1: &Coordinates, point2: &Coordinates) -> f64 {
+    let lat1 = point1.lat.to_radians();
+    let lat2 = point2.lat.to_radians();
+    let d_lon = (point2.lon - point1.lon).to_radians();
+    let y = d_lon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+    y.atan2(x)
+}
+
+/// Returns the lowest and the highest latitudes of the great circle arc from point1 to point2,
+/// in degrees. An arc that heads towards a pole at both ends, seen from each end, goes past the
+/// latitudes of its ends up to the vertex of its great circle, whose latitude is given by
+/// Clairaut's relation.
+pub fn arc_latitude_range(point1: &Coordinates, point2: &Coordinates) -> (f64, f64) {
+    let mut south = point1.lat.min(point2.lat);
+    let mut north = point1.lat.max(point2.lat);
+    let forward = bearing(point1, point2);
+    let backward = bearing(point2, point1);
+    let vertex = (forward.sin() * point1.lat.to_radians().cos())
+        .abs()
+        .min(1.0)
+        .acos()
+        .to_degrees();
+    if forward.cos() > 0.0 && backward.cos() > 0.0 {
+        north = north.max(vertex);
+    } else if forward.cos() < 0.0 && backward.cos() < 0.0 {
+        south = south.min(-vertex);
+    }
+    (south, north)
+}
+
+/// Returns the coordinates reached by going the given meters from the point along the great
+/// circle with the given bearing (in radians clockwise from the north), on a sphere.
+pub fn destination(point: &Coordinates, bearing: f64, meters: f64) -> Coordinates {
+    let angle = meters / EARTH_RADIUS;
+    let lat1 = point.lat.to_radians();
+    let lat2 = (lat1.sin() * angle.cos() + lat1.cos() * angle.sin() * bearing.cos()).asin();
+    let d_lon =
+        (bearing.sin() * angle.sin() * lat1.cos()).atan2(angle.cos() - lat1.sin() * lat2.sin());
+    // Keeps the longitude between -180 and 180 degrees.
+    let lon = (point.lon + d_lon.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+    Coordinates::new(lat2.to_degrees(), lon)
+}
+
+/// Returns the point of the great circle arc from point1 to point2 nearest to the given point,
+/// along with its distance in meters, on a sphere.
+/// The distance from the arc is the cross track distance, when the point is beside the arc,
+/// and the distance to the nearest end of the arc otherwise.
+pub fn nearest_point_on_arc(
+    point: &Coordinates,
+    point1: &Coordinates,
+    point2: &Coordinates,
+) -> (Coordinates, f64) {
+    let from_start = haversine(point1, point);
+    let length = haversine(point1, point2);
+    if length == 0.0 {
+        return (point1.clone(), from_start);
+    }
+    let angle = from_start / EARTH_RADIUS;
+    let arc_bearing = bearing(point1, point2);
+    let turn = bearing(point1, point) - arc_bearing;
+    let cross_track = (angle.sin() * turn.sin()).asin();
+    let along_track = (angle.sin() * turn.cos()).atan2(angle.cos()) * EARTH_RADIUS;
+    if along_track <= 0.0 {
+        (point1.clone(), from_start)
+    } else if along_track >= length {
+        (point2.clone(), haversine(point2, point))
+    } else {
+        (
+            destination(point1, arc_bearing, along_track),
+            cross_track.abs() * EARTH_RADIUS,
+        )
+    }
+}
@@ -9,28 +9,192 @@
 ///
 use circle::contains_in_circle;
 use circle::execute_circle;
-use coordinates::Shape;
+use coordinates::*;
+use corridor::{contains_in_corridor, execute_corridor};
+use csv_loader::{read_track_records_file, TrackColumns};
+use error::GeofenceError;
+use evaluation::{evaluate, Evaluation};
+use fence::{BoundaryDistance, Fence, Geofence};
+use gpx::read_gpx_file;
+use loader::*;
+use multipolygon::contains_in_multipolygon;
+use multipolygon::execute_multipolygon;
 use polygon::contains_in_polygon;
 use polygon::execute_polygon;
+use rectangle::{contains_in_rectangle, execute_rectangle};
+use reporter::{Colour, Reporter};
+use std::path::Path;
 use triangle::contains_in_triangle;
 use triangle::execute_triangle;
 
-/// Calls the execute method depending on the Shape
-pub fn execute(shape: Shape, filename: &str, delay: bool) -> String {
+/// Returns the shape named in the "shape" field of the json file in the data folder.
+pub fn detect_shape(filename: &str) -> Result<Shape, GeofenceError> {
+    detect_shape_file(data_path(filename))
+}
+
+/// Returns the given shape, or the shape named in the json file when no shape is given.
+fn shape_or_detect(shape: Option<Shape>, filename: &str) -> Result<Shape, GeofenceError> {
     match shape {
-        Shape::Polygon => execute_polygon(filename, delay),
-        Shape::Circle => execute_circle(filename, delay),
-        Shape::Triangle => execute_triangle(filename, delay),
+        Some(shape) => Ok(shape),
+        None => detect_shape(filename),
+    }
+}
+
+/// Calls the execute method depending on the Shape
+/// The shape can be None, to take it from the "shape" field of the json file. When a shape is
+/// given, the file must describe the same shape.
+/// The fence and the evaluations are printed with the reporter, use NoopReporter to print nothing.
+pub fn execute<S: Into<Option<Shape>>>(
+    shape: S,
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
+    match shape_or_detect(shape.into(), filename)? {
+        Shape::Polygon => execute_polygon(filename, reporter),
+        Shape::Circle => execute_circle(filename, reporter),
+        Shape::Triangle => execute_triangle(filename, reporter),
+        Shape::MultiPolygon => execute_multipolygon(filename, reporter),
+        Shape::Corridor => execute_corridor(filename, reporter),
+        Shape::Rectangle => execute_rectangle(filename, reporter),
     }
 }
 
 ///
 /// Calls the contains method depending on the Shape.
+/// The shape can be None, to take it from the "shape" field of the json file, for example
+/// contains(None, "circle_geofence.json", 3.0, 3.0, &NoopReporter).
+/// The fence and the result are printed with the reporter, use NoopReporter to print nothing.
 ///
-pub fn contains(shape: Shape, filename: &str, latitude: f64, longitude: f64) -> bool {
-    match shape {
-        Shape::Polygon => contains_in_polygon(filename, latitude, longitude),
-        Shape::Circle => contains_in_circle(filename, latitude, longitude),
-        Shape::Triangle => contains_in_triangle(filename, latitude, longitude),
+pub fn contains<S: Into<Option<Shape>>>(
+    shape: S,
+    filename: &str,
+    latitude: f64,
+    longitude: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
+    match shape_or_detect(shape.into(), filename)? {
+        Shape::Polygon => contains_in_polygon(filename, latitude, longitude, reporter),
+        Shape::Circle => contains_in_circle(filename, latitude, longitude, reporter),
+        Shape::Triangle => contains_in_triangle(filename, latitude, longitude, reporter),
+        Shape::MultiPolygon => contains_in_multipolygon(filename, latitude, longitude, reporter),
+        Shape::Corridor => contains_in_corridor(filename, latitude, longitude, reporter),
+        Shape::Rectangle => contains_in_rectangle(filename, latitude, longitude, reporter),
+    }
+}
+
+///
+/// Returns the signed distance from the coordinates to the border of the fence of the json
+/// file, negative inside and positive outside the fence, along with the nearest point of the
+/// border. The shape can be None, to take it from the "shape" field of the json file.
+///
+pub fn boundary_distance<S: Into<Option<Shape>>>(
+    shape: S,
+    filename: &str,
+    latitude: f64,
+    longitude: f64,
+) -> Result<BoundaryDistance, GeofenceError> {
+    let fence = read_fence(shape, filename)?;
+    Ok(fence.boundary_distance(&Coordinates::new(latitude, longitude)))
+}
+
+///
+/// Reads the json file in the data folder and creates the fence of the given shape from it.
+/// The shape can be None, to create the fence of the shape named in the file.
+///
+pub fn read_fence<S: Into<Option<Shape>>>(
+    shape: S,
+    filename: &str,
+) -> Result<Fence, GeofenceError> {
+    read_fence_file(shape, data_path(filename))
+}
+
+///
+/// Reads the json file at the given path and creates the fence of the given shape from it.
+/// The shape can be None, to create the fence of the shape named in the file.
+///
+pub fn read_fence_file<S: Into<Option<Shape>>, P: AsRef<Path>>(
+    shape: S,
+    path: P,
+) -> Result<Fence, GeofenceError> {
+    read_fence_json_file(path, shape.into())?.to_fence()
+}
+
+///
+/// Replays the tracks recorded in the GPX file against the fence of the json file, both in the
+/// data folder, the same way execute tracks the moving coordinates of the json file.
+/// The name of each track is used as the vehicle. The waypoints are evaluated after the tracks,
+/// each one on its own with its name as the vehicle.
+///
+pub fn execute_gpx<S: Into<Option<Shape>>>(
+    shape: S,
+    filename: &str,
+    gpx_filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
+    let fence = read_fence(shape, filename)?;
+    let gpx = read_gpx_file(data_path(gpx_filename))?;
+    reporter.display_bold(
+        &format!(
+            "Created {} Fence, from the file {}",
+            fence.shape(),
+            filename
+        ),
+        Colour::Blue,
+    );
+    let mut evaluations = Vec::new();
+    for (vehicle, fixes) in &gpx.into_tracks() {
+        evaluations.extend(track(&fence, filename, vehicle, fixes, reporter));
     }
+    Ok(evaluations)
+}
+
+///
+/// Tracks the vehicles of the CSV file against the fence of the json file, both in the data
+/// folder, the same way execute tracks the moving coordinates of the json file.
+/// The columns of the CSV file are mapped with the given column names.
+/// The rows are streamed from the file one at a time, so the evaluations are in the order of
+/// the rows, with the vehicles mixed as they are in the file.
+///
+pub fn execute_csv<S: Into<Option<Shape>>>(
+    shape: S,
+    filename: &str,
+    csv_filename: &str,
+    columns: &TrackColumns,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
+    let fence = read_fence(shape, filename)?;
+    let records = read_track_records_file(data_path(csv_filename), columns)?;
+    reporter.display_bold(
+        &format!(
+            "Created {} Fence, from the file {}",
+            fence.shape(),
+            filename
+        ),
+        Colour::Blue,
+    );
+    // The rows are evaluated one at a time as they are read, in the order of the file.
+    let mut evaluations = Vec::new();
+    for record in records {
+        let (vehicle, fix) = record?;
+        evaluations.extend(evaluate(&fence, filename, &vehicle, &[fix]));
+    }
+    reporter.display_evaluations(&evaluations);
+    Ok(evaluations)
+}
+
+///
+/// Tracks the moving coordinates of the vehicle against a fence that is already in memory.
+/// Returns the evaluation of each of the coordinates, and prints them with the reporter.
+/// Use evaluation::evaluate to get the evaluations without a reporter.
+///
+pub fn track(
+    fence: &dyn Geofence,
+    fence_id: &str,
+    vehicle: &str,
+    moving_coordinate: &[Fix],
+    reporter: &dyn Reporter,
+) -> Vec<Evaluation> {
+    let evaluations = evaluate(fence, fence_id, vehicle, moving_coordinate);
+    reporter.display_evaluations(&evaluations);
+    evaluations
 }
@@ -0,0 +1,522 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads and writes fences and tracks as GeoJSON, the format exported by most GIS tools.
+/// GeoJSON positions are [longitude, latitude], in that order, while the rest of the crate works
+/// with latitude first, so the axis order is swapped when reading and when writing.
+/// Polygon and MultiPolygon features are read as fences, and so are Point features that have a
+/// "radius" property, which are circular fences with the radius in meters unless the "unit"
+/// property says otherwise ("degrees" for a planar circle).
+/// LineString features that have a "half_width" property are corridor fences along the line, with
+/// the half width in meters unless the "unit" property says otherwise.
+/// Features with a "shape" property of "Rectangle" and a "bbox" member are rectangular fences,
+/// the bbox is [west, south, east, north] and crosses the antimeridian when west is greater
+/// than east.
+/// Other LineString and MultiPoint features, and Point features without a radius, are read as tracks.
+///
+extern crate serde_json;
+
+use self::serde_json::{Map, Value};
+use circle::CircleFence;
+use coordinates::*;
+use corridor::CorridorFence;
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::Fence;
+use geodesy::{Model, Unit};
+use multipolygon::MultiPolygonFence;
+use polygon::{open_ring, PolygonFence};
+use rectangle::RectangleFence;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+use triangle::TriangleFence;
+
+/// Fences and tracks read from a GeoJSON document, along with the ids of their features.
+#[derive(Debug, Clone, Default)]
+pub struct GeoJson {
+    pub fences: Vec<(String, Fence)>,
+    pub tracks: Vec<(String, Vec<Fix>)>,
+}
+
+impl GeoJson {
+    /// Returns the fences and tracks as a GeoJSON FeatureCollection.
+    pub fn to_geojson(&self) -> Value {
+        let features = self
+            .fences
+            .iter()
+            .map(|(id, fence)| fence_to_feature(id, fence))
+            .chain(
+                self.tracks
+                    .iter()
+                    .map(|(id, fixes)| track_to_feature(id, fixes)),
+            )
+            .collect();
+        feature_collection(features)
+    }
+}
+
+fn parse_error(reason: String) -> GeofenceError {
+    GeofenceError::Parse(format!("GeoJSON: {}", reason))
+}
+
+/// Reads a GeoJSON position, [lon, lat] or [lon, lat, altitude].
+/// The longitude and the latitude must be numbers, an altitude that is not a number is ignored.
+fn read_position(value: &Value) -> Result<Fix, GeofenceError> {
+    let numbers = value.as_array().map(Vec::as_slice).unwrap_or_default();
+    match numbers {
+        [lon, lat, rest @ ..] => match (lon.as_f64(), lat.as_f64()) {
+            (Some(lon), Some(lat)) => {
+                let mut fix = Fix::new(lat, lon);
+                fix.altitude = rest.first().and_then(Value::as_f64);
+                Ok(fix)
+            }
+            _ => Err(parse_error(format!(
+                "the longitude and the latitude of a position must be numbers, but {} was supplied",
+                value
+            ))),
+        },
+        _ => Err(parse_error(format!(
+            "a position needs a longitude and a latitude, but {} was supplied",
+            value
+        ))),
+    }
+}
+
+/// Reads a list of GeoJSON positions.
+fn read_positions(value: &Value) -> Result<Vec<Fix>, GeofenceError> {
+    value
+        .as_array()
+        .ok_or_else(|| {
+            parse_error(format!(
+                "a list of positions was expected, but {} was supplied",
+                value
+            ))
+        })?
+        .iter()
+        .map(read_position)
+        .collect()
+}
+
+/// Reads a GeoJSON linear ring. The position repeated at the end to close the ring is dropped,
+/// as the fences circle back to the first coordinate on their own.
+fn read_ring(value: &Value) -> Result<Vec<Coordinates>, GeofenceError> {
+    let ring = read_positions(value)?
+        .iter()
+        .map(Fix::coordinates)
+        .collect();
+    Ok(open_ring(ring))
+}
+
+/// Reads the rings of a GeoJSON polygon, the first ring is the outer ring and the rest are holes.
+fn read_polygon(value: &Value, fill_rule: FillRule) -> Result<PolygonFence, GeofenceError> {
+    let mut rings = value
+        .as_array()
+        .map(|rings| rings.iter().map(read_ring).collect())
+        .unwrap_or_else(|| Ok(Vec::new()))?;
+    if rings.is_empty() {
+        return Err(parse_error("a polygon needs at least 1 ring".to_string()));
+    }
+    let exterior = rings.remove(0);
+    Ok(PolygonFence::with_holes(exterior, rings)?.with_fill_rule(fill_rule))
+}
+
+/// Creates the circular fence of a Point feature with a radius.
+fn read_circle(
+    center: &Coordinates,
+    radius: f64,
+    properties: &Value,
+) -> Result<CircleFence, GeofenceError> {
+    let unit = match properties.get("unit") {
+        Some(Value::String(unit)) if unit == "degrees" => {
+            return CircleFence::from_center(center, radius)
+        }
+        Some(unit) => serde_json::from_value(unit.clone())?,
+        None => Unit::Meters,
+    };
+    let model = match properties.get("model") {
+        Some(model) => serde_json::from_value(model.clone())?,
+        None => Model::default(),
+    };
+    CircleFence::geodesic(center, radius, unit, model)
+}
+
+/// Sets the timestamps of the track from the "times" property, when there is one per fix.
+fn read_times(fixes: &mut [Fix], properties: &Value) {
+    if let Some(times) = properties.get("times").and_then(Value::as_array) {
+        if times.len() == fixes.len() {
+            for (fix, time) in fixes.iter_mut().zip(times) {
+                fix.timestamp = time.as_f64();
+            }
+        }
+    }
+}
+
+/// Returns the id of the feature, from its "id" member, its "name" property or its position
+/// in the document.
+fn feature_id(feature: &Value, properties: &Value, index: usize) -> String {
+    match feature.get("id") {
+        Some(Value::String(id)) => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => properties
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("feature-{}", index)),
+    }
+}
+
+/// Reads a GeoJSON geometry as a fence or a track, and adds it to the GeoJson.
+fn read_geometry(
+    geometry: &Value,
+    id: String,
+    properties: &Value,
+    geojson: &mut GeoJson,
+) -> Result<(), GeofenceError> {
+    let kind = geometry
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let coordinates = geometry.get("coordinates").unwrap_or(&Value::Null);
+    let fill_rule = match properties.get("fill_rule") {
+        Some(fill_rule) => serde_json::from_value(fill_rule.clone())?,
+        None => FillRule::default(),
+    };
+    match kind {
+        "Polygon" => {
+            let polygon = read_polygon(coordinates, fill_rule)?;
+            let is_triangle = properties
+                .get("shape")
+                .and_then(Value::as_str)
+                .is_some_and(|shape| shape.eq_ignore_ascii_case("triangle"));
+            let fence = if is_triangle && polygon.holes().is_empty() {
+                TriangleFence::from_points(polygon.points())?.into()
+            } else {
+                polygon.into()
+            };
+            geojson.fences.push((id, fence));
+        }
+        "MultiPolygon" => {
+            let polygons = coordinates
+                .as_array()
+                .map(|polygons| {
+                    polygons
+                        .iter()
+                        .map(|polygon| read_polygon(polygon, fill_rule))
+                        .collect()
+                })
+                .unwrap_or_else(|| Ok(Vec::new()))?;
+            let fence = MultiPolygonFence::new(polygons)?.with_fill_rule(fill_rule);
+            geojson.fences.push((id, fence.into()));
+        }
+        "Point" => {
+            let mut fixes = vec![read_position(coordinates)?];
+            match properties.get("radius").and_then(Value::as_f64) {
+                Some(radius) => {
+                    let fence = read_circle(&fixes[0].coordinates(), radius, properties)?;
+                    geojson.fences.push((id, fence.into()));
+                }
+                None => {
+                    read_times(&mut fixes, properties);
+                    geojson.tracks.push((id, fixes));
+                }
+            }
+        }
+        "LineString" if properties.get("half_width").is_some() => {
+            let path = read_positions(coordinates)?
+                .iter()
+                .map(Fix::coordinates)
+                .collect();
+            let half_width = match properties.get("half_width").and_then(Value::as_f64) {
+                Some(half_width) => half_width,
+                None => {
+                    return Err(parse_error(
+                        "the half_width of a corridor must be a number".to_string(),
+                    ))
+                }
+            };
+            let unit = match properties.get("unit") {
+                Some(unit) => Some(serde_json::from_value(unit.clone())?),
+                None => None,
+            };
+            let corridor = CorridorCoordinates {
+                path,
+                half_width,
+                unit,
+            };
+            let fence = CorridorFence::from_coordinates(corridor)?;
+            geojson.fences.push((id, fence.into()));
+        }
+        "LineString" | "MultiPoint" => {
+            let mut fixes = read_positions(coordinates)?;
+            read_times(&mut fixes, properties);
+            geojson.tracks.push((id, fixes));
+        }
+        _ => {
+            return Err(parse_error(format!(
+                "a geometry of type '{}' is not supported",
+                kind
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Reads a GeoJSON feature, features without a geometry are skipped.
+fn read_feature(feature: &Value, index: usize, geojson: &mut GeoJson) -> Result<(), GeofenceError> {
+    let properties = feature.get("properties").unwrap_or(&Value::Null);
+    let id = feature_id(feature, properties, index);
+    let is_rectangle = properties
+        .get("shape")
+        .and_then(Value::as_str)
+        .is_some_and(|shape| shape.eq_ignore_ascii_case("rectangle"));
+    if let (true, Some(bbox)) = (is_rectangle, feature.get("bbox")) {
+        let limits: Option<Vec<f64>> = bbox
+            .as_array()
+            .map(|limits| limits.iter().filter_map(Value::as_f64).collect());
+        return match limits.as_deref() {
+            Some(&[west, south, east, north]) => {
+                let fence = RectangleFence::new(south, west, north, east)?;
+                geojson.fences.push((id, fence.into()));
+                Ok(())
+            }
+            _ => Err(parse_error(
+                "the bbox of a rectangle must be [west, south, east, north]".to_string(),
+            )),
+        };
+    }
+    match feature.get("geometry") {
+        Some(geometry) if !geometry.is_null() => read_geometry(geometry, id, properties, geojson),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the GeoJSON contents from the reader, which can be a FeatureCollection, a single
+/// Feature or a bare geometry.
+/// Returns GeofenceError::Parse if the contents are not valid GeoJSON, for example a position
+/// without a latitude, and GeofenceError::InvalidGeometry if the coordinates cannot form a fence.
+pub fn read_geojson<R: Read>(reader: R) -> Result<GeoJson, GeofenceError> {
+    let value: Value = serde_json::from_reader(reader)?;
+    let mut geojson = GeoJson::default();
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            let features = value.get("features").and_then(Value::as_array);
+            for (index, feature) in features.into_iter().flatten().enumerate() {
+                read_feature(feature, index, &mut geojson)?;
+            }
+        }
+        Some("Feature") => read_feature(&value, 0, &mut geojson)?,
+        _ => read_geometry(
+            &value,
+            feature_id(&value, &Value::Null, 0),
+            &Value::Null,
+            &mut geojson,
+        )?,
+    }
+    Ok(geojson)
+}
+
+/// Reads the GeoJSON file at the given path.
+pub fn read_geojson_file<P: AsRef<Path>>(path: P) -> Result<GeoJson, GeofenceError> {
+    read_geojson(BufReader::new(File::open(path)?))
+}
+
+/// Returns the coordinates as a GeoJSON position, [lon, lat].
+fn position(point: &Coordinates) -> Value {
+    serde_json::json!([point.lon, point.lat])
+}
+
+/// Returns the fix as a GeoJSON position, with the altitude when it is known.
+fn fix_position(fix: &Fix) -> Value {
+    match fix.altitude {
+        Some(altitude) => serde_json::json!([fix.lon, fix.lat, altitude]),
+        None => serde_json::json!([fix.lon, fix.lat]),
+    }
+}
+
+/// Returns the ring as a GeoJSON linear ring, which repeats the first position at the end.
+fn ring(ring: &[Coordinates]) -> Value {
+    let mut positions: Vec<Value> = ring.iter().map(position).collect();
+    if let Some(first) = ring.first() {
+        positions.push(position(first));
+    }
+    Value::Array(positions)
+}
+
+/// Returns the rings of the polygon, the outer ring followed by the holes.
+fn polygon(fence: &PolygonFence) -> Value {
+    Value::Array(fence.rings().map(ring).collect())
+}
+
+fn feature(id: &str, geometry: Value, properties: Value) -> Value {
+    serde_json::json!({
+        "type": "Feature",
+        "id": id,
+        "geometry": geometry,
+        "properties": properties,
+    })
+}
+
+fn feature_collection(features: Vec<Value>) -> Value {
+    serde_json::json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// Returns the fence as a GeoJSON Feature with the given id.
+/// Circular fences are written as a Point with the radius and its unit in the properties,
+/// triangular fences as a Polygon with a "shape" property so they are read back as triangles,
+/// corridor fences as a LineString with the half width in meters in the properties, and
+/// rectangular fences as a Polygon (a MultiPolygon split at the antimeridian when they cross it)
+/// with a "bbox" member and a "shape" property so they are read back as rectangles.
+pub fn fence_to_feature(id: &str, fence: &Fence) -> Value {
+    let (geometry, properties) = match fence {
+        Fence::Polygon(fence) => (
+            serde_json::json!({ "type": "Polygon", "coordinates": polygon(fence) }),
+            serde_json::json!({ "name": id, "fill_rule": fence.fill_rule() }),
+        ),
+        Fence::MultiPolygon(fence) => (
+            serde_json::json!({
+                "type": "MultiPolygon",
+                "coordinates": fence.polygons().iter().map(polygon).collect::<Vec<Value>>(),
+            }),
+            serde_json::json!({ "name": id, "fill_rule": fence.fill_rule() }),
+        ),
+        Fence::Triangle(fence) => (
+            serde_json::json!({ "type": "Polygon", "coordinates": [ring(fence.vertices())] }),
+            serde_json::json!({ "name": id, "shape": Shape::Triangle.to_string() }),
+        ),
+        Fence::Corridor(fence) => (
+            serde_json::json!({
+                "type": "LineString",
+                "coordinates": fence.path().iter().map(position).collect::<Vec<Value>>(),
+            }),
+            serde_json::json!({ "name": id, "half_width": fence.half_width(), "unit": Unit::Meters }),
+        ),
+        Fence::Rectangle(fence) => {
+            // The four corners of a box always make a polygon, so there is nothing to report.
+            let polygons = fence.to_polygons().unwrap_or_default();
+            let geometry = match polygons.as_slice() {
+                [single] => {
+                    serde_json::json!({ "type": "Polygon", "coordinates": polygon(single) })
+                }
+                _ => serde_json::json!({
+                    "type": "MultiPolygon",
+                    "coordinates": polygons.iter().map(polygon).collect::<Vec<Value>>(),
+                }),
+            };
+            let mut feature = feature(
+                id,
+                geometry,
+                serde_json::json!({ "name": id, "shape": Shape::Rectangle.to_string() }),
+            );
+            feature["bbox"] = serde_json::json!([
+                fence.min_lon(),
+                fence.min_lat(),
+                fence.max_lon(),
+                fence.max_lat()
+            ]);
+            return feature;
+        }
+        Fence::Circle(fence) => {
+            let circle = fence.circle();
+            let center = Coordinates::new(circle.lat, circle.lon);
+            let properties = match circle.unit {
+                Some(unit) => serde_json::json!({
+                    "name": id,
+                    "radius": circle.rad,
+                    "unit": unit,
+                    "model": circle.model.unwrap_or_default(),
+                }),
+                None => serde_json::json!({ "name": id, "radius": circle.rad, "unit": "degrees" }),
+            };
+            (
+                serde_json::json!({ "type": "Point", "coordinates": position(&center) }),
+                properties,
+            )
+        }
+    };
+    feature(id, geometry, properties)
+}
+
+/// Returns the fences as a GeoJSON FeatureCollection.
+pub fn fences_to_geojson(fences: &[(String, Fence)]) -> Value {
+    feature_collection(
+        fences
+            .iter()
+            .map(|(id, fence)| fence_to_feature(id, fence))
+            .collect(),
+    )
+}
+
+/// Returns the track as a GeoJSON LineString Feature with the given id.
+/// The timestamps of the fixes are written in the "times" property, when all of them are known.
+pub fn track_to_feature(id: &str, fixes: &[Fix]) -> Value {
+    let mut properties = Map::new();
+    properties.insert("name".to_string(), Value::from(id));
+    let times: Option<Vec<f64>> = fixes.iter().map(|fix| fix.timestamp).collect();
+    if let Some(times) = times {
+        properties.insert("times".to_string(), Value::from(times));
+    }
+    feature(
+        id,
+        serde_json::json!({
+            "type": "LineString",
+            "coordinates": fixes.iter().map(fix_position).collect::<Vec<Value>>(),
+        }),
+        Value::Object(properties),
+    )
+}
+
+/// Returns the evaluations as a GeoJSON FeatureCollection of points, with the vehicle, the fence
+/// id, the location with respect to the fence and the signed distance to the border of the fence
+/// (negative inside) as properties.
+pub fn evaluations_to_geojson(evaluations: &[Evaluation]) -> Value {
+    let features = evaluations
+        .iter()
+        .enumerate()
+        .map(|(index, evaluation)| {
+            let mut properties = Map::new();
+            properties.insert(
+                "vehicle".to_string(),
+                Value::from(evaluation.vehicle.as_str()),
+            );
+            properties.insert(
+                "fence".to_string(),
+                Value::from(evaluation.fence_id.as_str()),
+            );
+            properties.insert(
+                "location".to_string(),
+                serde_json::json!(evaluation.location),
+            );
+            properties.insert("distance".to_string(), Value::from(evaluation.distance));
+            if let Some(timestamp) = evaluation.position.timestamp {
+                properties.insert("time".to_string(), Value::from(timestamp));
+            }
+            feature(
+                &format!("{}-{}", evaluation.vehicle, index),
+                serde_json::json!({
+                    "type": "Point",
+                    "coordinates": fix_position(&evaluation.position),
+                }),
+                Value::Object(properties),
+            )
+        })
+        .collect();
+    feature_collection(features)
+}
+
+/// Writes the GeoJSON value to the writer.
+pub fn write_geojson<W: Write>(writer: W, geojson: &Value) -> Result<(), GeofenceError> {
+    serde_json::to_writer_pretty(writer, geojson)?;
+    Ok(())
+}
+
+/// Writes the GeoJSON value to the file at the given path.
+pub fn write_geojson_file<P: AsRef<Path>>(path: P, geojson: &Value) -> Result<(), GeofenceError> {
+    write_geojson(File::create(path)?, geojson)
+}
@@ -0,0 +1,136 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads the tracks and waypoints recorded by handheld GPS devices from GPX files, so that real
+/// drives can be replayed against the fences instead of hand typing the moving coordinates.
+/// Every trk becomes a track, with the points of all its trkseg in order. Each trkpt becomes a
+/// Fix, with the time, elevation, speed and course when the device recorded them.
+///
+extern crate roxmltree;
+
+use self::roxmltree::{Document, Node};
+use coordinates::*;
+use error::GeofenceError;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// Tracks and waypoints read from a GPX document, along with their names.
+#[derive(Debug, Clone, Default)]
+pub struct Gpx {
+    pub tracks: Vec<(String, Vec<Fix>)>,
+    pub waypoints: Vec<(String, Fix)>,
+}
+
+impl Gpx {
+    /// Returns the tracks followed by the waypoints, each waypoint as a track of a single fix
+    /// named after the waypoint, so that both can be evaluated against the fences.
+    pub fn into_tracks(self) -> Vec<(String, Vec<Fix>)> {
+        let mut tracks = self.tracks;
+        tracks.extend(
+            self.waypoints
+                .into_iter()
+                .map(|(name, fix)| (name, vec![fix])),
+        );
+        tracks
+    }
+}
+
+fn parse_error(reason: String) -> GeofenceError {
+    GeofenceError::Parse(format!("GPX: {}", reason))
+}
+
+/// Returns the child elements of the node with the given tag name, ignoring the namespace.
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Returns the text of the first child element with the given tag name.
+fn child_text<'a>(node: Node<'a, '_>, name: &'static str) -> Option<&'a str> {
+    children(node, name)
+        .next()
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+/// Returns the number in the first element with the given tag name, which can also be in the
+/// extensions of the point, such as the speed and course of the Garmin TrackPointExtension.
+fn number(node: Node, name: &'static str) -> Result<Option<f64>, GeofenceError> {
+    let text = node
+        .descendants()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+        .and_then(|child| child.text());
+    match text {
+        Some(text) => text
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| parse_error(format!("{} '{}' is not a number", name, text))),
+        None => Ok(None),
+    }
+}
+
+fn attribute(node: Node, name: &str) -> Result<f64, GeofenceError> {
+    let value = node
+        .attribute(name)
+        .ok_or_else(|| parse_error(format!("a {} needs a {}", node.tag_name().name(), name)))?;
+    value
+        .trim()
+        .parse()
+        .map_err(|_| parse_error(format!("{} '{}' is not a number", name, value)))
+}
+
+/// Reads a trkpt or wpt element as a Fix.
+fn read_point(point: Node) -> Result<Fix, GeofenceError> {
+    let mut fix = Fix::new(attribute(point, "lat")?, attribute(point, "lon")?);
+    fix.altitude = number(point, "ele")?;
+    fix.speed = number(point, "speed")?;
+    fix.heading = number(point, "course")?;
+    if let Some(time) = child_text(point, "time") {
+        fix.timestamp = Some(parse_timestamp(time)?);
+    }
+    Ok(fix)
+}
+
+/// Reads the GPX document from the text.
+pub fn read_gpx_str(text: &str) -> Result<Gpx, GeofenceError> {
+    let document = Document::parse(text).map_err(|error| parse_error(error.to_string()))?;
+    let root = document.root_element();
+    let mut gpx = Gpx::default();
+    for (index, trk) in children(root, "trk").enumerate() {
+        let name = child_text(trk, "name")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("track-{}", index));
+        let fixes = children(trk, "trkseg")
+            .flat_map(|segment| children(segment, "trkpt"))
+            .map(read_point)
+            .collect::<Result<Vec<Fix>, GeofenceError>>()?;
+        gpx.tracks.push((name, fixes));
+    }
+    for (index, wpt) in children(root, "wpt").enumerate() {
+        let name = child_text(wpt, "name")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("waypoint-{}", index));
+        gpx.waypoints.push((name, read_point(wpt)?));
+    }
+    Ok(gpx)
+}
+
+/// Reads the GPX document from the reader.
+pub fn read_gpx<R: Read>(mut reader: R) -> Result<Gpx, GeofenceError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    read_gpx_str(&text)
+}
+
+/// Reads the GPX file at the given path.
+pub fn read_gpx_file<P: AsRef<Path>>(path: P) -> Result<Gpx, GeofenceError> {
+    read_gpx(BufReader::new(File::open(path)?))
+}
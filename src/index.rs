@@ -0,0 +1,105 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Spatial index over a large number of fences of any shape, to find out quickly which fences
+/// contain a given coordinate.
+/// The bounding boxes of the fences are kept in an R-tree, so a query only has to run the
+/// containment check on the few fences whose bounding box contains the coordinate.
+///
+extern crate rstar;
+
+use self::rstar::primitives::{GeomWithData, Rectangle};
+use self::rstar::{RTree, AABB};
+use coordinates::*;
+use fence::Geofence;
+use std::collections::HashMap;
+
+/// Bounding box of a fence stored in the R-tree, along with the id of the fence.
+type IndexEntry = GeomWithData<Rectangle<[f64; 2]>, String>;
+
+fn to_entry(id: &str, bbox: &BoundingBox) -> IndexEntry {
+    GeomWithData::new(
+        Rectangle::from_corners([bbox.min_lat, bbox.min_lon], [bbox.max_lat, bbox.max_lon]),
+        id.to_string(),
+    )
+}
+
+/// Fences indexed by their ids.
+#[derive(Default)]
+pub struct FenceIndex {
+    tree: RTree<IndexEntry>,
+    fences: HashMap<String, (Box<dyn Geofence>, BoundingBox)>,
+}
+
+impl FenceIndex {
+    pub fn new() -> FenceIndex {
+        FenceIndex::default()
+    }
+
+    /// Adds the fence to the index with the given id.
+    /// Returns the fence that was previously stored with the same id, if any.
+    pub fn insert<F: Geofence + 'static>(
+        &mut self,
+        id: &str,
+        fence: F,
+    ) -> Option<Box<dyn Geofence>> {
+        self.insert_boxed(id, Box::new(fence))
+    }
+
+    /// Adds the boxed fence to the index with the given id.
+    /// Returns the fence that was previously stored with the same id, if any.
+    pub fn insert_boxed(
+        &mut self,
+        id: &str,
+        fence: Box<dyn Geofence>,
+    ) -> Option<Box<dyn Geofence>> {
+        let previous = self.remove(id);
+        let bbox = fence.bounding_box();
+        self.tree.insert(to_entry(id, &bbox));
+        self.fences.insert(id.to_string(), (fence, bbox));
+        previous
+    }
+
+    /// Removes the fence with the given id from the index, and returns it.
+    pub fn remove(&mut self, id: &str) -> Option<Box<dyn Geofence>> {
+        let (fence, bbox) = self.fences.remove(id)?;
+        self.tree.remove(&to_entry(id, &bbox));
+        Some(fence)
+    }
+
+    /// Returns the fence with the given id.
+    pub fn get(&self, id: &str) -> Option<&dyn Geofence> {
+        self.fences.get(id).map(|(fence, _)| fence.as_ref())
+    }
+
+    /// Returns the number of fences in the index.
+    pub fn len(&self) -> usize {
+        self.fences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fences.is_empty()
+    }
+
+    /// Returns the ids of the fences that contain the given coordinates.
+    pub fn query_point(&self, point: &Coordinates) -> Vec<&str> {
+        self.tree
+            .locate_all_at_point(&[point.lat, point.lon])
+            .map(|entry| entry.data.as_str())
+            .filter(|id| self.fences[*id].0.contains(point))
+            .collect()
+    }
+
+    /// Returns the ids of the fences whose bounding box intersects the given box.
+    pub fn query_bbox(&self, bbox: &BoundingBox) -> Vec<&str> {
+        let envelope =
+            AABB::from_corners([bbox.min_lat, bbox.min_lon], [bbox.max_lat, bbox.max_lon]);
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|entry| entry.data.as_str())
+            .collect()
+    }
+}
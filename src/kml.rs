@@ -0,0 +1,229 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads fences and tracks from KML, the format saved by desktop globe tools, and from KMZ,
+/// which is a zip archive holding the KML document (needs the "kmz" feature, on by default).
+/// Every Placemark becomes a fence or a track, named after the placemark:
+/// Polygon -> PolygonFence (outerBoundaryIs and innerBoundaryIs), several polygons in a
+/// MultiGeometry -> MultiPolygonFence, Point with a "radius" in its ExtendedData -> CircleFence
+/// (meters unless a "unit" is given, "degrees" for a planar circle), LineString -> track.
+/// KML coordinates are "lon,lat[,alt]" tuples separated by whitespace.
+///
+extern crate roxmltree;
+
+use self::roxmltree::{Document, Node};
+use circle::CircleFence;
+use coordinates::*;
+use error::GeofenceError;
+use fence::Fence;
+use geodesy::{Model, Unit};
+use multipolygon::MultiPolygonFence;
+use polygon::{open_ring, PolygonFence};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// Fences and tracks read from a KML document, along with the names of their placemarks.
+#[derive(Debug, Clone, Default)]
+pub struct Kml {
+    pub fences: Vec<(String, Fence)>,
+    pub tracks: Vec<(String, Vec<Fix>)>,
+}
+
+fn parse_error(reason: String) -> GeofenceError {
+    GeofenceError::Parse(format!("KML: {}", reason))
+}
+
+/// Returns the child elements of the node with the given tag name, ignoring the namespace.
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Returns the descendant elements of the node with the given tag name, ignoring the namespace.
+fn descendants<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Reads the text of a coordinates element, "lon,lat[,alt]" tuples separated by whitespace.
+fn read_coordinates(node: Node) -> Result<Vec<Fix>, GeofenceError> {
+    let text = descendants(node, "coordinates")
+        .next()
+        .and_then(|coordinates| coordinates.text())
+        .unwrap_or_default();
+    text.split_whitespace()
+        .map(|tuple| {
+            let numbers = tuple
+                .split(',')
+                .map(|number| number.trim().parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| parse_error(format!("'{}' is not a coordinate", tuple)))?;
+            if numbers.len() < 2 {
+                return Err(parse_error(format!(
+                    "'{}' needs a longitude and a latitude",
+                    tuple
+                )));
+            }
+            let mut fix = Fix::new(numbers[1], numbers[0]);
+            fix.altitude = numbers.get(2).cloned();
+            Ok(fix)
+        })
+        .collect()
+}
+
+fn read_ring(boundary: Node) -> Result<Vec<Coordinates>, GeofenceError> {
+    let ring = read_coordinates(boundary)?
+        .iter()
+        .map(Fix::coordinates)
+        .collect();
+    Ok(open_ring(ring))
+}
+
+/// Reads a Polygon element, the outerBoundaryIs is the outer ring and every innerBoundaryIs
+/// is a hole.
+fn read_polygon(polygon: Node) -> Result<PolygonFence, GeofenceError> {
+    let outer = children(polygon, "outerBoundaryIs")
+        .next()
+        .ok_or_else(|| parse_error("a Polygon needs an outerBoundaryIs".to_string()))?;
+    let holes = children(polygon, "innerBoundaryIs")
+        .map(read_ring)
+        .collect::<Result<Vec<Vec<Coordinates>>, GeofenceError>>()?;
+    PolygonFence::with_holes(read_ring(outer)?, holes)
+}
+
+/// Returns the value of the ExtendedData field of the placemark with the given name,
+/// from either a Data or a SchemaData/SimpleData element.
+fn extended_data<'a>(placemark: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    let extended_data = children(placemark, "ExtendedData").next()?;
+    extended_data
+        .descendants()
+        .filter(|node| node.attribute("name") == Some(name))
+        .find_map(|node| match node.tag_name().name() {
+            "Data" => children(node, "value")
+                .next()
+                .and_then(|value| value.text()),
+            "SimpleData" => node.text(),
+            _ => None,
+        })
+        .map(str::trim)
+}
+
+/// Creates the circular fence of a Point placemark with a radius.
+fn read_circle(
+    placemark: Node,
+    center: &Coordinates,
+    radius: &str,
+) -> Result<CircleFence, GeofenceError> {
+    let radius: f64 = radius
+        .parse()
+        .map_err(|_| parse_error(format!("radius '{}' is not a number", radius)))?;
+    let unit = match extended_data(placemark, "unit") {
+        Some("degrees") => return CircleFence::from_center(center, radius),
+        Some(unit) => unit.parse()?,
+        None => Unit::Meters,
+    };
+    let model = match extended_data(placemark, "model") {
+        Some(model) => model.parse()?,
+        None => Model::default(),
+    };
+    CircleFence::geodesic(center, radius, unit, model)
+}
+
+/// Reads the geometries of the placemark, and adds them to the Kml as a fence or a track.
+fn read_placemark(placemark: Node, index: usize, kml: &mut Kml) -> Result<(), GeofenceError> {
+    let name = children(placemark, "name")
+        .next()
+        .and_then(|name| name.text())
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|| format!("placemark-{}", index));
+
+    let mut polygons = descendants(placemark, "Polygon")
+        .map(read_polygon)
+        .collect::<Result<Vec<PolygonFence>, GeofenceError>>()?;
+    if polygons.len() == 1 {
+        kml.fences.push((name, polygons.remove(0).into()));
+        return Ok(());
+    } else if !polygons.is_empty() {
+        kml.fences
+            .push((name, MultiPolygonFence::new(polygons)?.into()));
+        return Ok(());
+    }
+
+    let mut track = Vec::new();
+    for point in descendants(placemark, "Point") {
+        let fixes = read_coordinates(point)?;
+        match (fixes.first(), extended_data(placemark, "radius")) {
+            (Some(center), Some(radius)) => {
+                let fence = read_circle(placemark, &center.coordinates(), radius)?;
+                kml.fences.push((name, fence.into()));
+                return Ok(());
+            }
+            _ => track.extend(fixes),
+        }
+    }
+    for line in descendants(placemark, "LineString") {
+        track.extend(read_coordinates(line)?);
+    }
+    if !track.is_empty() {
+        kml.tracks.push((name, track));
+    }
+    Ok(())
+}
+
+/// Reads the KML document from the text.
+pub fn read_kml_str(text: &str) -> Result<Kml, GeofenceError> {
+    let document = Document::parse(text).map_err(|error| parse_error(error.to_string()))?;
+    let mut kml = Kml::default();
+    for (index, placemark) in descendants(document.root(), "Placemark").enumerate() {
+        read_placemark(placemark, index, &mut kml)?;
+    }
+    Ok(kml)
+}
+
+/// Reads the KML document from the reader.
+pub fn read_kml<R: Read>(mut reader: R) -> Result<Kml, GeofenceError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    read_kml_str(&text)
+}
+
+/// Reads the KML file at the given path.
+pub fn read_kml_file<P: AsRef<Path>>(path: P) -> Result<Kml, GeofenceError> {
+    read_kml(BufReader::new(File::open(path)?))
+}
+
+/// Reads the KML document in the KMZ archive from the reader.
+/// The document is doc.kml, or the first .kml file in the archive if there is no doc.kml.
+#[cfg(feature = "kmz")]
+pub fn read_kmz<R: Read + std::io::Seek>(reader: R) -> Result<Kml, GeofenceError> {
+    extern crate zip;
+
+    let mut archive =
+        zip::ZipArchive::new(reader).map_err(|error| parse_error(format!("KMZ {}", error)))?;
+    let document = archive
+        .file_names()
+        .filter(|name| name.to_ascii_lowercase().ends_with(".kml"))
+        .min_by_key(|name| *name != "doc.kml")
+        .map(str::to_string)
+        .ok_or_else(|| parse_error("the KMZ archive has no .kml document".to_string()))?;
+    let file = archive
+        .by_name(&document)
+        .map_err(|error| parse_error(format!("KMZ {}", error)))?;
+    read_kml(file)
+}
+
+/// Reads the KMZ file at the given path.
+#[cfg(feature = "kmz")]
+pub fn read_kmz_file<P: AsRef<Path>>(path: P) -> Result<Kml, GeofenceError> {
+    read_kmz(BufReader::new(File::open(path)?))
+}
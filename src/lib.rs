@@ -3,11 +3,31 @@
 // Please see the file LICENSE in the source
 // distribution of this software for license terms.
 
-//Incorporates circle, coordinates, geofencer, polygon and triangle module.
+//Incorporates circle, coordinates, corridor, crossing, csv_loader, error, evaluation, fence, geodesy, geofencer, geojson, gpx, index, kml, loader, multipolygon, nmea, polygon, rectangle, reporter, tracker, triangle, validity, wkb and wkt module.
 #![allow(dead_code)]
 
 pub mod circle;
 pub mod coordinates;
+pub mod corridor;
+pub mod crossing;
+pub mod csv_loader;
+pub mod error;
+pub mod evaluation;
+pub mod fence;
+pub mod geodesy;
 pub mod geofencer;
+pub mod geojson;
+pub mod gpx;
+pub mod index;
+pub mod kml;
+pub mod loader;
+pub mod multipolygon;
+pub mod nmea;
 pub mod polygon;
+pub mod rectangle;
+pub mod reporter;
+pub mod tracker;
 pub mod triangle;
+pub mod validity;
+pub mod wkb;
+pub mod wkt;
@@ -0,0 +1,266 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads the json fence files in to the MovingTracker, MovingTrackerCircle and
+/// MovingTrackerMultiPolygon structs, or in to the FenceFile of the shape named in the file.
+/// The json can be read from any Path or from anything that implements Read, so the
+/// fences can be loaded once and kept in memory instead of re-reading the file for every query.
+///
+extern crate serde_derive;
+
+use self::serde_derive::Deserialize;
+use circle::CircleFence;
+use coordinates::*;
+use corridor::CorridorFence;
+use error::GeofenceError;
+use fence::Fence;
+use multipolygon::MultiPolygonFence;
+use polygon::PolygonFence;
+use rectangle::RectangleFence;
+use std::env;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use triangle::TriangleFence;
+use validity::Validation;
+
+/// Json fence file of any shape, deserialized according to its "shape" field.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "shape")]
+pub enum FenceFile {
+    Polygon(MovingTracker),
+    Circle(MovingTrackerCircle),
+    Triangle(MovingTracker),
+    MultiPolygon(MovingTrackerMultiPolygon),
+    Corridor(MovingTrackerCorridor),
+    Rectangle(MovingTrackerRectangle),
+}
+
+impl FenceFile {
+    /// Returns the shape of the fence described by the file.
+    pub fn shape(&self) -> Shape {
+        match self {
+            FenceFile::Polygon(_) => Shape::Polygon,
+            FenceFile::Circle(_) => Shape::Circle,
+            FenceFile::Triangle(_) => Shape::Triangle,
+            FenceFile::MultiPolygon(_) => Shape::MultiPolygon,
+            FenceFile::Corridor(_) => Shape::Corridor,
+            FenceFile::Rectangle(_) => Shape::Rectangle,
+        }
+    }
+
+    /// Returns the vehicle whose moving coordinates are in the file.
+    pub fn vehicle(&self) -> &str {
+        match self {
+            FenceFile::Polygon(u) | FenceFile::Triangle(u) => &u.vehicle,
+            FenceFile::Circle(u) => &u.vehicle,
+            FenceFile::MultiPolygon(u) => &u.vehicle,
+            FenceFile::Corridor(u) => &u.vehicle,
+            FenceFile::Rectangle(u) => &u.vehicle,
+        }
+    }
+
+    /// Returns the moving coordinates of the vehicle.
+    pub fn moving_coordinate(&self) -> &[Fix] {
+        match self {
+            FenceFile::Polygon(u) | FenceFile::Triangle(u) => &u.moving_coordinate,
+            FenceFile::Circle(u) => &u.moving_coordinate,
+            FenceFile::MultiPolygon(u) => &u.moving_coordinate,
+            FenceFile::Corridor(u) => &u.moving_coordinate,
+            FenceFile::Rectangle(u) => &u.moving_coordinate,
+        }
+    }
+
+    /// Creates the fence described by the file.
+    /// Returns GeofenceError::InvalidGeometry if the coordinates cannot form the fence.
+    pub fn to_fence(&self) -> Result<Fence, GeofenceError> {
+        let fence = match self {
+            FenceFile::Polygon(u) => {
+                PolygonFence::with_holes(u.shape_coordinate.clone(), u.interior_coordinate.clone())?
+                    .with_fill_rule(u.fill_rule)
+                    .into()
+            }
+            FenceFile::Circle(u) => CircleFence::new(u.shape_coordinate.clone())?.into(),
+            FenceFile::Triangle(u) => TriangleFence::from_points(&u.shape_coordinate)?.into(),
+            FenceFile::MultiPolygon(u) => {
+                MultiPolygonFence::from_coordinates(u.shape_coordinate.clone())?
+                    .with_fill_rule(u.fill_rule)
+                    .into()
+            }
+            FenceFile::Corridor(u) => {
+                CorridorFence::from_coordinates(u.shape_coordinate.clone())?.into()
+            }
+            FenceFile::Rectangle(u) => RectangleFence::from_coordinates(u.shape_coordinate)?.into(),
+        };
+        Ok(fence)
+    }
+
+    /// Creates the fence described by the file, and validates or repairs it.
+    /// With Validation::Reject, returns GeofenceError::InvalidGeometry listing the defects of
+    /// the fence.
+    pub fn to_fence_with(&self, validation: Validation) -> Result<Fence, GeofenceError> {
+        validation.apply(self.to_fence()?)
+    }
+
+    /// Fills in the shape field of the struct, which serde takes out as the tag of the enum.
+    fn set_shape(&mut self) {
+        let shape = self.shape().to_string();
+        match self {
+            FenceFile::Polygon(u) | FenceFile::Triangle(u) => u.shape = shape,
+            FenceFile::Circle(u) => u.shape = shape,
+            FenceFile::MultiPolygon(u) => u.shape = shape,
+            FenceFile::Corridor(u) => u.shape = shape,
+            FenceFile::Rectangle(u) => u.shape = shape,
+        }
+    }
+}
+
+/// Resolves the given filename against the data folder of the current directory,
+/// which is where the simulator and the filename based functions look for the json files.
+pub fn data_path(filename: &str) -> PathBuf {
+    let mut path_buff = env::current_dir().unwrap_or_default();
+    path_buff.push("data");
+    path_buff.push(filename);
+    path_buff
+}
+
+/// Reads the json contents from the reader and checks that its "shape" field
+/// matches the expected shape, before it is mapped to the struct.
+fn read_shape<R: Read>(reader: R, shape: Shape) -> Result<serde_json::Value, GeofenceError> {
+    let value: serde_json::Value = serde_json::from_reader(reader)?;
+    let found = value
+        .get("shape")
+        .and_then(|found| found.as_str())
+        .unwrap_or_default();
+    if !found.eq_ignore_ascii_case(&shape.to_string()) {
+        return Err(GeofenceError::ShapeMismatch {
+            expected: shape,
+            found: found.to_string(),
+        });
+    }
+    Ok(value)
+}
+
+/// Reads the json contents from the reader as an instance of `MovingTracker`.
+/// Used for Polygon and Triangle fences, the shape is the one expected in the json.
+pub fn read_tracker<R: Read>(reader: R, shape: Shape) -> Result<MovingTracker, GeofenceError> {
+    let u = serde_json::from_value(read_shape(reader, shape)?)?;
+    Ok(u)
+}
+
+/// Reads the json file at the given path as an instance of `MovingTracker`.
+pub fn read_tracker_file<P: AsRef<Path>>(
+    path: P,
+    shape: Shape,
+) -> Result<MovingTracker, GeofenceError> {
+    read_tracker(BufReader::new(File::open(path)?), shape)
+}
+
+/// Reads the json contents from the reader as an instance of `MovingTrackerCircle`.
+pub fn read_circle_tracker<R: Read>(reader: R) -> Result<MovingTrackerCircle, GeofenceError> {
+    let u = serde_json::from_value(read_shape(reader, Shape::Circle)?)?;
+    Ok(u)
+}
+
+/// Reads the json file at the given path as an instance of `MovingTrackerCircle`.
+pub fn read_circle_tracker_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<MovingTrackerCircle, GeofenceError> {
+    read_circle_tracker(BufReader::new(File::open(path)?))
+}
+
+/// Reads the json contents from the reader as an instance of `MovingTrackerMultiPolygon`.
+pub fn read_multipolygon_tracker<R: Read>(
+    reader: R,
+) -> Result<MovingTrackerMultiPolygon, GeofenceError> {
+    let u = serde_json::from_value(read_shape(reader, Shape::MultiPolygon)?)?;
+    Ok(u)
+}
+
+/// Reads the json file at the given path as an instance of `MovingTrackerMultiPolygon`.
+pub fn read_multipolygon_tracker_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<MovingTrackerMultiPolygon, GeofenceError> {
+    read_multipolygon_tracker(BufReader::new(File::open(path)?))
+}
+
+/// Reads the json contents from the reader as an instance of `MovingTrackerCorridor`.
+pub fn read_corridor_tracker<R: Read>(reader: R) -> Result<MovingTrackerCorridor, GeofenceError> {
+    let u = serde_json::from_value(read_shape(reader, Shape::Corridor)?)?;
+    Ok(u)
+}
+
+/// Reads the json file at the given path as an instance of `MovingTrackerCorridor`.
+pub fn read_corridor_tracker_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<MovingTrackerCorridor, GeofenceError> {
+    read_corridor_tracker(BufReader::new(File::open(path)?))
+}
+
+/// Reads the json contents from the reader as an instance of `MovingTrackerRectangle`.
+pub fn read_rectangle_tracker<R: Read>(reader: R) -> Result<MovingTrackerRectangle, GeofenceError> {
+    let u = serde_json::from_value(read_shape(reader, Shape::Rectangle)?)?;
+    Ok(u)
+}
+
+/// Reads the json file at the given path as an instance of `MovingTrackerRectangle`.
+pub fn read_rectangle_tracker_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<MovingTrackerRectangle, GeofenceError> {
+    read_rectangle_tracker(BufReader::new(File::open(path)?))
+}
+
+/// Returns the shape named in the "shape" field of the json value, ignoring the case.
+fn shape_of(value: &serde_json::Value) -> Result<Shape, GeofenceError> {
+    value
+        .get("shape")
+        .and_then(|shape| shape.as_str())
+        .ok_or_else(|| GeofenceError::Parse("the json file has no \"shape\" field".to_string()))?
+        .parse()
+}
+
+/// Reads the json contents from the reader, and returns the shape named in its "shape" field.
+/// Returns GeofenceError::Parse if the field is missing or is not one of the shapes.
+pub fn detect_shape<R: Read>(reader: R) -> Result<Shape, GeofenceError> {
+    let value: serde_json::Value = serde_json::from_reader(reader)?;
+    shape_of(&value)
+}
+
+/// Reads the json file at the given path, and returns the shape named in its "shape" field.
+pub fn detect_shape_file<P: AsRef<Path>>(path: P) -> Result<Shape, GeofenceError> {
+    detect_shape(BufReader::new(File::open(path)?))
+}
+
+/// Reads the json contents from the reader as the FenceFile of the shape named in the file,
+/// so the callers do not need to know the shape beforehand.
+/// When a shape is given, returns GeofenceError::ShapeMismatch if the file describes another
+/// shape. Returns GeofenceError::Parse if the shape is unknown, or if the coordinates of the
+/// file do not match its shape, for example a Circle with a list of shape coordinates.
+pub fn read_fence_json<R: Read>(
+    reader: R,
+    shape: Option<Shape>,
+) -> Result<FenceFile, GeofenceError> {
+    let mut value = match shape {
+        Some(shape) => read_shape(reader, shape)?,
+        None => serde_json::from_reader(reader)?,
+    };
+    let shape = shape_of(&value)?;
+    // The names of the variants are the tags, whatever the case in the file.
+    value["shape"] = serde_json::Value::String(shape.to_string());
+    let mut file: FenceFile = serde_json::from_value(value).map_err(|error| {
+        GeofenceError::Parse(format!("the {} fence file is malformed: {}", shape, error))
+    })?;
+    file.set_shape();
+    Ok(file)
+}
+
+/// Reads the json file at the given path as the FenceFile of the shape named in the file.
+pub fn read_fence_json_file<P: AsRef<Path>>(
+    path: P,
+    shape: Option<Shape>,
+) -> Result<FenceFile, GeofenceError> {
+    read_fence_json(BufReader::new(File::open(path)?), shape)
+}
@@ -0,0 +1,260 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// This module will create MultiPolygon fence, a fence made of several polygons (for example the
+/// disjoint parcels of a customer site), each of which can have holes.
+/// It validates if the given coordinates is within the fence or not, using the winding numbers of
+/// all the rings of all the polygons together with the fill rule of the fence.
+///
+use coordinates::*;
+use crossing::edge_intersections;
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::{BoundaryDistance, Geofence};
+use geofencer::track;
+use loader::{data_path, read_multipolygon_tracker_file};
+use polygon::{locate_in_rings, ring_edges, PolygonFence};
+use reporter::{Colour, Reporter};
+use validity::{repair_polygon, validate_multipolygon, Defect};
+
+/// MultiPolygon fence built from a list of polygon fences.
+/// The winding numbers of the rings of all the polygons are added up, so the polygons share a
+/// single fill rule, which is the fill rule of the fence.
+#[derive(Debug, Clone)]
+pub struct MultiPolygonFence {
+    polygons: Vec<PolygonFence>,
+    fill_rule: FillRule,
+}
+
+impl MultiPolygonFence {
+    /// Creates the fence from the given polygons, with their fill rule.
+    /// Returns GeofenceError::InvalidGeometry if there are no polygons, or if the polygons do
+    /// not all have the same fill rule.
+    pub fn new(polygons: Vec<PolygonFence>) -> Result<MultiPolygonFence, GeofenceError> {
+        let fill_rule = match polygons.first() {
+            Some(polygon) => polygon.fill_rule(),
+            None => {
+                return Err(GeofenceError::InvalidGeometry(
+                    "a multipolygon fence needs at least 1 polygon".to_string(),
+                ))
+            }
+        };
+        if let Some(pos) = polygons
+            .iter()
+            .position(|polygon| polygon.fill_rule() != fill_rule)
+        {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "the polygons of a multipolygon fence must have the same fill rule, but polygon \
+                 {} has {:?} and polygon 0 has {:?}",
+                pos,
+                polygons[pos].fill_rule(),
+                fill_rule
+            )));
+        }
+        Ok(MultiPolygonFence {
+            polygons,
+            fill_rule,
+        })
+    }
+
+    /// Creates the fence from the coordinates of the outer ring and holes of each polygon.
+    pub fn from_coordinates(
+        polygons: Vec<PolygonCoordinates>,
+    ) -> Result<MultiPolygonFence, GeofenceError> {
+        let polygons = polygons
+            .into_iter()
+            .map(|polygon| PolygonFence::with_holes(polygon.exterior, polygon.interior))
+            .collect::<Result<Vec<PolygonFence>, GeofenceError>>()?;
+        MultiPolygonFence::new(polygons)
+    }
+
+    /// Sets the rule used to decide if a coordinate is inside the fence, for the fence and each
+    /// of its polygons.
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> MultiPolygonFence {
+        self.fill_rule = fill_rule;
+        self.polygons = self
+            .polygons
+            .into_iter()
+            .map(|polygon| polygon.with_fill_rule(fill_rule))
+            .collect();
+        self
+    }
+
+    /// Returns the polygons that make up the fence.
+    pub fn polygons(&self) -> &[PolygonFence] {
+        &self.polygons
+    }
+
+    /// Returns the rule used to decide if a coordinate is inside the fence.
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    /// Returns all the rings of all the polygons of the fence.
+    pub fn rings(&self) -> impl Iterator<Item = &[Coordinates]> {
+        self.polygons.iter().flat_map(|polygon| polygon.rings())
+    }
+
+    /// Returns the defects of the rings of all the polygons, see validity::Defect.
+    /// The fence is valid when there are none.
+    pub fn validate(&self) -> Vec<Defect> {
+        validate_multipolygon(&self.polygons, self.fill_rule)
+    }
+
+    /// Repairs each polygon of the fence, see validity::repair_polygon.
+    /// Polygons that overlap each other are left as they are.
+    pub fn repair(&self) -> Result<MultiPolygonFence, GeofenceError> {
+        let mut polygons = Vec::new();
+        for polygon in &self.polygons {
+            polygons.extend(repair_polygon(
+                polygon.points(),
+                polygon.holes(),
+                self.fill_rule,
+            )?);
+        }
+        Ok(MultiPolygonFence::new(polygons)?.with_fill_rule(self.fill_rule))
+    }
+}
+
+impl Geofence for MultiPolygonFence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        locate_in_rings(point, self.rings(), self.fill_rule)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let mut bbox = self.polygons[0].bounding_box();
+        for polygon in &self.polygons[1..] {
+            let other = polygon.bounding_box();
+            bbox.min_lat = bbox.min_lat.min(other.min_lat);
+            bbox.min_lon = bbox.min_lon.min(other.min_lon);
+            bbox.max_lat = bbox.max_lat.max(other.max_lat);
+            bbox.max_lon = bbox.max_lon.max(other.max_lon);
+        }
+        bbox
+    }
+
+    /// Sum of the areas of the polygons.
+    fn area(&self) -> f64 {
+        self.polygons.iter().map(|polygon| polygon.area()).sum()
+    }
+
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        if self.contains(point) {
+            return 0.0;
+        }
+        self.polygons
+            .iter()
+            .flat_map(|polygon| polygon.edges())
+            .map(|(point1, point2)| distance_to_segment(point, point1, point2))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        edge_intersections(start, end, self.rings().flat_map(ring_edges))
+    }
+
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        let (nearest, distance) = nearest_point_on_edges(point, self.rings().flat_map(ring_edges))
+            .unwrap_or((point.clone(), 0.0));
+        BoundaryDistance::new(distance, nearest, self.contains(point))
+    }
+}
+
+/// The below function reads the incoming json file which is of the format
+///{
+//  "shape": "MultiPolygon",
+//  "vehicle": "van",
+//  "shape_coordinate":[
+//    {"exterior": [{"lat": 0.0,"lon": 0.0},{"lat": 4.0,"lon": 0.0},{"lat": 4.0,"lon": 4.0}],
+//     "interior": [[{"lat": 2.0,"lon": 1.0},{"lat": 3.0,"lon": 1.0},{"lat": 3.0,"lon": 2.0}]]},
+//    {"exterior": [{"lat": 10.0,"lon": 0.0},{"lat": 14.0,"lon": 0.0},{"lat": 14.0,"lon": 4.0}]}
+//  ],
+//  "fill_rule": "even_odd",
+//  "moving_coordinate": [{"lat":1.0 ,"lon": 1.0}]
+//}
+/// shape_coordinate -> contains the outer ring and the optional holes of each polygon
+/// fill_rule -> optional, even_odd (default) or non_zero
+/// moving_coordinate -> contains series of lat, lon for which location has to be determined.
+/// Returns Result -> contains either MovingTrackerMultiPolygon struct if success or Error if any failure.
+fn read_movingtracker_file(filename: &str) -> Result<MovingTrackerMultiPolygon, GeofenceError> {
+    read_multipolygon_tracker_file(data_path(filename))
+}
+
+/// Creates the fence from the contents of the json file.
+fn get_fence(u: &MovingTrackerMultiPolygon) -> Result<MultiPolygonFence, GeofenceError> {
+    Ok(
+        MultiPolygonFence::from_coordinates(u.shape_coordinate.clone())?
+            .with_fill_rule(u.fill_rule),
+    )
+}
+
+/// The function does the below
+/// Reads the input file
+/// Creates multipolygon fence based on the coordinates given in the input file
+/// Executes set of coordinates for which position has to be determined.
+/// Prints if the coordinates are within or outside the fence, and returns the evaluation of each of them
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates
+/// reporter -> prints the fence and the evaluations
+///
+pub fn execute_multipolygon(
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = get_fence(&u)?;
+    reporter.display_bold(
+        "Created MultiPolygon Fence, with the below coordinates",
+        Colour::Blue,
+    );
+    reporter.display(&format!("{:?}", u.shape_coordinate), Colour::Blue);
+    Ok(track(
+        &fence,
+        filename,
+        &u.vehicle,
+        &u.moving_coordinate,
+        reporter,
+    ))
+}
+
+/// The function does the below
+/// Reads the input file
+/// Creates multipolygon fence based on the coordinates given in the input file
+/// checks if the given coordinates in the function paramater is within the fence.
+/// Prints if the coordinates are within or outside the fence
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates which is necessary to create the fence
+/// lat and lon -> search coordinates
+/// reporter -> prints the fence and the result
+pub fn contains_in_multipolygon(
+    filename: &str,
+    lat: f64,
+    lon: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = get_fence(&u)?;
+    let point: Coordinates = Coordinates::new(lat, lon);
+    let mut retval = false;
+    reporter.display_bold(
+        "Searching the vehicle in MultiPolygon Fence, which is built with coordinates",
+        Colour::Blue,
+    );
+    reporter.display(&format!("{:?}", u.shape_coordinate), Colour::Blue);
+    let mut str = "is out of the fence";
+    if fence.contains(&point) {
+        str = "is inside the fence";
+        retval = true;
+    }
+    reporter.display(
+        &format!(
+            "The {} positioned at latitude {}, longitude {}, {} ",
+            u.vehicle, lat, lon, &str
+        ),
+        Colour::Black,
+    );
+    Ok(retval)
+}
@@ -0,0 +1,310 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Parses the NMEA 0183 sentences streamed by GPS receivers, so that the raw output of a device
+/// gateway can be turned in to fixes and fed to the tracker.
+/// RMC (recommended minimum) and GGA (fix data) sentences from any talker ($GPRMC, $GNRMC,
+/// $GPGGA ...) give a fix, every other sentence is ignored. The checksum after the '*' is
+/// validated, and the positions are converted from degrees and minutes to decimal degrees.
+/// The date is only sent in RMC sentences, so the GGA fixes of a stream that starts before the
+/// first RMC sentence are held back until the date is known.
+///
+use coordinates::*;
+use error::GeofenceError;
+use std::io::BufRead;
+use tracker::{Event, Tracker};
+
+/// Meters per second in a knot, the unit of the speed in RMC sentences.
+const KNOT: f64 = 0.514_444;
+/// Seconds in a day.
+const DAY: f64 = 86_400.0;
+
+fn parse_error(sentence: &str, reason: &str) -> GeofenceError {
+    GeofenceError::Parse(format!("NMEA: {} in '{}'", reason, sentence))
+}
+
+/// Checks the checksum of the sentence and returns the fields between the '$' and the '*'.
+/// The checksum is the XOR of all the characters between the '$' and the '*', in hex.
+/// Sentences without a checksum are rejected, unless optional_checksum is true.
+fn fields(sentence: &str, optional_checksum: bool) -> Result<Vec<&str>, GeofenceError> {
+    let body = sentence
+        .strip_prefix('$')
+        .ok_or_else(|| parse_error(sentence, "a sentence starts with '$'"))?;
+    let body = match body.split_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum.trim(), 16)
+                .map_err(|_| parse_error(sentence, "the checksum is not hex"))?;
+            let actual = body.bytes().fold(0, |checksum, byte| checksum ^ byte);
+            if actual != expected {
+                return Err(parse_error(
+                    sentence,
+                    &format!("checksum {:02X} does not match {:02X}", actual, expected),
+                ));
+            }
+            body
+        }
+        None if optional_checksum => body,
+        None => return Err(parse_error(sentence, "the checksum is missing")),
+    };
+    Ok(body.split(',').collect())
+}
+
+/// Converts the position from the NMEA degrees and minutes format (ddmm.mmmm for latitudes,
+/// dddmm.mmmm for longitudes) to decimal degrees. Southern and western hemispheres are negative.
+/// Returns GeofenceError::Parse if the minutes are not below 60, or if the position is beyond
+/// 90 degrees for a latitude (N, S) or 180 degrees for a longitude (E, W).
+pub fn dms_to_decimal(value: &str, hemisphere: &str) -> Result<f64, GeofenceError> {
+    let error = || {
+        GeofenceError::Parse(format!(
+            "NMEA: '{},{}' is not a position in degrees and minutes",
+            value, hemisphere
+        ))
+    };
+    let value: f64 = value.parse().map_err(|_| error())?;
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(error());
+    }
+    let degrees = (value / 100.0).trunc();
+    let minutes = value - degrees * 100.0;
+    if minutes >= 60.0 {
+        return Err(error());
+    }
+    let decimal = degrees + minutes / 60.0;
+    let (sign, limit) = match hemisphere {
+        "N" => (1.0, 90.0),
+        "S" => (-1.0, 90.0),
+        "E" => (1.0, 180.0),
+        "W" => (-1.0, 180.0),
+        _ => return Err(error()),
+    };
+    if decimal > limit {
+        return Err(error());
+    }
+    Ok(sign * decimal)
+}
+
+/// Parses an optional number field, empty fields are None.
+fn number(sentence: &str, field: &str) -> Result<Option<f64>, GeofenceError> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    field
+        .parse()
+        .map(Some)
+        .map_err(|_| parse_error(sentence, &format!("'{}' is not a number", field)))
+}
+
+/// Parses the hhmmss.ss time of day in to seconds since midnight.
+fn time_of_day(sentence: &str, field: &str) -> Result<Option<f64>, GeofenceError> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    let error = || parse_error(sentence, &format!("'{}' is not a time", field));
+    let hours: f64 = field
+        .get(..2)
+        .and_then(|h| h.parse().ok())
+        .ok_or_else(error)?;
+    let minutes: f64 = field
+        .get(2..4)
+        .and_then(|m| m.parse().ok())
+        .ok_or_else(error)?;
+    let seconds: f64 = field
+        .get(4..)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(error)?;
+    Ok(Some(hours * 3600.0 + minutes * 60.0 + seconds))
+}
+
+/// Parses the ddmmyy date in to seconds since the unix epoch, at midnight.
+/// Two digit years from 80 are in the 1900s, and the rest in the 2000s.
+fn date(sentence: &str, field: &str) -> Result<Option<f64>, GeofenceError> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    if field.len() != 6 || !field.is_ascii() {
+        return Err(parse_error(sentence, &format!("'{}' is not a date", field)));
+    }
+    let century = if &field[4..6] >= "80" { "19" } else { "20" };
+    let midnight = format!(
+        "{}{}-{}-{}T00:00:00Z",
+        century,
+        &field[4..6],
+        &field[2..4],
+        &field[..2]
+    );
+    parse_timestamp(&midnight)
+        .map(Some)
+        .map_err(|_| parse_error(sentence, &format!("'{}' is not a date", field)))
+}
+
+/// Parses the NMEA sentences one at a time. The date is only sent in RMC sentences, so the
+/// parser remembers the last date to give GGA sentences a timestamp, and moves it on to the next
+/// day when the time of day goes past midnight.
+#[derive(Debug, Clone, Default)]
+pub struct NmeaParser {
+    date: Option<f64>,
+    /// Time of day of the last fix that was given a timestamp.
+    time_of_day: Option<f64>,
+    optional_checksum: bool,
+    /// Fixes received before the first date, along with their time of day, see parse_dated.
+    held: Vec<(Fix, Option<f64>)>,
+}
+
+impl NmeaParser {
+    pub fn new() -> NmeaParser {
+        NmeaParser::default()
+    }
+
+    /// Accepts the sentences that have no checksum, as sent by some devices, when
+    /// optional_checksum is true. By default they are rejected.
+    pub fn with_optional_checksum(mut self, optional_checksum: bool) -> NmeaParser {
+        self.optional_checksum = optional_checksum;
+        self
+    }
+
+    /// Parses the sentence, and returns the fix it contains.
+    /// Returns None for RMC and GGA sentences without a valid fix (status V, quality 0), and for
+    /// all the other sentences. Returns GeofenceError::Parse if the checksum does not match or
+    /// is missing, or if the RMC or GGA sentence is malformed.
+    /// GGA fixes received before the first RMC sentence have no timestamp.
+    pub fn parse(&mut self, sentence: &str) -> Result<Option<Fix>, GeofenceError> {
+        Ok(self.parse_fix(sentence)?.map(|(fix, _)| fix))
+    }
+
+    /// Parses the sentence like parse, but holds back the fixes received before the first date.
+    /// They are returned with their timestamps, ahead of the fix of the sentence, once a
+    /// sentence brings the date, so that a stream that starts with GGA sentences gives a single
+    /// dated timeline.
+    pub fn parse_dated(&mut self, sentence: &str) -> Result<Vec<Fix>, GeofenceError> {
+        let parsed = self.parse_fix(sentence)?;
+        if self.date.is_none() {
+            self.held.extend(parsed);
+            return Ok(Vec::new());
+        }
+        let mut fixes = self.release();
+        fixes.extend(parsed.map(|(fix, _)| fix));
+        Ok(fixes)
+    }
+
+    /// Returns the fixes that are still held back by parse_dated, without a timestamp as no
+    /// date was received, for the end of the stream.
+    pub fn finish(&mut self) -> Vec<Fix> {
+        self.held.drain(..).map(|(fix, _)| fix).collect()
+    }
+
+    /// Returns the held back fixes, dated with the date that has just been received. The fixes
+    /// came before the fix that brought the date, so a later time of day is on the day before.
+    fn release(&mut self) -> Vec<Fix> {
+        let (date, first) = match (self.date, self.time_of_day) {
+            (Some(date), first) => (date, first.unwrap_or(DAY)),
+            (None, _) => return Vec::new(),
+        };
+        self.held
+            .drain(..)
+            .map(|(mut fix, time_of_day)| {
+                fix.timestamp = time_of_day.map(|time_of_day| {
+                    if time_of_day > first {
+                        date - DAY + time_of_day
+                    } else {
+                        date + time_of_day
+                    }
+                });
+                fix
+            })
+            .collect()
+    }
+
+    /// Parses the sentence, and returns the fix it contains along with its time of day.
+    fn parse_fix(&mut self, sentence: &str) -> Result<Option<(Fix, Option<f64>)>, GeofenceError> {
+        let sentence = sentence.trim();
+        let fields = fields(sentence, self.optional_checksum)?;
+        let kind = fields[0].get(2..).unwrap_or_default();
+        let field = |pos: usize| fields.get(pos).cloned().unwrap_or_default();
+        match kind {
+            "RMC" => {
+                let date = date(sentence, field(9))?;
+                let time_of_day = time_of_day(sentence, field(1))?;
+                if date.is_some() {
+                    // The time of day of the sentence is on its own date.
+                    self.date = date;
+                    self.time_of_day = time_of_day;
+                }
+                if field(2) != "A" {
+                    return Ok(None);
+                }
+                let mut fix = Fix::new(
+                    dms_to_decimal(field(3), field(4))?,
+                    dms_to_decimal(field(5), field(6))?,
+                );
+                fix.speed = number(sentence, field(7))?.map(|knots| knots * KNOT);
+                fix.heading = number(sentence, field(8))?;
+                fix.timestamp = self.timestamp(time_of_day);
+                Ok(Some((fix, time_of_day)))
+            }
+            "GGA" => {
+                if matches!(field(6), "" | "0") {
+                    return Ok(None);
+                }
+                let mut fix = Fix::new(
+                    dms_to_decimal(field(2), field(3))?,
+                    dms_to_decimal(field(4), field(5))?,
+                );
+                fix.altitude = number(sentence, field(9))?;
+                let time_of_day = time_of_day(sentence, field(1))?;
+                fix.timestamp = self.timestamp(time_of_day);
+                Ok(Some((fix, time_of_day)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the timestamp of the time of day on the last date that was received.
+    /// A time of day more than 12 hours before the last one has gone past midnight, so the
+    /// date is moved on to the next day.
+    fn timestamp(&mut self, time_of_day: Option<f64>) -> Option<f64> {
+        let time_of_day = time_of_day?;
+        let mut date = self.date?;
+        if self
+            .time_of_day
+            .is_some_and(|last| time_of_day + DAY / 2.0 < last)
+        {
+            date += DAY;
+            self.date = Some(date);
+        }
+        self.time_of_day = Some(time_of_day);
+        Some(date + time_of_day)
+    }
+}
+
+/// Parses a single NMEA sentence, see NmeaParser::parse.
+/// GGA sentences have no timestamp, as the date is only sent in RMC sentences.
+pub fn parse_sentence(sentence: &str) -> Result<Option<Fix>, GeofenceError> {
+    NmeaParser::new().parse(sentence)
+}
+
+/// Reads the NMEA sentences of the vehicle, one per line, and feeds the fixes to the tracker.
+/// The fixes received before the first date are fed once the date is known, see
+/// NmeaParser::parse_dated.
+/// Returns the Enter, Exit and Dwell events of the vehicle.
+/// Sentences that cannot be parsed, such as lines garbled on a serial link, are skipped, so that
+/// one bad line does not stop the stream. Only errors reading from the reader are returned.
+pub fn track_nmea<R: BufRead>(
+    reader: R,
+    vehicle: &str,
+    tracker: &mut Tracker,
+) -> Result<Vec<Event>, GeofenceError> {
+    let mut parser = NmeaParser::new();
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        for fix in parser.parse_dated(&line?).unwrap_or_default() {
+            events.extend(tracker.update_fix(vehicle, &fix));
+        }
+    }
+    for fix in parser.finish() {
+        events.extend(tracker.update_fix(vehicle, &fix));
+    }
+    Ok(events)
+}
@@ -4,127 +4,251 @@
 // distribution of this software for license terms.
 
 /// This module will create polygon fence and will validate if the given coordinates is within the fence or not
-/// Uses Ray casting along with Cramers rule to verify if the line intersects or not.
+/// Uses the winding number of the fence around the coordinates, with exact orientation tests.
 ///
 use coordinates::*;
-use std::env;
-use std::error::Error;
-use std::fs::File;
-use std::{thread, time};
+use crossing::edge_intersections;
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::{BoundaryDistance, Geofence};
+use geofencer::track;
+use loader::{data_path, read_tracker_file};
+use multipolygon::MultiPolygonFence;
+use reporter::{Colour, Reporter};
+use validity::{repair_polygon, validate_polygon, Defect};
 
-/// The below function will form list of line equations between various points (Coordinates).
+/// Returns the winding number of the ring around the given point, or None if the point
+/// lies on the border of the ring.
+/// The winding number counts how many times the ring winds around the point, counter clockwise
+/// turns are counted as +1 and clockwise turns as -1. It is 0 when the point is outside the ring.
+/// Each edge is only counted when it crosses the horizontal line through the point, and the
+/// side of the point is decided with an exact orientation test, so points whose line passes
+/// through a vertex or along a horizontal edge are classified correctly.
 /// The last point will be circled back to the first point.
-/// For example if P, Q, R are three different points then
-/// Line equation will be PQ, QR, RP
 ///
+pub fn winding_number(point: &Coordinates, ring: &[Coordinates]) -> Option<i32> {
+    let len = ring.len();
+    let mut winding = 0;
+    for (pos, point1) in ring.iter().enumerate() {
+        let point2 = &ring[(pos + 1) % len];
+        let side = orientation(point1, point2, point);
+        if side == 0.0 && within_bounds(point, point1, point2) {
+            return None;
+        }
+        if point1.lon <= point.lon {
+            // An upward crossing, with the point on the left of the edge.
+            if point2.lon > point.lon && side > 0.0 {
+                winding += 1;
+            }
+        } else if point2.lon <= point.lon && side < 0.0 {
+            // A downward crossing, with the point on the right of the edge.
+            winding -= 1;
+        }
+    }
+    Some(winding)
+}
+
+/// This method will check if the given point is within the box spanned by point1 and point2.
+/// For a point on the line through point1 and point2, this means it is on the segment.
 ///
+fn within_bounds(point: &Coordinates, point1: &Coordinates, point2: &Coordinates) -> bool {
+    point.lat >= point1.lat.min(point2.lat)
+        && point.lat <= point1.lat.max(point2.lat)
+        && point.lon >= point1.lon.min(point2.lon)
+        && point.lon <= point1.lon.max(point2.lon)
+}
 
-fn get_lineequation(points: &[Coordinates], len: usize) -> Vec<(f64, f64, f64)> {
-    let mut line_equn: Vec<(f64, f64, f64)> = Vec::new();
+///
+/// This method checks if a particular coordinate is inside, outside or on the border of
+/// the ring of coordinates, using the winding number of the ring around the coordinate.
+/// A coordinate is inside when the winding number is not zero.
+///
+pub fn locate_in_ring(point: &Coordinates, ring: &[Coordinates]) -> Location {
+    locate_in_rings(point, [ring], FillRule::NonZero)
+}
 
-    for (_pos, coord) in points.iter().enumerate() {
-        let point1 = coord;
-        let point2: &Coordinates = &(points[((_pos + 1) % len)]);
-        //Check if vertical or horizontal line first.
-        if (point1.lon - point2.lon).abs() == 0.0 {
-            //Vertical
-            line_equn.push((0.0, 1.0, point1.lon));
-        } else if (point1.lat - point2.lat).abs() == 0.0 {
-            //Horizontal
-            line_equn.push((1.0, 0.0, point1.lat));
-        } else {
-            //Not vertical or Horizontal line
-            let a = -1.0 * ((point2.lon - point1.lon) / (point2.lat - point1.lat));
-            let c = point1.lon + (a * point1.lat);
-            line_equn.push((a, 1.0, c));
+///
+/// This method checks if a particular coordinate is inside, outside or on the border of a fence
+/// made of several rings, such as a polygon with holes.
+/// The winding numbers of all the rings are added up, and the fill rule decides if the total
+/// means inside: an odd total for EvenOdd, a non zero total for NonZero.
+///
+pub fn locate_in_rings<'a, I>(point: &Coordinates, rings: I, fill_rule: FillRule) -> Location
+where
+    I: IntoIterator<Item = &'a [Coordinates]>,
+{
+    let mut winding = 0;
+    for ring in rings {
+        match winding_number(point, ring) {
+            None => return Location::OnBoundary,
+            Some(ring_winding) => winding += ring_winding,
         }
     }
-    //println!("The line equation {:?}",line_equn);
-    line_equn
+    let inside = match fill_rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    };
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
 }
 
-/// This method will check if the a given lat or lon is within a particular
-/// coordinate.
-///
-fn check_if_within(point: f64, point1: f64, point2: f64) -> bool {
-    point1 >= point2 && point <= point1 && point >= point2 || point <= point2 && point >= point1
+/// Returns the edges of the ring as pairs of consecutive coordinates,
+/// the last edge joins the last coordinate back to the first one.
+pub fn ring_edges(ring: &[Coordinates]) -> impl Iterator<Item = (&Coordinates, &Coordinates)> {
+    let len = ring.len();
+    ring.iter()
+        .enumerate()
+        .map(move |(pos, coord)| (coord, &ring[(pos + 1) % len]))
 }
 
-/// This method will check if the a given lat or lon is within a particular
-/// coordinate.
-///
-fn check_inbounds(point: &Coordinates, point1: &Coordinates, point2: &Coordinates) -> bool {
-    let within_x = check_if_within(point.lat, point1.lat, point2.lat);
-    let within_y = check_if_within(point.lon, point1.lon, point2.lon);
-    let mut retval = false;
-    if within_x && within_y {
-        retval = true
+/// Returns the area of the ring calculated with the shoelace formula.
+/// The area is positive when the ring is counter clockwise and negative when it is clockwise.
+pub fn ring_area(ring: &[Coordinates]) -> f64 {
+    let twice_area: f64 = ring_edges(ring)
+        .map(|(point1, point2)| point1.lat * point2.lon - point2.lat * point1.lon)
+        .sum();
+    twice_area / 2.0
+}
+
+/// Drops the last coordinate of the ring when it repeats the first one. Formats such as GeoJSON
+/// and WKT close their rings explicitly, while the fences circle back to the first coordinate.
+pub fn open_ring(mut ring: Vec<Coordinates>) -> Vec<Coordinates> {
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
     }
+    ring
+}
 
-    retval
+/// Checks that the ring has enough coordinates to enclose an area.
+fn check_ring(ring: &[Coordinates]) -> Result<(), GeofenceError> {
+    if ring.len() < 3 {
+        return Err(GeofenceError::InvalidGeometry(format!(
+            "a polygon ring needs at least 3 coordinates, but {} were supplied",
+            ring.len()
+        )));
+    }
+    Ok(())
 }
-///
-/// This method checks if a particular value is within the list
-///
-fn vec_contains(point: &Coordinates, vec_coord: &[Coordinates]) -> bool {
-    let mut retval = false;
-    for (_pos, coord) in vec_coord.iter().enumerate() {
-        if (point.lat - coord.lat).abs() == 0.0 && (point.lon - coord.lon).abs() == 0.0 {
-            retval = true;
-            break;
+
+/// Polygon fence built from a list of coordinates.
+/// The last coordinate is circled back to the first one to close the fence.
+/// The fence can have holes (interior rings), which are excluded from the fence.
+#[derive(Debug, Clone)]
+pub struct PolygonFence {
+    points: Vec<Coordinates>,
+    holes: Vec<Vec<Coordinates>>,
+    fill_rule: FillRule,
+}
+
+impl PolygonFence {
+    /// Creates the fence from the given coordinates.
+    /// Returns GeofenceError::InvalidGeometry if there are less than 3 coordinates.
+    pub fn new(points: Vec<Coordinates>) -> Result<PolygonFence, GeofenceError> {
+        PolygonFence::with_holes(points, Vec::new())
+    }
+
+    /// Creates the fence from the coordinates of the outer ring and of the holes.
+    /// Returns GeofenceError::InvalidGeometry if any of the rings has less than 3 coordinates.
+    pub fn with_holes(
+        points: Vec<Coordinates>,
+        holes: Vec<Vec<Coordinates>>,
+    ) -> Result<PolygonFence, GeofenceError> {
+        check_ring(&points)?;
+        for hole in &holes {
+            check_ring(hole)?;
         }
+        Ok(PolygonFence {
+            points,
+            holes,
+            fill_rule: FillRule::default(),
+        })
+    }
+
+    /// Sets the rule used to decide if a coordinate is inside the fence.
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> PolygonFence {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Returns the coordinates that make up the outer ring of the fence.
+    pub fn points(&self) -> &[Coordinates] {
+        &self.points
+    }
+
+    /// Returns the holes of the fence.
+    pub fn holes(&self) -> &[Vec<Coordinates>] {
+        &self.holes
+    }
+
+    /// Returns the rule used to decide if a coordinate is inside the fence.
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    /// Returns the outer ring followed by the holes.
+    pub fn rings(&self) -> impl Iterator<Item = &[Coordinates]> {
+        Some(self.points.as_slice())
+            .into_iter()
+            .chain(self.holes.iter().map(|hole| hole.as_slice()))
+    }
+
+    /// Returns the edges of all the rings of the fence as pairs of consecutive coordinates,
+    /// the last edge of a ring joins its last coordinate back to the first one.
+    pub fn edges(&self) -> impl Iterator<Item = (&Coordinates, &Coordinates)> {
+        self.rings().flat_map(ring_edges)
+    }
+
+    /// Returns the defects of the outer ring and the holes, see validity::Defect.
+    /// The fence is valid when there are none.
+    pub fn validate(&self) -> Vec<Defect> {
+        validate_polygon(&self.points, &self.holes, self.fill_rule)
+    }
+
+    /// Returns the valid multipolygon that covers the same area as the fence, with the closing,
+    /// duplicate and spike vertices removed, the rings oriented and a polygon for each loop of
+    /// a ring whose edges cross. See validity::repair_polygon.
+    pub fn repair(&self) -> Result<MultiPolygonFence, GeofenceError> {
+        let polygons = repair_polygon(&self.points, &self.holes, self.fill_rule)?;
+        Ok(MultiPolygonFence::new(polygons)?.with_fill_rule(self.fill_rule))
     }
-    retval
 }
 
-///
-/// This method checks if a particular coordinate is within the polygon fence.
-/// This method uses Cramer's rule in verifying if a line drawn from a point is intersecting
-/// within the boundaries of the created fence.
-/// It returns boolean true if the coordinates are within the fence and
-/// false if the coordinates are outside the fence.
-///
+impl Geofence for PolygonFence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        locate_in_rings(point, self.rings(), self.fill_rule)
+    }
 
-fn contains(point: &Coordinates, points: &[Coordinates]) -> bool {
-    // Find the horizontal line equation that passes through the point.
-    // Use the Ax + By = C format and depict as a tuple of (A, B, C).
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(&self.points)
+    }
 
-    let point_horizon_eqn: (f64, f64, f64) = (0.0, 1.0, point.lon);
-    let points_len = points.len();
-    let mut retval = false;
-    if points.len() < 3 {
-        panic!("The supplied fence points should be more than 3 for creating proper polygon");
+    /// Area of the outer ring minus the area of the holes.
+    fn area(&self) -> f64 {
+        let holes: f64 = self.holes.iter().map(|hole| ring_area(hole).abs()).sum();
+        ring_area(&self.points).abs() - holes
     }
-    let line_eq: Vec<(f64, f64, f64)> = get_lineequation(&points, points_len);
-    let mut intersection_left: Vec<Coordinates> = Vec::new();
-    let mut intersection_right: Vec<Coordinates> = Vec::new();
-    for (_pos, coord) in line_eq.iter().enumerate() {
-        // find_intersect() function uses Cramers rule to verify if the line intersects or not.
-        let det1 = (coord.0 * point_horizon_eqn.1) - (coord.1 * point_horizon_eqn.0);
-        let detx = (coord.2 * point_horizon_eqn.1) - (coord.1 * point_horizon_eqn.2);
-        let dety = (coord.0 * point_horizon_eqn.2) - (coord.2 * point_horizon_eqn.0);
 
-        if det1 != 0.0 {
-            let point_coordinate = Coordinates::new(detx / det1, dety / det1);
-            let point1 = &points[_pos];
-            let point2 = &(points[((_pos + 1) % points_len)]);
-            let check_inbound: bool = check_inbounds(&point_coordinate, point1, point2);
-            if point_coordinate.lat < point.lat {
-                if !vec_contains(&point_coordinate, &intersection_left) && check_inbound {
-                    intersection_left.push(point_coordinate);
-                }
-            } else if !vec_contains(&point_coordinate, &intersection_right) && check_inbound {
-                intersection_right.push(point_coordinate);
-            }
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        if self.contains(point) {
+            return 0.0;
         }
+        self.edges()
+            .map(|(point1, point2)| distance_to_segment(point, point1, point2))
+            .fold(f64::INFINITY, f64::min)
     }
 
-    if (!intersection_left.is_empty() && !intersection_right.is_empty())
-        && (intersection_left.len() % 2 == 1 && intersection_right.len() % 2 == 1)
-    {
-        retval = true;
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        edge_intersections(start, end, self.edges())
+    }
+
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        let (nearest, distance) =
+            nearest_point_on_edges(point, self.edges()).unwrap_or((point.clone(), 0.0));
+        BoundaryDistance::new(distance, nearest, self.contains(point))
     }
-    retval
 }
 
 /// The below function reads the incoming json file which is of the format
@@ -132,97 +256,52 @@ fn contains(point: &Coordinates, points: &[Coordinates]) -> bool {
 //  "shape": "Polygon",
 //  "vehicle": "van",
 //  "shape_coordinate":[ {"lat": -2.0,"lon": 3.0},{"lat": 4.0,"lon": 4.0},
+//  "interior_coordinate":[[ {"lat": 0.0,"lon": 1.0},{"lat": 1.0,"lon": 1.0},{"lat": 1.0,"lon": 2.0}]],
+//  "fill_rule": "even_odd",
 //  "moving_coordinate": [{"lat":1.0 ,"lon": 1.0}]
 //}
 /// shape_coordinate -> contains the coordinates which is used to create the Polygon fence
+/// interior_coordinate -> optional, contains the holes that are excluded from the fence
+/// fill_rule -> optional, even_odd (default) or non_zero
 /// moving_coordinate -> contains series of lat, lon for which location has to be determined.
 /// maps it to the MovingTracker struct and returns the result to the caller.
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates
 /// Returns Result -> contains either MovingTracker struct if success or Error if any failure.
-
-fn read_movingtracker_file(filename: &str) -> Result<MovingTracker, Box<dyn Error>> {
-    // Open the file in read-only mode.
-
-    let current_path = env::current_dir();
-    let mut path_buff = current_path.ok().unwrap();
-    path_buff.push("data");
-    path_buff.push(filename);
-    let actual_path = path_buff.to_str().unwrap();
-
-    // Read the JSON contents of the file as an instance of `MovingTracker`.
-    let u = serde_json::from_reader(File::open(actual_path)?)?;
-
-    // Return the value as Result.
-    Ok(u)
-}
-
-/// The below function calls get_json_info  retrieves MovingTracker struct
-/// The function takes the below parameters
-/// filename -> contains the json file which contains the coordinates
-/// Returns MovingTracker
-
-fn get_json_info(filename: &str) -> MovingTracker {
-    // Read the JSON contents of the file as an instance of `MovingTracker`.
-
-    let json_structure = read_movingtracker_file(filename);
-    match json_structure{
-        Ok(json_structure)=> json_structure,
-        Err(error) => {
-            panic!("Problem opening the json file. Check if the file is correct and has correct json values. The error is : {:?}", error)
-        }
-    }
+fn read_movingtracker_file(filename: &str) -> Result<MovingTracker, GeofenceError> {
+    // Read the JSON contents of the file in the data folder as an instance of `MovingTracker`.
+    read_tracker_file(data_path(filename), Shape::Polygon)
 }
 
 /// The function does the below
 /// Reads the input file
 /// Creates polygon fence based on the coordinates given in the input file
 /// Executes set of coordinates for which position has to be determined.
-/// Prints if the coordinates are within or outside the fence
+/// Prints if the coordinates are within or outside the fence, and returns the evaluation of each of them
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates
+/// reporter -> prints the fence and the evaluations
 ///
-pub fn execute_polygon(filename: &str, delay: bool) -> String {
-    let second = time::Duration::from_millis(1000);
+pub fn execute_polygon(
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
     // let start = get_current_time();
-    let u = get_json_info(filename);
-    let cor: Vec<Coordinates> = u.shape_coordinate;
-    let run: Vec<Coordinates> = u.moving_coordinate;
-    //let mut vec_ret:Vec<String> = Vec:new();
-    let mut ret_val = "".to_string();
-    display_bold(
+    let u = read_movingtracker_file(filename)?;
+    let fence = PolygonFence::with_holes(u.shape_coordinate, u.interior_coordinate)?
+        .with_fill_rule(u.fill_rule);
+    reporter.display_bold(
         "Created Polygon Fence, with the below coordinates",
         Colour::Blue,
     );
-    display(&format!("{:?}", cor), Colour::Blue);
-    display_bold("Tracking and Verifying, if the below moving objects position is within or outside the fence", Colour::Black);
-
-    for x in run {
-        let mut str = "is out of the fence";
-        if delay {
-            thread::sleep(second);
-        }
-        if contains(&x, &cor) {
-            str = "is inside the fence";
-        }
-        display(
-            &format!(
-                "The {} positioned at latitude {}, longitude {}, {} ",
-                u.vehicle, x.lat, x.lon, &str
-            ),
-            Colour::Black,
-        );
-
-        ret_val.push_str(
-            format!(
-                "The {} positioned at latitude {}, longitude {}, {} \n ",
-                u.vehicle, x.lat, x.lon, &str
-            )
-            .as_str(),
-        );
-    }
-    // println!("The time taken for the execute method to perform this operation is {:?}", calculate_run_time(&start));
-    ret_val
+    reporter.display(&format!("{:?}", fence.points()), Colour::Blue);
+    Ok(track(
+        &fence,
+        filename,
+        &u.vehicle,
+        &u.moving_coordinate,
+        reporter,
+    ))
 }
 
 /// The function does the below
@@ -233,25 +312,31 @@ pub fn execute_polygon(filename: &str, delay: bool) -> String {
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates which is necessary to create the fence
 /// lat and lon -> search coordinates
-
-pub fn contains_in_polygon(filename: &str, lat: f64, lon: f64) -> bool {
-    let u = get_json_info(filename);
-    let cor: Vec<Coordinates> = u.shape_coordinate;
+/// reporter -> prints the fence and the result
+pub fn contains_in_polygon(
+    filename: &str,
+    lat: f64,
+    lon: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = PolygonFence::with_holes(u.shape_coordinate, u.interior_coordinate)?
+        .with_fill_rule(u.fill_rule);
     let point: Coordinates = Coordinates::new(lat, lon);
     let mut retval = false;
     // let start = get_current_time();
-    display_bold(
+    reporter.display_bold(
         "Searching the vehicle in Polygon Fence, which is built with coordinates",
         Colour::Blue,
     );
-    display(&format!("{:?}", cor), Colour::Blue);
+    reporter.display(&format!("{:?}", fence.points()), Colour::Blue);
     //println!("******** Searching the vehicle in Polygon Fence, which is built with coordinates {:?} *********", cor);
     let mut str = "is out of the fence";
-    if contains(&point, &cor) {
+    if fence.contains(&point) {
         str = "is inside the fence";
         retval = true;
     }
-    display(
+    reporter.display(
         &format!(
             "The {} positioned at latitude {}, longitude {}, {} ",
             u.vehicle, lat, lon, &str
@@ -259,5 +344,5 @@ pub fn contains_in_polygon(filename: &str, lat: f64, lon: f64) -> bool {
         Colour::Black,
     );
     //  println!("The time taken for the contain method to perform this operation is {:?}", calculate_run_time(&start));
-    retval
+    Ok(retval)
 }
@@ -0,0 +1,364 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// This module will create Rectangular fence, a box between a minimum and a maximum latitude and
+/// longitude, and will validate if the given coordinates is within the fence or not by comparing
+/// them with the limits of the box, without going through the edges of a polygon.
+/// A box whose minimum longitude is greater than its maximum longitude crosses the antimeridian,
+/// for example from 170 to -170 is the 20 degrees of longitude either side of 180.
+///
+use coordinates::*;
+use crossing::edge_intersections;
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::{BoundaryDistance, Geofence};
+use geofencer::track;
+use loader::{data_path, read_rectangle_tracker_file};
+use polygon::PolygonFence;
+use reporter::{Colour, Reporter};
+
+/// Returns the difference in degrees between two longitudes, going round the shorter way.
+fn lon_distance(lon1: f64, lon2: f64) -> f64 {
+    let difference = (lon1 - lon2).abs() % 360.0;
+    difference.min(360.0 - difference)
+}
+
+/// Rectangular fence built from the minimum and maximum latitude and longitude.
+#[derive(Debug, Clone)]
+pub struct RectangleFence {
+    min_lat: f64,
+    min_lon: f64,
+    max_lat: f64,
+    max_lon: f64,
+}
+
+impl RectangleFence {
+    /// Creates the fence from the limits of the box. The box crosses the antimeridian when
+    /// min_lon is greater than max_lon.
+    /// Returns GeofenceError::InvalidGeometry if min_lat is greater than max_lat, or if the
+    /// latitudes are not between -90 and 90 or the longitudes between -180 and 180.
+    pub fn new(
+        min_lat: f64,
+        min_lon: f64,
+        max_lat: f64,
+        max_lon: f64,
+    ) -> Result<RectangleFence, GeofenceError> {
+        let valid_lat = |lat: f64| (-90.0..=90.0).contains(&lat);
+        let valid_lon = |lon: f64| (-180.0..=180.0).contains(&lon);
+        if !(valid_lat(min_lat) && valid_lat(max_lat) && valid_lon(min_lon) && valid_lon(max_lon)) {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "the limits of a rectangle fence must be latitudes between -90 and 90 and \
+                 longitudes between -180 and 180, but {}, {}, {}, {} were supplied",
+                min_lat, min_lon, max_lat, max_lon
+            )));
+        }
+        if min_lat > max_lat {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "the minimum latitude {} of a rectangle fence is greater than its maximum latitude {}",
+                min_lat, max_lat
+            )));
+        }
+        Ok(RectangleFence {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+        })
+    }
+
+    /// Creates the fence from the limits given in the json file.
+    pub fn from_coordinates(
+        rectangle: RectangleCoordinates,
+    ) -> Result<RectangleFence, GeofenceError> {
+        RectangleFence::new(
+            rectangle.min_lat,
+            rectangle.min_lon,
+            rectangle.max_lat,
+            rectangle.max_lon,
+        )
+    }
+
+    pub fn min_lat(&self) -> f64 {
+        self.min_lat
+    }
+
+    pub fn min_lon(&self) -> f64 {
+        self.min_lon
+    }
+
+    pub fn max_lat(&self) -> f64 {
+        self.max_lat
+    }
+
+    pub fn max_lon(&self) -> f64 {
+        self.max_lon
+    }
+
+    /// Returns true if the box crosses the antimeridian, going east from min_lon past 180
+    /// to max_lon.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_lon > self.max_lon
+    }
+
+    /// Returns the width of the box in longitude degrees.
+    pub fn lon_span(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.max_lon - self.min_lon + 360.0
+        } else {
+            self.max_lon - self.min_lon
+        }
+    }
+
+    /// Returns true if the longitude is between the western and the eastern side of the box.
+    fn contains_lon(&self, lon: f64) -> bool {
+        if self.crosses_antimeridian() {
+            lon >= self.min_lon || lon <= self.max_lon
+        } else {
+            lon >= self.min_lon && lon <= self.max_lon
+        }
+    }
+
+    /// Returns the western and eastern longitudes of the box, split at the antimeridian when
+    /// the box crosses it.
+    fn lon_ranges(&self) -> Vec<(f64, f64)> {
+        if self.crosses_antimeridian() {
+            vec![(self.min_lon, 180.0), (-180.0, self.max_lon)]
+        } else {
+            vec![(self.min_lon, self.max_lon)]
+        }
+    }
+
+    /// Returns the box as polygons, one polygon or two polygons split at the antimeridian,
+    /// for the formats that have no rectangles.
+    pub fn to_polygons(&self) -> Result<Vec<PolygonFence>, GeofenceError> {
+        self.lon_ranges()
+            .into_iter()
+            .map(|(west, east)| {
+                PolygonFence::new(vec![
+                    Coordinates::new(self.min_lat, west),
+                    Coordinates::new(self.min_lat, east),
+                    Coordinates::new(self.max_lat, east),
+                    Coordinates::new(self.max_lat, west),
+                ])
+            })
+            .collect()
+    }
+
+    /// Returns the sides of the box moved east by the given degrees of longitude. The eastern
+    /// side of a box that crosses the antimeridian is put past 180, so that the sides join up.
+    fn sides(&self, shift: f64) -> Vec<(Coordinates, Coordinates)> {
+        let west = self.min_lon + shift;
+        let east = west + self.lon_span();
+        let corners = [
+            Coordinates::new(self.min_lat, west),
+            Coordinates::new(self.min_lat, east),
+            Coordinates::new(self.max_lat, east),
+            Coordinates::new(self.max_lat, west),
+        ];
+        (0..corners.len())
+            .map(|corner| {
+                (
+                    corners[corner].clone(),
+                    corners[(corner + 1) % corners.len()].clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the point of the box nearest to the coordinates, which are the coordinates
+    /// themselves when they are inside the box.
+    fn clamp(&self, point: &Coordinates) -> Coordinates {
+        let lat = point.lat.clamp(self.min_lat, self.max_lat);
+        let lon = if self.contains_lon(point.lon) {
+            point.lon
+        } else if lon_distance(point.lon, self.min_lon) <= lon_distance(point.lon, self.max_lon) {
+            self.min_lon
+        } else {
+            self.max_lon
+        };
+        Coordinates::new(lat, lon)
+    }
+}
+
+impl Geofence for RectangleFence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        if point.lat < self.min_lat || point.lat > self.max_lat || !self.contains_lon(point.lon) {
+            Location::Outside
+        } else if point.lat == self.min_lat
+            || point.lat == self.max_lat
+            || point.lon == self.min_lon
+            || point.lon == self.max_lon
+        {
+            Location::OnBoundary
+        } else {
+            Location::Inside
+        }
+    }
+
+    /// A box that crosses the antimeridian spans every longitude in its bounding box, as the
+    /// bounding box cannot go past 180.
+    fn bounding_box(&self) -> BoundingBox {
+        if self.crosses_antimeridian() {
+            BoundingBox::new(self.min_lat, -180.0, self.max_lat, 180.0)
+        } else {
+            BoundingBox::new(self.min_lat, self.min_lon, self.max_lat, self.max_lon)
+        }
+    }
+
+    fn area(&self) -> f64 {
+        (self.max_lat - self.min_lat) * self.lon_span()
+    }
+
+    /// Longitudes are compared going round the shorter way, across the antimeridian if needed.
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        let nearest = self.clamp(point);
+        (point.lat - nearest.lat).hypot(lon_distance(point.lon, nearest.lon))
+    }
+
+    /// The segment goes the shorter way round, so its end is moved by a full turn when it is
+    /// more than 180 degrees of longitude away from the start. It is then intersected with the
+    /// box and with the box moved by one and two turns either way, as the segment can reach
+    /// past 180 or -180.
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        let d_lon = end.lon - start.lon;
+        let end = if d_lon > 180.0 {
+            Coordinates::new(end.lat, end.lon - 360.0)
+        } else if d_lon < -180.0 {
+            Coordinates::new(end.lat, end.lon + 360.0)
+        } else {
+            end.clone()
+        };
+        let mut fractions = Vec::new();
+        for turns in -2..=2 {
+            let sides = self.sides(f64::from(turns) * 360.0);
+            fractions.extend(edge_intersections(
+                start,
+                &end,
+                sides.iter().map(|(point1, point2)| (point1, point2)),
+            ));
+        }
+        fractions
+    }
+
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        if !self.contains(point) {
+            let nearest = self.clamp(point);
+            let distance = (point.lat - nearest.lat).hypot(lon_distance(point.lon, nearest.lon));
+            return BoundaryDistance::new(distance, nearest, false);
+        }
+        // Inside the box the nearest side is straight to the south, north, west or east.
+        let candidates = [
+            (
+                point.lat - self.min_lat,
+                Coordinates::new(self.min_lat, point.lon),
+            ),
+            (
+                self.max_lat - point.lat,
+                Coordinates::new(self.max_lat, point.lon),
+            ),
+            (
+                lon_distance(point.lon, self.min_lon),
+                Coordinates::new(point.lat, self.min_lon),
+            ),
+            (
+                lon_distance(point.lon, self.max_lon),
+                Coordinates::new(point.lat, self.max_lon),
+            ),
+        ];
+        let (distance, nearest) = candidates
+            .iter()
+            .min_by(|(distance1, _), (distance2, _)| distance1.total_cmp(distance2))
+            .cloned()
+            .unwrap_or((0.0, point.clone()));
+        BoundaryDistance::new(distance, nearest, true)
+    }
+}
+
+/// The below function reads the incoming json file which is of the format
+///{
+//  "shape": "Rectangle",
+//  "vehicle": "van",
+//  "shape_coordinate":{"min_lat": -2.0,"min_lon": 3.0,"max_lat": 4.0,"max_lon": 6.0},
+//  "moving_coordinate": [{"lat":1.0 ,"lon": 4.0}]
+//}
+/// shape_coordinate -> contains the limits of the box, which is used to create the rectangular fence.
+/// The box crosses the antimeridian when min_lon is greater than max_lon.
+/// moving_coordinate -> contains series of lat, lon for which location has to be determined.
+/// maps it to the MovingTrackerRectangle struct and returns the result to the caller.
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates
+/// Returns Result -> contains either MovingTrackerRectangle struct if success or Error if any failure.
+fn read_movingtracker_file(filename: &str) -> Result<MovingTrackerRectangle, GeofenceError> {
+    read_rectangle_tracker_file(data_path(filename))
+}
+
+/// The function does the below
+/// Reads the input file
+/// Creates rectangular fence based on the limits given in the input file
+/// Executes set of coordinates for which position has to be determined.
+/// Prints if the coordinates are within or outside the fence, and returns the evaluation of each of them
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates
+/// reporter -> prints the fence and the evaluations
+///
+pub fn execute_rectangle(
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = RectangleFence::from_coordinates(u.shape_coordinate)?;
+    reporter.display_bold(
+        "Created Rectangular Fence, with the below limits",
+        Colour::Blue,
+    );
+    reporter.display(&format!("{:?}", fence), Colour::Blue);
+    Ok(track(
+        &fence,
+        filename,
+        &u.vehicle,
+        &u.moving_coordinate,
+        reporter,
+    ))
+}
+
+/// The function does the below
+/// Reads the input file
+/// Creates rectangular fence based on the limits given in the input file
+/// checks if the given coordinates in the function paramater is within the rectangular fence.
+/// Prints if the coordinates are within or outside the fence
+///The function the below parameters
+/// filename -> contains the json file which contains the coordinates which is necessary to create the fence
+/// lat and lon -> search coordinates
+/// reporter -> prints the fence and the result
+pub fn contains_in_rectangle(
+    filename: &str,
+    lat: f64,
+    lon: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
+    let u = read_movingtracker_file(filename)?;
+    let fence = RectangleFence::from_coordinates(u.shape_coordinate)?;
+    let point: Coordinates = Coordinates::new(lat, lon);
+    let mut retval = false;
+    reporter.display_bold(
+        "Searching the vehicle in Rectangular Fence, which is built with limits",
+        Colour::Blue,
+    );
+    reporter.display(&format!("{:?}", fence), Colour::Blue);
+    let mut str = "is out of the fence";
+    if fence.contains(&point) {
+        str = "is inside the fence";
+        retval = true;
+    }
+    reporter.display(
+        &format!(
+            "The {} positioned at latitude {}, longitude {}, {} ",
+            u.vehicle, lat, lon, &str
+        ),
+        Colour::Black,
+    );
+    Ok(retval)
+}
@@ -0,0 +1,162 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Prints the progress of the execute and contains functions, so that the geometry modules
+/// themselves never write to the terminal. The caller picks the reporter:
+/// AnsiReporter prints in colour (needs the "ansi" feature, on by default), PlainReporter prints
+/// plain text, for log files, and NoopReporter prints nothing, for embedding the library in a
+/// server.
+///
+#[cfg(feature = "ansi")]
+extern crate ansi_term;
+
+use evaluation::Evaluation;
+use std::thread;
+use std::time::Duration;
+
+/// Colours of the text printed by the reporters. Reporters without colours ignore them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Blue,
+    Yellow,
+    Purple,
+    Black,
+}
+
+/// Receives the text printed while the fences are created and the positions are evaluated.
+pub trait Reporter {
+    /// Prints a line of text.
+    fn display(&self, text: &str, colour: Colour);
+
+    /// Prints a line of text in bold, used for headings.
+    fn display_bold(&self, text: &str, colour: Colour);
+
+    /// Prints a line of text in bold and underlined, used for titles.
+    fn display_underline(&self, text: &str, colour: Colour);
+
+    /// Time to wait between the positions of a vehicle, to simulate a moving object.
+    fn delay(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Prints the evaluations, one sentence per position, waiting for the delay between them.
+    fn display_evaluations(&self, evaluations: &[Evaluation]) {
+        self.display_bold(
+            "Tracking and Verifying, if the below moving objects position is within or outside the fence",
+            Colour::Black,
+        );
+        for evaluation in evaluations {
+            if let Some(delay) = self.delay() {
+                thread::sleep(delay);
+            }
+            self.display(&evaluation.to_string(), Colour::Black);
+        }
+    }
+}
+
+/// Prints in colour to the standard output, with the ANSI escape codes.
+#[cfg(feature = "ansi")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct AnsiReporter {
+    delay: Option<Duration>,
+}
+
+#[cfg(feature = "ansi")]
+impl AnsiReporter {
+    pub fn new() -> AnsiReporter {
+        AnsiReporter::default()
+    }
+
+    /// Waits for the given time between the positions of a vehicle.
+    pub fn with_delay(mut self, delay: Duration) -> AnsiReporter {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+#[cfg(feature = "ansi")]
+fn ansi_colour(colour: Colour) -> ansi_term::Colour {
+    match colour {
+        Colour::Red => ansi_term::Colour::Red,
+        Colour::Blue => ansi_term::Colour::Blue,
+        Colour::Yellow => ansi_term::Colour::Yellow,
+        Colour::Purple => ansi_term::Colour::Purple,
+        Colour::Black => ansi_term::Colour::Black,
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl Reporter for AnsiReporter {
+    fn display(&self, text: &str, colour: Colour) {
+        match colour {
+            Colour::Black => println!("{}", text),
+            _ => println!("{}", ansi_colour(colour).paint(text)),
+        }
+    }
+
+    fn display_bold(&self, text: &str, colour: Colour) {
+        println!("{}", ansi_colour(colour).bold().paint(text));
+    }
+
+    fn display_underline(&self, text: &str, colour: Colour) {
+        println!("{}", ansi_colour(colour).bold().underline().paint(text));
+    }
+
+    fn delay(&self) -> Option<Duration> {
+        self.delay
+    }
+}
+
+/// Prints plain text to the standard output, without colours.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PlainReporter {
+    delay: Option<Duration>,
+}
+
+impl PlainReporter {
+    pub fn new() -> PlainReporter {
+        PlainReporter::default()
+    }
+
+    /// Waits for the given time between the positions of a vehicle.
+    pub fn with_delay(mut self, delay: Duration) -> PlainReporter {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+impl Reporter for PlainReporter {
+    fn display(&self, text: &str, _colour: Colour) {
+        println!("{}", text);
+    }
+
+    fn display_bold(&self, text: &str, _colour: Colour) {
+        println!("{}", text);
+    }
+
+    fn display_underline(&self, text: &str, _colour: Colour) {
+        println!("{}", text);
+    }
+
+    fn delay(&self) -> Option<Duration> {
+        self.delay
+    }
+}
+
+/// Prints nothing and never waits.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoopReporter;
+
+impl Reporter for NoopReporter {
+    fn display(&self, _text: &str, _colour: Colour) {}
+
+    fn display_bold(&self, _text: &str, _colour: Colour) {}
+
+    fn display_underline(&self, _text: &str, _colour: Colour) {}
+
+    fn display_evaluations(&self, _evaluations: &[Evaluation]) {}
+}
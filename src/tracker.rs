@@ -0,0 +1,232 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Stateful tracker that consumes a stream of positions for each vehicle and emits an event only
+/// when the state of the vehicle with respect to a fence changes:
+/// Enter when the vehicle comes in to the fence, Exit when it leaves the fence and
+/// Dwell once per visit when it has stayed inside the fence for longer than the dwell threshold.
+///
+use coordinates::{Coordinates, Fix};
+use crossing::{segment_crossings, Crossing};
+use fence::Geofence;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Dwell threshold used when none is given to the tracker.
+pub const DEFAULT_DWELL_THRESHOLD: Duration = Duration::from_secs(300);
+
+/// The change in the state of a vehicle with respect to a fence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    Enter,
+    Exit,
+    /// The vehicle has been inside the fence for the given duration.
+    Dwell(Duration),
+}
+
+/// Event emitted by the tracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub vehicle: String,
+    pub fence_id: String,
+    pub kind: EventKind,
+    /// Position of the vehicle that triggered the event.
+    pub position: Fix,
+    pub time: SystemTime,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let action = match self.kind {
+            EventKind::Enter => "entered".to_string(),
+            EventKind::Exit => "exited".to_string(),
+            EventKind::Dwell(duration) => {
+                format!("has been inside for {} seconds in", duration.as_secs())
+            }
+        };
+        write!(
+            f,
+            "The {} {} the fence {} at latitude {}, longitude {}",
+            self.vehicle, action, self.fence_id, self.position.lat, self.position.lon
+        )
+    }
+}
+
+/// State of a vehicle inside a fence.
+#[derive(Debug, Copy, Clone)]
+struct Visit {
+    entered: SystemTime,
+    dwell_reported: bool,
+}
+
+/// Keeps the fences and the state of every vehicle with respect to each of the fences.
+pub struct Tracker {
+    fences: Vec<(String, Box<dyn Geofence>)>,
+    dwell_threshold: Duration,
+    /// Visits in progress, keyed by vehicle and then by fence id.
+    visits: HashMap<String, HashMap<String, Visit>>,
+    /// Time of the last position consumed for each vehicle.
+    last_seen: HashMap<String, SystemTime>,
+}
+
+impl Default for Tracker {
+    fn default() -> Tracker {
+        Tracker::new()
+    }
+}
+
+impl Tracker {
+    pub fn new() -> Tracker {
+        Tracker {
+            fences: Vec::new(),
+            dwell_threshold: DEFAULT_DWELL_THRESHOLD,
+            visits: HashMap::new(),
+            last_seen: HashMap::new(),
+        }
+    }
+
+    /// Sets how long a vehicle has to stay inside a fence before a Dwell event is emitted.
+    pub fn with_dwell_threshold(mut self, dwell_threshold: Duration) -> Tracker {
+        self.dwell_threshold = dwell_threshold;
+        self
+    }
+
+    /// Adds a fence to be tracked, identified by the given id.
+    pub fn add_fence<F: Geofence + 'static>(&mut self, id: &str, fence: F) {
+        self.fences.push((id.to_string(), Box::new(fence)));
+    }
+
+    /// Removes the fence with the given id, along with the visits in progress for it.
+    /// Returns false if there was no fence with that id.
+    pub fn remove_fence(&mut self, id: &str) -> bool {
+        let len = self.fences.len();
+        self.fences.retain(|(fence_id, _)| fence_id != id);
+        for visits in self.visits.values_mut() {
+            visits.remove(id);
+        }
+        self.fences.len() != len
+    }
+
+    /// Returns true if the vehicle was inside the fence at its last position.
+    pub fn is_inside(&self, vehicle: &str, fence_id: &str) -> bool {
+        self.visits
+            .get(vehicle)
+            .is_some_and(|visits| visits.contains_key(fence_id))
+    }
+
+    /// Takes the two consecutive fixes as a segment, and returns the fences that the segment
+    /// intersects, along with the crossings of their borders. The crossings are empty when the
+    /// whole segment is inside the fence.
+    /// Unlike update, this catches a vehicle that cut through a fence between two fixes that are
+    /// both outside it. The state of the vehicles is not changed.
+    pub fn segment_crossings(&self, from: &Fix, to: &Fix) -> Vec<(String, Vec<Crossing>)> {
+        let start = from.coordinates();
+        self.fences
+            .iter()
+            .filter_map(|(fence_id, fence)| {
+                let crossings = segment_crossings(fence.as_ref(), from, to);
+                if crossings.is_empty() && !fence.contains(&start) {
+                    return None;
+                }
+                Some((fence_id.clone(), crossings))
+            })
+            .collect()
+    }
+
+    /// Consumes the next position of the vehicle, and returns the events caused by it.
+    /// A vehicle that is seen for the first time is considered to be outside all the fences.
+    /// Positions older than the last position of the vehicle arrived out of sequence,
+    /// they are ignored and cause no events.
+    pub fn update(
+        &mut self,
+        vehicle: &str,
+        position: &Coordinates,
+        time: SystemTime,
+    ) -> Vec<Event> {
+        let timestamp = time
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs_f64())
+            .ok();
+        let fix = Fix {
+            timestamp,
+            ..Fix::from(position.clone())
+        };
+        self.update_at(vehicle, &fix, time)
+    }
+
+    /// Consumes the next fix of the vehicle, and returns the events caused by it.
+    /// The time of the fix is used to calculate the dwell time. Fixes without a timestamp are
+    /// kept out of the timeline of the vehicle: they are taken to be recorded at the time of the
+    /// last fix of the vehicle (or now, for its first fix), and do not cause the fixes with a
+    /// timestamp that follow them to be ignored as out of sequence.
+    pub fn update_fix(&mut self, vehicle: &str, fix: &Fix) -> Vec<Event> {
+        match fix.time() {
+            Some(time) => self.update_at(vehicle, fix, time),
+            None => {
+                let last = self.last_seen.get(vehicle).cloned();
+                let events = self.update_at(vehicle, fix, last.unwrap_or_else(SystemTime::now));
+                match last {
+                    Some(last) => self.last_seen.insert(vehicle.to_string(), last),
+                    None => self.last_seen.remove(vehicle),
+                };
+                events
+            }
+        }
+    }
+
+    fn update_at(&mut self, vehicle: &str, fix: &Fix, time: SystemTime) -> Vec<Event> {
+        if self.last_seen.get(vehicle).is_some_and(|last| time < *last) {
+            return Vec::new();
+        }
+        self.last_seen.insert(vehicle.to_string(), time);
+        let position = fix.coordinates();
+        let mut events = Vec::new();
+        let visits = self.visits.entry(vehicle.to_string()).or_default();
+        for (fence_id, fence) in &self.fences {
+            let inside = fence.contains(&position);
+            let mut event = |kind| {
+                events.push(Event {
+                    vehicle: vehicle.to_string(),
+                    fence_id: fence_id.clone(),
+                    kind,
+                    position: fix.clone(),
+                    time,
+                })
+            };
+            match (visits.get_mut(fence_id), inside) {
+                (None, true) => {
+                    visits.insert(
+                        fence_id.clone(),
+                        Visit {
+                            entered: time,
+                            dwell_reported: false,
+                        },
+                    );
+                    event(EventKind::Enter);
+                }
+                (Some(_), false) => {
+                    visits.remove(fence_id);
+                    event(EventKind::Exit);
+                }
+                (Some(visit), true) => {
+                    // A visit started by a fix without a timestamp, taken to be now, starts
+                    // again at the first fix recorded before it.
+                    if time < visit.entered {
+                        visit.entered = time;
+                    }
+                    let dwell = time.duration_since(visit.entered).unwrap_or_default();
+                    if !visit.dwell_reported && dwell >= self.dwell_threshold {
+                        visit.dwell_reported = true;
+                        event(EventKind::Dwell(dwell));
+                    }
+                }
+                (None, false) => {}
+            }
+        }
+        events
+    }
+}
@@ -7,10 +7,15 @@
 /// This module will create Triangular fence and will validate if the given coordinates is within the fence or not
 ///
 use coordinates::*;
-use std::env;
-use std::error::Error;
-use std::fs::File;
-use std::{thread, time};
+use crossing::edge_intersections;
+use error::GeofenceError;
+use evaluation::Evaluation;
+use fence::{BoundaryDistance, Geofence};
+use geofencer::track;
+use loader::{data_path, read_tracker_file};
+use polygon::ring_edges;
+use reporter::{Colour, Reporter};
+use validity::{validate_ring, Defect};
 
 // Triangle
 fn get_area(side1: &Coordinates, side2: &Coordinates, side3: &Coordinates) -> f64 {
@@ -20,35 +25,151 @@ fn get_area(side1: &Coordinates, side2: &Coordinates, side3: &Coordinates) -> f6
     area.abs()
 }
 
+/// Default tolerance of the triangular fence, in latitude / longitude degrees.
+/// Coordinates that are closer than this to a side of the triangle are on its border.
+pub const DEFAULT_TOLERANCE: f64 = 1e-9;
+
 ///
-/// This method checks if a particular coordinate is within the triangular fence.
-/// This method  verifies if a line drawn from a point is intersecting
-/// within the boundaries of the created fence.
-/// It returns boolean true if the coordinates are within the fence and
-/// false if the coordinates are outside the fence.
+/// This method checks if a particular coordinate is inside, outside or on the border of the
+/// triangular fence.
+/// For each side of the triangle, it calculates the distance of the coordinate from the side,
+/// signed with the orientation of the coordinate with respect to the side. The coordinate is
+/// inside when it is on the inner side of all the three sides by more than the tolerance, and
+/// outside when it is on the outer side of any of the sides by more than the tolerance.
+/// Otherwise the coordinate is on the border of the fence.
 ///
+pub fn locate(triangle: &[Coordinates], point: &Coordinates, tolerance: f64) -> Location {
+    if triangle.len() != 3 {
+        return Location::Outside;
+    }
+    // Positive if the vertices are counter clockwise, negative if they are clockwise.
+    let turn = orientation(&triangle[0], &triangle[1], &triangle[2]);
+    if turn == 0.0 {
+        // All the three vertices are on a line, so there is no inside.
+        let on_side = (0..3).any(|pos| {
+            distance_to_segment(point, &triangle[pos], &triangle[(pos + 1) % 3]) <= tolerance
+        });
+        return if on_side {
+            Location::OnBoundary
+        } else {
+            Location::Outside
+        };
+    }
+    let mut location = Location::Inside;
+    for pos in 0..3 {
+        let side1 = &triangle[pos];
+        let side2 = &triangle[(pos + 1) % 3];
+        // orientation is twice the area of the triangle formed with the side, so dividing it
+        // by the length of the side gives the distance of the coordinate from the side.
+        let signed_distance =
+            turn.signum() * orientation(side1, side2, point) / distance(side1, side2);
+        if signed_distance < -tolerance {
+            return Location::Outside;
+        }
+        if signed_distance <= tolerance {
+            location = Location::OnBoundary;
+        }
+    }
+    location
+}
 
+///
+/// This method checks if a particular coordinate is within the triangular fence,
+/// using the default tolerance.
+/// It returns boolean true if the coordinates are within or on the border of the fence and
+/// false if the coordinates are outside the fence.
+///
 pub fn contains(triangle: &[Coordinates], point: &Coordinates) -> bool {
-    let mut b_contains = false;
-    if triangle.len() == 3 {
-        let side1 = &triangle[0];
-        let side2 = &triangle[1];
-        let side3 = &triangle[2];
-        //Calculate area of triangle ABC
-        let area_abc = get_area(side1, side2, side3);
-        println!("area {:?}", area_abc);
-        //Calculate area of triangle PBC
-        let area_pbc = get_area(point, side2, side3);
-        //Calculate area of triangle PAC
-        let area_pac = get_area(side1, point, side3);
-        //Calculate area of triangle PAB
-        let area_pab = get_area(side1, side2, point);
-        // Check if sum of area_pbc, area_pac and area_pab  is equal to area_abc
-        if (area_abc - (area_pab + area_pac + area_pbc)).abs() == 0.0 {
-            b_contains = true;
+    locate(triangle, point, DEFAULT_TOLERANCE) != Location::Outside
+}
+
+/// Triangular fence built from exactly three coordinates.
+/// Coordinates within the tolerance of a side of the triangle are on the border of the fence.
+#[derive(Debug, Clone)]
+pub struct TriangleFence {
+    vertices: Vec<Coordinates>,
+    tolerance: f64,
+}
+
+impl TriangleFence {
+    pub fn new(vertex1: Coordinates, vertex2: Coordinates, vertex3: Coordinates) -> TriangleFence {
+        TriangleFence {
+            vertices: vec![vertex1, vertex2, vertex3],
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Sets the tolerance of the fence, in latitude / longitude degrees.
+    pub fn with_tolerance(mut self, tolerance: f64) -> TriangleFence {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Returns the tolerance of the fence.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// Creates the fence from a list of coordinates.
+    /// Returns GeofenceError::InvalidGeometry if the list does not contain exactly three coordinates.
+    pub fn from_points(points: &[Coordinates]) -> Result<TriangleFence, GeofenceError> {
+        if points.len() != 3 {
+            return Err(GeofenceError::InvalidGeometry(format!(
+                "a triangle fence needs exactly 3 coordinates, but {} were supplied",
+                points.len()
+            )));
+        }
+        Ok(TriangleFence {
+            vertices: points.to_vec(),
+            tolerance: DEFAULT_TOLERANCE,
+        })
+    }
+
+    /// Returns the three coordinates that make up the fence.
+    pub fn vertices(&self) -> &[Coordinates] {
+        &self.vertices
+    }
+
+    /// Returns the defects of the triangle, such as vertices that are repeated or on a line.
+    pub fn validate(&self) -> Vec<Defect> {
+        validate_ring(&self.vertices)
+    }
+}
+
+impl Geofence for TriangleFence {
+    fn locate(&self, point: &Coordinates) -> Location {
+        locate(&self.vertices, point, self.tolerance)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(&self.vertices)
+    }
+
+    fn area(&self) -> f64 {
+        // get_area returns twice the area of the triangle.
+        get_area(&self.vertices[0], &self.vertices[1], &self.vertices[2]) / 2.0
+    }
+
+    fn distance_to(&self, point: &Coordinates) -> f64 {
+        if self.contains(point) {
+            return 0.0;
         }
+        (0..3)
+            .map(|pos| {
+                distance_to_segment(point, &self.vertices[pos], &self.vertices[(pos + 1) % 3])
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn segment_intersections(&self, start: &Coordinates, end: &Coordinates) -> Vec<f64> {
+        edge_intersections(start, end, ring_edges(&self.vertices))
+    }
+
+    fn boundary_distance(&self, point: &Coordinates) -> BoundaryDistance {
+        let (nearest, distance) = nearest_point_on_edges(point, ring_edges(&self.vertices))
+            .unwrap_or((point.clone(), 0.0));
+        BoundaryDistance::new(distance, nearest, self.contains(point))
     }
-    b_contains
 }
 
 /// The below function reads the incoming json file which is of the format
@@ -64,85 +185,39 @@ pub fn contains(triangle: &[Coordinates], point: &Coordinates) -> bool {
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates
 /// Returns Result -> contains either MovingTracker struct if success or Error if any failure.
-pub fn read_movingtracker_file(filename: &str) -> Result<MovingTracker, Box<dyn Error>> {
-    // Open the file in read-only mode.
-
-    let current_path = env::current_dir();
-    let mut path_buff = current_path.ok().unwrap();
-    path_buff.push("data");
-    path_buff.push(filename);
-    let actual_path = path_buff.to_str().unwrap();
-
-    // Read the JSON contents of the file as an instance of `MovingTracker`.
-    let u = serde_json::from_reader(File::open(actual_path)?)?;
-
-    // Return the value as Result.
-    Ok(u)
+pub fn read_movingtracker_file(filename: &str) -> Result<MovingTracker, GeofenceError> {
+    // Read the JSON contents of the file in the data folder as an instance of `MovingTracker`.
+    read_tracker_file(data_path(filename), Shape::Triangle)
 }
 
-/// The below function calls get_json_info  retrieves MovingTracker struct
-/// The function takes the below parameters
-/// filename -> contains the json file which contains the coordinates
-/// Returns MovingTracker
-pub fn get_json_info(filename: &str) -> MovingTracker {
-    // Read the JSON contents of the file as an instance of `MovingTracker`.
-
-    let json_structure = read_movingtracker_file(filename);
-    match json_structure{
-        Ok(json_structure)=> json_structure,
-        Err(error) => {
-            panic!("Problem opening the json file. Check if the file is correct and has correct json values. The error is : {:?}", error)
-        }
-    }
-}
 /// The function does the below
 /// Reads the input file
 /// Creates triangular fence based on the coordinates given in the input file
 /// Executes set of coordinates for which position has to be determined.
-/// Prints if the coordinates are within or outside the fence
+/// Prints if the coordinates are within or outside the fence, and returns the evaluation of each of them
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates
+/// reporter -> prints the fence and the evaluations
 ///
-pub fn execute_triangle(filename: &str, delay: bool) -> String {
-    let second = time::Duration::from_millis(1000);
+pub fn execute_triangle(
+    filename: &str,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Evaluation>, GeofenceError> {
     //let start = get_current_time();
-    let u = get_json_info(filename);
-    let cor: Vec<Coordinates> = u.shape_coordinate;
-    let run: Vec<Coordinates> = u.moving_coordinate;
-    let mut ret_val = "".to_string();
-    display_bold(
+    let u = read_movingtracker_file(filename)?;
+    let fence = TriangleFence::from_points(&u.shape_coordinate)?;
+    reporter.display_bold(
         "Created Triangular Fence, with the below coordinates",
         Colour::Blue,
     );
-    display(&format!("{:?}", cor), Colour::Blue);
-    display_bold("Tracking and Verifying, if the below moving objects position is within or outside the fence", Colour::Black);
-
-    for x in run {
-        let mut str = "is out of the fence";
-        if delay {
-            thread::sleep(second);
-        }
-
-        if contains(&cor, &x) {
-            str = "is inside the fence";
-        }
-        display(
-            &format!(
-                "The {} positioned at latitude {}, longitude {}, {} ",
-                u.vehicle, x.lat, x.lon, &str
-            ),
-            Colour::Black,
-        );
-        ret_val.push_str(
-            format!(
-                "The {} positioned at latitude {}, longitude {}, {} \n ",
-                u.vehicle, x.lat, x.lon, &str
-            )
-            .as_str(),
-        );
-    }
-    //  println!("The time taken for the execute method to perform this operation is {:?}", calculate_run_time(&start));
-    ret_val
+    reporter.display(&format!("{:?}", fence.vertices()), Colour::Blue);
+    Ok(track(
+        &fence,
+        filename,
+        &u.vehicle,
+        &u.moving_coordinate,
+        reporter,
+    ))
 }
 
 /// The function does the below
@@ -153,23 +228,29 @@ pub fn execute_triangle(filename: &str, delay: bool) -> String {
 ///The function the below parameters
 /// filename -> contains the json file which contains the coordinates which is necessary to create the fence
 /// lat and lon -> search coordinates
-pub fn contains_in_triangle(filename: &str, lat: f64, lon: f64) -> bool {
+/// reporter -> prints the fence and the result
+pub fn contains_in_triangle(
+    filename: &str,
+    lat: f64,
+    lon: f64,
+    reporter: &dyn Reporter,
+) -> Result<bool, GeofenceError> {
     // let start = get_current_time();
-    let u = get_json_info(filename);
-    let cor: Vec<Coordinates> = u.shape_coordinate;
+    let u = read_movingtracker_file(filename)?;
+    let fence = TriangleFence::from_points(&u.shape_coordinate)?;
     let point: Coordinates = Coordinates::new(lat, lon);
     let mut retval = false;
-    display_bold(
+    reporter.display_bold(
         "Searching the vehicle in Polygon Fence, which is built with coordinates",
         Colour::Blue,
     );
-    display(&format!("{:?}", cor), Colour::Blue);
+    reporter.display(&format!("{:?}", fence.vertices()), Colour::Blue);
     let mut str = "is out of the fence";
-    if contains(&cor, &point) {
+    if fence.contains(&point) {
         str = "is inside the fence";
         retval = true;
     }
-    display(
+    reporter.display(
         &format!(
             "The {} positioned at latitude {}, longitude {}, {} ",
             u.vehicle, lat, lon, &str
@@ -177,5 +258,5 @@ pub fn contains_in_triangle(filename: &str, lat: f64, lon: f64) -> bool {
         Colour::Black,
     );
     //  println!("The time taken for the execute method to perform this operation is {:?}", calculate_run_time(&start));
-    retval
+    Ok(retval)
 }
@@ -0,0 +1,699 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Checks the rings of polygon fences for the defects that make polygon::contains give
+/// surprising answers: a last coordinate that closes the ring explicitly, duplicate consecutive
+/// vertices, spikes that double back on the previous edge, rings with no area, edges that cross
+/// each other (a bow-tie), holes that are outside of the fence, cross its outer ring or another
+/// hole, or wind the wrong way, and polygons of a multipolygon that overlap each other.
+/// Each defect names the ring and the vertices it was found at, counted in the order of the
+/// coordinates of the fence file.
+/// The repair functions remove the closing, duplicate and spike vertices, split the rings at the
+/// points where their edges cross, and orient the outer rings counter clockwise and the holes
+/// clockwise, which gives a valid multipolygon.
+///
+use coordinates::*;
+use error::GeofenceError;
+use fence::Fence;
+use polygon::{locate_in_ring, locate_in_rings, open_ring, ring_area, PolygonFence};
+use std::fmt;
+use std::str::FromStr;
+
+/// Defect of a ring of a fence.
+/// The ring is its position in the rings of the fence: 0 for the outer ring of a polygon,
+/// followed by its holes, and for a multipolygon the rings of each polygon one after the other.
+/// Vertices are positions in the coordinates of the ring, an edge is named by its first vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Defect {
+    /// The last vertex repeats the first one. The fence circles back to the first vertex on its
+    /// own, so the closing vertex adds an edge of no length.
+    ClosedRing { ring: usize },
+    /// The vertex is the same as the vertex it follows.
+    DuplicateVertex {
+        ring: usize,
+        vertex: usize,
+        repeats: usize,
+    },
+    /// The ring doubles back on itself at the vertex, the edges before and after it overlap.
+    Spike { ring: usize, vertex: usize },
+    /// The ring has less than 3 different vertices.
+    TooFewVertices { ring: usize, count: usize },
+    /// All the vertices of the ring are on a line, so it encloses no area.
+    ZeroArea { ring: usize },
+    /// Two edges of the ring that do not follow each other cross or touch at the point.
+    SelfIntersection {
+        ring: usize,
+        edge1: usize,
+        edge2: usize,
+        point: Coordinates,
+    },
+    /// The hole has a vertex outside of the outer ring of its polygon.
+    HoleOutside { ring: usize, vertex: usize },
+    /// An edge of the hole crosses or touches an edge of the other ring of its polygon, which is
+    /// the outer ring or another hole, at the point.
+    RingIntersection {
+        ring: usize,
+        edge: usize,
+        other: usize,
+        other_edge: usize,
+        point: Coordinates,
+    },
+    /// The hole is inside the other hole of its polygon, or the other hole is inside it.
+    HoleOverlap { ring: usize, other: usize },
+    /// The hole winds the same way as the outer ring of its polygon, so with the non zero fill
+    /// rule it adds to the fence instead of cutting it out.
+    HoleOrientation { ring: usize },
+    /// The polygon of a multipolygon, named by its outer ring, overlaps the polygon of the other
+    /// outer ring. With the even odd fill rule the area they share is left out of the fence.
+    PolygonOverlap { ring: usize, other: usize },
+}
+
+impl Defect {
+    /// Returns the ring of the fence the defect was found in.
+    pub fn ring(&self) -> usize {
+        match self {
+            Defect::ClosedRing { ring }
+            | Defect::DuplicateVertex { ring, .. }
+            | Defect::Spike { ring, .. }
+            | Defect::TooFewVertices { ring, .. }
+            | Defect::ZeroArea { ring }
+            | Defect::SelfIntersection { ring, .. }
+            | Defect::HoleOutside { ring, .. }
+            | Defect::RingIntersection { ring, .. }
+            | Defect::HoleOverlap { ring, .. }
+            | Defect::PolygonOverlap { ring, .. }
+            | Defect::HoleOrientation { ring } => *ring,
+        }
+    }
+
+    /// Moves the defect to the ring with the given offset, for the rings of a multipolygon.
+    fn offset(mut self, offset: usize) -> Defect {
+        match &mut self {
+            Defect::ClosedRing { ring }
+            | Defect::DuplicateVertex { ring, .. }
+            | Defect::Spike { ring, .. }
+            | Defect::TooFewVertices { ring, .. }
+            | Defect::ZeroArea { ring }
+            | Defect::SelfIntersection { ring, .. }
+            | Defect::HoleOutside { ring, .. }
+            | Defect::HoleOrientation { ring } => *ring += offset,
+            Defect::RingIntersection { ring, other, .. }
+            | Defect::HoleOverlap { ring, other }
+            | Defect::PolygonOverlap { ring, other } => {
+                *ring += offset;
+                *other += offset;
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for Defect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ring {}: ", self.ring())?;
+        match self {
+            Defect::ClosedRing { .. } => write!(f, "the last vertex repeats the first vertex"),
+            Defect::DuplicateVertex {
+                vertex, repeats, ..
+            } => write!(f, "vertex {} repeats vertex {}", vertex, repeats),
+            Defect::Spike { vertex, .. } => write!(f, "the ring doubles back at vertex {}", vertex),
+            Defect::TooFewVertices { count, .. } => {
+                write!(f, "the ring has only {} different vertices", count)
+            }
+            Defect::ZeroArea { .. } => write!(f, "all the vertices are on a line"),
+            Defect::SelfIntersection {
+                edge1,
+                edge2,
+                point,
+                ..
+            } => write!(
+                f,
+                "the edges starting at vertex {} and vertex {} cross at latitude {}, longitude {}",
+                edge1, edge2, point.lat, point.lon
+            ),
+            Defect::HoleOutside { vertex, .. } => {
+                write!(f, "vertex {} of the hole is outside of the fence", vertex)
+            }
+            Defect::RingIntersection {
+                edge,
+                other,
+                other_edge,
+                point,
+                ..
+            } => write!(
+                f,
+                "the edge starting at vertex {} crosses the edge starting at vertex {} of ring {} \
+                 at latitude {}, longitude {}",
+                edge, other_edge, other, point.lat, point.lon
+            ),
+            Defect::HoleOverlap { other, .. } => write!(f, "the hole overlaps hole {}", other),
+            Defect::PolygonOverlap { other, .. } => {
+                write!(f, "the polygon overlaps the polygon of ring {}", other)
+            }
+            Defect::HoleOrientation { .. } => {
+                write!(f, "the hole winds the same way as the outer ring")
+            }
+        }
+    }
+}
+
+/// Returns the point where the segment from point1 to point2 meets the segment from point3 to
+/// point4, or None if they do not meet. Overlapping segments return one of the shared points.
+fn segment_intersection(
+    point1: &Coordinates,
+    point2: &Coordinates,
+    point3: &Coordinates,
+    point4: &Coordinates,
+) -> Option<Coordinates> {
+    let side3 = orientation(point1, point2, point3);
+    let side4 = orientation(point1, point2, point4);
+    let side1 = orientation(point3, point4, point1);
+    let side2 = orientation(point3, point4, point2);
+    if side1 == 0.0 && side2 == 0.0 {
+        // Both the segments are on the same line.
+        let on_segment = |point: &Coordinates, start: &Coordinates, end: &Coordinates| {
+            point.lat >= start.lat.min(end.lat)
+                && point.lat <= start.lat.max(end.lat)
+                && point.lon >= start.lon.min(end.lon)
+                && point.lon <= start.lon.max(end.lon)
+        };
+        return [point3, point4, point1, point2]
+            .iter()
+            .enumerate()
+            .find(|(pos, point)| {
+                if *pos < 2 {
+                    on_segment(point, point1, point2)
+                } else {
+                    on_segment(point, point3, point4)
+                }
+            })
+            .map(|(_, point)| (*point).clone());
+    }
+    if side1 * side2 > 0.0 || side3 * side4 > 0.0 {
+        return None;
+    }
+    // The point divides the first segment in the ratio of the distances of its ends from
+    // the line of the second segment.
+    let fraction = side1 / (side1 - side2);
+    Some(Coordinates::new(
+        point1.lat + (point2.lat - point1.lat) * fraction,
+        point1.lon + (point2.lon - point1.lon) * fraction,
+    ))
+}
+
+/// Returns true if the ring doubles back at point2, coming from point1 and going on to point3.
+fn is_spike(point1: &Coordinates, point2: &Coordinates, point3: &Coordinates) -> bool {
+    orientation(point1, point2, point3) == 0.0
+        && (point2.lat - point1.lat) * (point3.lat - point2.lat)
+            + (point2.lon - point1.lon) * (point3.lon - point2.lon)
+            < 0.0
+}
+
+/// Returns true if all the vertices of the ring are on a line. The signed area of the ring is
+/// not enough, as the loops of a bow-tie cancel each other out.
+fn is_flat(ring: &[Coordinates]) -> bool {
+    ring.iter()
+        .all(|point| orientation(&ring[0], &ring[1], point) == 0.0)
+}
+
+/// Adds the defects of a single ring to the list, and returns the positions of the vertices
+/// that are left after dropping the closing and duplicate vertices.
+fn ring_defects(ring: &[Coordinates], index: usize, defects: &mut Vec<Defect>) -> Vec<usize> {
+    let mut len = ring.len();
+    if len > 1 && ring[0] == ring[len - 1] {
+        defects.push(Defect::ClosedRing { ring: index });
+        len -= 1;
+    }
+    let mut kept: Vec<usize> = Vec::with_capacity(len);
+    for vertex in 0..len {
+        match kept.last() {
+            Some(&previous) if ring[previous] == ring[vertex] => {
+                defects.push(Defect::DuplicateVertex {
+                    ring: index,
+                    vertex,
+                    repeats: previous,
+                })
+            }
+            _ => kept.push(vertex),
+        }
+    }
+    // The ring can still come back to its first vertex, when it was closed more than once.
+    while kept.len() > 1 && ring[kept[0]] == ring[kept[kept.len() - 1]] {
+        let vertex = kept.pop().unwrap_or_default();
+        defects.push(Defect::DuplicateVertex {
+            ring: index,
+            vertex,
+            repeats: kept[0],
+        });
+    }
+    let count = kept.len();
+    if count < 3 {
+        defects.push(Defect::TooFewVertices { ring: index, count });
+        return kept;
+    }
+    let points: Vec<Coordinates> = kept.iter().map(|&vertex| ring[vertex].clone()).collect();
+    if is_flat(&points) {
+        defects.push(Defect::ZeroArea { ring: index });
+        return kept;
+    }
+    for pos in 0..count {
+        let previous = &points[(pos + count - 1) % count];
+        if is_spike(previous, &points[pos], &points[(pos + 1) % count]) {
+            defects.push(Defect::Spike {
+                ring: index,
+                vertex: kept[pos],
+            });
+        }
+    }
+    // Edges that follow each other always share a vertex, so only the others are checked.
+    for edge1 in 0..count {
+        for edge2 in edge1 + 2..count {
+            if edge1 == 0 && edge2 == count - 1 {
+                continue;
+            }
+            let point = segment_intersection(
+                &points[edge1],
+                &points[edge1 + 1],
+                &points[edge2],
+                &points[(edge2 + 1) % count],
+            );
+            if let Some(point) = point {
+                defects.push(Defect::SelfIntersection {
+                    ring: index,
+                    edge1: kept[edge1],
+                    edge2: kept[edge2],
+                    point,
+                });
+            }
+        }
+    }
+    kept
+}
+
+/// Returns the defects of a single ring, such as the vertices of a triangular fence.
+pub fn validate_ring(ring: &[Coordinates]) -> Vec<Defect> {
+    let mut defects = Vec::new();
+    ring_defects(ring, 0, &mut defects);
+    defects
+}
+
+/// Returns the points where the edges of the ring meet the edges of the other ring, along with
+/// the positions of the two edges.
+fn ring_intersections(
+    ring: &[Coordinates],
+    other: &[Coordinates],
+) -> Vec<(usize, usize, Coordinates)> {
+    let mut points = Vec::new();
+    for edge in 0..ring.len() {
+        for other_edge in 0..other.len() {
+            if let Some(point) = segment_intersection(
+                &ring[edge],
+                &ring[(edge + 1) % ring.len()],
+                &other[other_edge],
+                &other[(other_edge + 1) % other.len()],
+            ) {
+                points.push((edge, other_edge, point));
+            }
+        }
+    }
+    points
+}
+
+/// Returns the defects of the outer ring and the holes of a polygon.
+/// The orientation of the holes only matters, and is only checked, with the non zero fill rule.
+pub fn validate_polygon(
+    exterior: &[Coordinates],
+    holes: &[Vec<Coordinates>],
+    fill_rule: FillRule,
+) -> Vec<Defect> {
+    let mut defects = Vec::new();
+    let outer_kept = ring_defects(exterior, 0, &mut defects);
+    let outer: Vec<Coordinates> = outer_kept
+        .iter()
+        .map(|&vertex| exterior[vertex].clone())
+        .collect();
+    let outer_valid = outer.len() >= 3 && !is_flat(&outer);
+    // The holes that have an area, along with their positions in the rings and their vertices.
+    let mut checked: Vec<(usize, Vec<usize>, Vec<Coordinates>)> = Vec::new();
+    for (pos, hole) in holes.iter().enumerate() {
+        let index = pos + 1;
+        let kept = ring_defects(hole, index, &mut defects);
+        if !outer_valid {
+            continue;
+        }
+        if let Some(&vertex) = kept
+            .iter()
+            .find(|&&vertex| locate_in_ring(&hole[vertex], &outer) == Location::Outside)
+        {
+            defects.push(Defect::HoleOutside {
+                ring: index,
+                vertex,
+            });
+        }
+        let points: Vec<Coordinates> = kept.iter().map(|&vertex| hole[vertex].clone()).collect();
+        if points.len() < 3 || is_flat(&points) {
+            continue;
+        }
+        // Checking the vertices is not enough, an edge can go out of the outer ring and back
+        // in between two vertices that are inside it.
+        for (edge, other_edge, point) in ring_intersections(&points, &outer) {
+            defects.push(Defect::RingIntersection {
+                ring: index,
+                edge: kept[edge],
+                other: 0,
+                other_edge: outer_kept[other_edge],
+                point,
+            });
+        }
+        for (other, other_kept, other_points) in &checked {
+            let intersections = ring_intersections(&points, other_points);
+            if intersections.is_empty() {
+                if ring_inside(&points, other_points) || ring_inside(other_points, &points) {
+                    defects.push(Defect::HoleOverlap {
+                        ring: index,
+                        other: *other,
+                    });
+                }
+                continue;
+            }
+            for (edge, other_edge, point) in intersections {
+                defects.push(Defect::RingIntersection {
+                    ring: index,
+                    edge: kept[edge],
+                    other: *other,
+                    other_edge: other_kept[other_edge],
+                    point,
+                });
+            }
+        }
+        if fill_rule == FillRule::NonZero && ring_area(&points) * ring_area(&outer) > 0.0 {
+            defects.push(Defect::HoleOrientation { ring: index });
+        }
+        checked.push((index, kept, points));
+    }
+    defects
+}
+
+/// Returns true if the edges of the rings cross each other away from their ends.
+fn rings_cross(ring: &[Coordinates], other: &[Coordinates]) -> bool {
+    (0..ring.len()).any(|edge| {
+        let (point1, point2) = (&ring[edge], &ring[(edge + 1) % ring.len()]);
+        (0..other.len()).any(|other_edge| {
+            let (point3, point4) = (&other[other_edge], &other[(other_edge + 1) % other.len()]);
+            orientation(point1, point2, point3) * orientation(point1, point2, point4) < 0.0
+                && orientation(point3, point4, point1) * orientation(point3, point4, point2) < 0.0
+        })
+    })
+}
+
+/// Returns true if a vertex or the middle of an edge of the rings of the polygon is inside the
+/// other polygon.
+fn polygon_reaches_in(
+    polygon: &[Vec<Coordinates>],
+    other: &[Vec<Coordinates>],
+    fill_rule: FillRule,
+) -> bool {
+    let inside = |point: &Coordinates| {
+        locate_in_rings(point, other.iter().map(|ring| ring.as_slice()), fill_rule)
+            == Location::Inside
+    };
+    polygon.iter().any(|ring| {
+        (0..ring.len()).any(|vertex| {
+            let next = &ring[(vertex + 1) % ring.len()];
+            let middle = Coordinates::new(
+                (ring[vertex].lat + next.lat) / 2.0,
+                (ring[vertex].lon + next.lon) / 2.0,
+            );
+            inside(&ring[vertex]) || inside(&middle)
+        })
+    })
+}
+
+/// Returns true if the insides of the polygons, given as their outer ring followed by their
+/// holes, overlap. Polygons that only touch, at a vertex or along an edge, do not overlap.
+fn polygons_overlap(
+    polygon: &[Vec<Coordinates>],
+    other: &[Vec<Coordinates>],
+    fill_rule: FillRule,
+) -> bool {
+    polygon
+        .iter()
+        .any(|ring| other.iter().any(|other_ring| rings_cross(ring, other_ring)))
+        || polygon_reaches_in(polygon, other, fill_rule)
+        || polygon_reaches_in(other, polygon, fill_rule)
+}
+
+/// Returns the defects of all the polygons of a multipolygon, with the rings counted across
+/// the polygons in the order of MultiPolygonFence::rings.
+/// Polygons whose outer ring has no area are not checked against the others for overlaps.
+pub fn validate_multipolygon(polygons: &[PolygonFence], fill_rule: FillRule) -> Vec<Defect> {
+    let mut defects = Vec::new();
+    let mut offset = 0;
+    // The polygons that have an area, along with the position of their outer ring and their
+    // rings without the closing, duplicate and spike vertices.
+    let mut checked: Vec<(usize, Vec<Vec<Coordinates>>)> = Vec::new();
+    for polygon in polygons {
+        defects.extend(
+            validate_polygon(polygon.points(), polygon.holes(), fill_rule)
+                .into_iter()
+                .map(|defect| defect.offset(offset)),
+        );
+        let rings: Vec<Vec<Coordinates>> = polygon
+            .rings()
+            .map(|ring| clean_ring(ring.to_vec()))
+            .filter(|ring| ring.len() >= 3 && !is_flat(ring))
+            .collect();
+        let outer = clean_ring(polygon.points().to_vec());
+        if outer.len() >= 3 && !is_flat(&outer) {
+            for (other, other_rings) in &checked {
+                if polygons_overlap(&rings, other_rings, fill_rule) {
+                    defects.push(Defect::PolygonOverlap {
+                        ring: offset,
+                        other: *other,
+                    });
+                }
+            }
+            checked.push((offset, rings));
+        }
+        offset += 1 + polygon.holes().len();
+    }
+    defects
+}
+
+/// Removes the closing vertex, the duplicate vertices and the spikes of the ring.
+/// Removing a spike can leave a duplicate vertex or a new spike behind, so it is repeated
+/// until nothing changes.
+fn clean_ring(ring: Vec<Coordinates>) -> Vec<Coordinates> {
+    let mut ring = open_ring(ring);
+    loop {
+        let len = ring.len();
+        ring.dedup();
+        while ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+        let count = ring.len();
+        if count >= 3 {
+            let spike = (0..count).find(|&pos| {
+                is_spike(
+                    &ring[(pos + count - 1) % count],
+                    &ring[pos],
+                    &ring[(pos + 1) % count],
+                )
+            });
+            if let Some(pos) = spike {
+                ring.remove(pos);
+            }
+        }
+        if ring.len() == len {
+            return ring;
+        }
+    }
+}
+
+/// Splits the ring at the points where its edges cross or touch, in to rings that neither cross
+/// nor touch themselves. Each ring keeps the direction in which the original ring went round it.
+/// Rings with no area left are dropped.
+fn split_ring(ring: Vec<Coordinates>) -> Vec<Vec<Coordinates>> {
+    let mut pending = vec![ring];
+    let mut rings = Vec::new();
+    while let Some(ring) = pending.pop() {
+        let ring = clean_ring(ring);
+        let count = ring.len();
+        if count < 3 || is_flat(&ring) {
+            continue;
+        }
+        let crossing = (0..count)
+            .flat_map(|edge1| (edge1 + 2..count).map(move |edge2| (edge1, edge2)))
+            .filter(|&(edge1, edge2)| !(edge1 == 0 && edge2 == count - 1))
+            .find_map(|(edge1, edge2)| {
+                segment_intersection(
+                    &ring[edge1],
+                    &ring[edge1 + 1],
+                    &ring[edge2],
+                    &ring[(edge2 + 1) % count],
+                )
+                .map(|point| (edge1, edge2, point))
+            });
+        match crossing {
+            // The loop between the two edges becomes a ring of its own, and the rest of the
+            // ring goes through the crossing point instead of round the loop.
+            Some((edge1, edge2, point)) => {
+                let mut lobe = vec![point.clone()];
+                lobe.extend_from_slice(&ring[edge1 + 1..=edge2]);
+                let mut rest = ring[..=edge1].to_vec();
+                rest.push(point);
+                rest.extend_from_slice(&ring[edge2 + 1..]);
+                pending.push(lobe);
+                pending.push(rest);
+            }
+            None => rings.push(ring),
+        }
+    }
+    rings
+}
+
+/// Returns true if the ring is inside the other ring. The rings do not cross, so the first
+/// vertex that is not on the border of the other ring decides.
+fn ring_inside(ring: &[Coordinates], other: &[Coordinates]) -> bool {
+    ring.iter()
+        .map(|point| locate_in_ring(point, other))
+        .find(|location| *location != Location::OnBoundary)
+        == Some(Location::Inside)
+}
+
+/// Returns true if the edges of the rings do not meet and neither ring is inside the other.
+fn rings_apart(ring: &[Coordinates], other: &[Coordinates]) -> bool {
+    ring_intersections(ring, other).is_empty()
+        && !ring_inside(ring, other)
+        && !ring_inside(other, ring)
+}
+
+/// Returns the ring going round in the direction given by the sign of the area,
+/// positive (counter clockwise) for outer rings and negative (clockwise) for holes.
+fn orient(mut ring: Vec<Coordinates>, sign: f64) -> Vec<Coordinates> {
+    if ring_area(&ring) * sign < 0.0 {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Repairs the outer ring and the holes of a polygon, and returns the polygons of the valid
+/// multipolygon that covers the same area.
+/// A ring whose edges cross is split in to a polygon for each of its loops. A loop that is
+/// inside another loop becomes a hole of it when the fill rule leaves it out of the fence (every
+/// loop with EvenOdd, a loop winding the other way with NonZero), and is dropped otherwise.
+/// Holes are cut out of the polygon they are in, and dropped when they are not in any of them,
+/// or when they cross its outer ring or overlap one of its other holes.
+/// Returns GeofenceError::InvalidGeometry if no area is left after removing the defects.
+pub fn repair_polygon(
+    exterior: &[Coordinates],
+    holes: &[Vec<Coordinates>],
+    fill_rule: FillRule,
+) -> Result<Vec<PolygonFence>, GeofenceError> {
+    let mut loops = split_ring(exterior.to_vec());
+    // Larger loops first, so that a loop is always checked against the loops that can hold it.
+    loops.sort_by(|ring1, ring2| ring_area(ring2).abs().total_cmp(&ring_area(ring1).abs()));
+    // Each polygon is kept as its outer ring, the direction of the loop it came from, and its holes.
+    let mut polygons: Vec<(Vec<Coordinates>, f64, Vec<Vec<Coordinates>>)> = Vec::new();
+    for ring in loops {
+        let direction = ring_area(&ring).signum();
+        match polygons
+            .iter_mut()
+            .rev()
+            .find(|(outer, _, _)| ring_inside(&ring, outer))
+        {
+            Some((_, outer_direction, inner)) => {
+                if fill_rule == FillRule::EvenOdd || direction != *outer_direction {
+                    inner.push(orient(ring, -1.0));
+                }
+            }
+            None => polygons.push((orient(ring, 1.0), direction, Vec::new())),
+        }
+    }
+    for hole in holes {
+        // The loops of a hole touch each other where it is split, so they are only checked
+        // against the holes that were cut out before.
+        let mut cut = Vec::new();
+        for ring in split_ring(hole.clone()) {
+            if let Some(pos) = polygons
+                .iter()
+                .rposition(|(outer, _, _)| ring_inside(&ring, outer))
+            {
+                let (outer, _, inner) = &polygons[pos];
+                if ring_intersections(&ring, outer).is_empty()
+                    && inner.iter().all(|other| rings_apart(&ring, other))
+                {
+                    cut.push((pos, orient(ring, -1.0)));
+                }
+            }
+        }
+        for (pos, ring) in cut {
+            polygons[pos].2.push(ring);
+        }
+    }
+    if polygons.is_empty() {
+        return Err(GeofenceError::InvalidGeometry(
+            "the polygon has no area left after removing its defects".to_string(),
+        ));
+    }
+    polygons
+        .into_iter()
+        .map(|(outer, _, inner)| {
+            PolygonFence::with_holes(outer, inner).map(|polygon| polygon.with_fill_rule(fill_rule))
+        })
+        .collect()
+}
+
+/// What to do with the defects of the fences that are read from files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Validation {
+    /// The fences are used as they are.
+    #[default]
+    Off,
+    /// A fence with any defect is rejected with GeofenceError::InvalidGeometry.
+    Reject,
+    /// The fences are repaired, a polygon that has to be split becomes a multipolygon.
+    Repair,
+}
+
+impl Validation {
+    /// Validates or repairs the fence.
+    pub fn apply(self, fence: Fence) -> Result<Fence, GeofenceError> {
+        match self {
+            Validation::Off => Ok(fence),
+            Validation::Reject => {
+                let defects = fence.validate();
+                if defects.is_empty() {
+                    return Ok(fence);
+                }
+                let reasons: Vec<String> =
+                    defects.iter().map(|defect| defect.to_string()).collect();
+                Err(GeofenceError::InvalidGeometry(format!(
+                    "the {} fence has defects: {}",
+                    fence.shape(),
+                    reasons.join("; ")
+                )))
+            }
+            Validation::Repair => fence.repair(),
+        }
+    }
+}
+
+impl FromStr for Validation {
+    type Err = GeofenceError;
+
+    fn from_str(validation: &str) -> Result<Validation, GeofenceError> {
+        match validation.to_lowercase().as_str() {
+            "off" => Ok(Validation::Off),
+            "reject" => Ok(Validation::Reject),
+            "repair" => Ok(Validation::Repair),
+            _ => Err(GeofenceError::Parse(format!(
+                "unknown validation '{}', expected off, reject or repair",
+                validation
+            ))),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads and writes fence geometries as WKB (well known binary), and as the EWKB variant used by
+/// PostGIS, which carries the SRID of the geometry. Databases usually hand the binary out as a
+/// hex string, which can be converted with from_hex and to_hex.
+/// As in WKT, positions are longitude followed by latitude, and Z and M values are skipped.
+///
+use coordinates::*;
+use error::GeofenceError;
+use multipolygon::MultiPolygonFence;
+use polygon::{open_ring, PolygonFence};
+use wkt::Geometry;
+
+const WKB_POINT: u32 = 1;
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTIPOLYGON: u32 = 6;
+
+/// Flags of the EWKB geometry type.
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+fn parse_error(reason: String) -> GeofenceError {
+    GeofenceError::Parse(format!("WKB: {}", reason))
+}
+
+/// Reads the WKB bytes one value at a time.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], GeofenceError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| parse_error(format!("unexpected end of data at byte {}", self.pos)))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, GeofenceError> {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(if self.little_endian {
+            u32::from_le_bytes(buf)
+        } else {
+            u32::from_be_bytes(buf)
+        })
+    }
+
+    fn read_f64(&mut self) -> Result<f64, GeofenceError> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(if self.little_endian {
+            f64::from_le_bytes(buf)
+        } else {
+            f64::from_be_bytes(buf)
+        })
+    }
+
+    /// Reads a position of the given number of dimensions, x (lon) and y (lat) first.
+    fn position(&mut self, dimensions: usize) -> Result<Coordinates, GeofenceError> {
+        let lon = self.read_f64()?;
+        let lat = self.read_f64()?;
+        for _ in 2..dimensions {
+            self.read_f64()?;
+        }
+        Ok(Coordinates::new(lat, lon))
+    }
+
+    fn positions(&mut self, dimensions: usize) -> Result<Vec<Coordinates>, GeofenceError> {
+        let count = self.read_u32()?;
+        (0..count).map(|_| self.position(dimensions)).collect()
+    }
+
+    fn polygon(&mut self, dimensions: usize) -> Result<PolygonFence, GeofenceError> {
+        let count = self.read_u32()?;
+        let mut rings = (0..count)
+            .map(|_| self.positions(dimensions).map(open_ring))
+            .collect::<Result<Vec<Vec<Coordinates>>, GeofenceError>>()?;
+        if rings.is_empty() {
+            return Err(parse_error("a polygon needs at least 1 ring".to_string()));
+        }
+        let exterior = rings.remove(0);
+        PolygonFence::with_holes(exterior, rings)
+    }
+
+    /// Reads the byte order and the type of a geometry, and returns the type without its
+    /// Z / M / SRID flags, the number of dimensions of its positions and the SRID, if any.
+    fn header(&mut self) -> Result<(u32, usize, Option<u32>), GeofenceError> {
+        self.little_endian = match self.take(1)?[0] {
+            0 => false,
+            1 => true,
+            order => return Err(parse_error(format!("unknown byte order {}", order))),
+        };
+        let kind = self.read_u32()?;
+        let srid = if kind & EWKB_SRID != 0 {
+            Some(self.read_u32()?)
+        } else {
+            None
+        };
+        // ISO WKB adds 1000 to the type for Z, 2000 for M and 3000 for ZM.
+        let iso = (kind & 0x0fff_ffff) / 1000;
+        let has_z = kind & EWKB_Z != 0 || iso == 1 || iso == 3;
+        let has_m = kind & EWKB_M != 0 || iso == 2 || iso == 3;
+        let dimensions = 2 + has_z as usize + has_m as usize;
+        Ok(((kind & 0x0fff_ffff) % 1000, dimensions, srid))
+    }
+
+    /// Reads a geometry, along with the SRID when the geometry is EWKB.
+    fn geometry(&mut self) -> Result<(Geometry, Option<u32>), GeofenceError> {
+        let (kind, dimensions, srid) = self.header()?;
+        let geometry = match kind {
+            WKB_POINT => Geometry::Point(self.position(dimensions)?),
+            WKB_LINESTRING => Geometry::LineString(self.positions(dimensions)?),
+            WKB_POLYGON => Geometry::Polygon(self.polygon(dimensions)?),
+            WKB_MULTIPOLYGON => {
+                let count = self.read_u32()?;
+                // The members are read as polygons, not as any geometry, so that nested
+                // multipolygons cannot recurse without end.
+                let polygons = (0..count)
+                    .map(|_| match self.header()? {
+                        (WKB_POLYGON, dimensions, _) => self.polygon(dimensions),
+                        (other, _, _) => Err(parse_error(format!(
+                            "a multipolygon can only contain polygons, but it contains geometry type {}",
+                            other
+                        ))),
+                    })
+                    .collect::<Result<Vec<PolygonFence>, GeofenceError>>()?;
+                Geometry::MultiPolygon(MultiPolygonFence::new(polygons)?)
+            }
+            other => {
+                return Err(parse_error(format!(
+                    "geometry type {} is not supported",
+                    other
+                )))
+            }
+        };
+        Ok((geometry, srid))
+    }
+}
+
+/// Reads the WKB (or EWKB) bytes as a geometry, along with the SRID if there is one.
+/// Returns GeofenceError::Parse if the bytes are not valid WKB for a POINT, LINESTRING, POLYGON
+/// or MULTIPOLYGON, and GeofenceError::InvalidGeometry if the rings cannot form a fence.
+pub fn read_ewkb(bytes: &[u8]) -> Result<(Geometry, Option<u32>), GeofenceError> {
+    let mut reader = Reader {
+        bytes,
+        pos: 0,
+        little_endian: true,
+    };
+    let geometry = reader.geometry()?;
+    if reader.pos != bytes.len() {
+        return Err(parse_error(format!(
+            "unexpected data after the geometry at byte {}",
+            reader.pos
+        )));
+    }
+    Ok(geometry)
+}
+
+/// Reads the WKB (or EWKB) bytes as a geometry, ignoring the SRID.
+pub fn read_wkb(bytes: &[u8]) -> Result<Geometry, GeofenceError> {
+    read_ewkb(bytes).map(|(geometry, _)| geometry)
+}
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_position(out: &mut Vec<u8>, point: &Coordinates) {
+    out.extend_from_slice(&point.lon.to_le_bytes());
+    out.extend_from_slice(&point.lat.to_le_bytes());
+}
+
+fn write_positions<'a, I>(out: &mut Vec<u8>, points: I)
+where
+    I: ExactSizeIterator<Item = &'a Coordinates>,
+{
+    write_u32(out, points.len() as u32);
+    for point in points {
+        write_position(out, point);
+    }
+}
+
+fn write_polygon(out: &mut Vec<u8>, fence: &PolygonFence) {
+    write_u32(out, fence.rings().count() as u32);
+    for ring in fence.rings() {
+        // WKB rings repeat the first position at the end.
+        write_u32(out, ring.len() as u32 + 1);
+        for point in ring.iter().chain(ring.first()) {
+            write_position(out, point);
+        }
+    }
+}
+
+/// Writes the geometry in little endian byte order, with the SRID when there is one.
+fn write_geometry(out: &mut Vec<u8>, geometry: &Geometry, srid: Option<u32>) {
+    let kind = match geometry {
+        Geometry::Point(_) => WKB_POINT,
+        Geometry::LineString(_) => WKB_LINESTRING,
+        Geometry::Polygon(_) => WKB_POLYGON,
+        Geometry::MultiPolygon(_) => WKB_MULTIPOLYGON,
+    };
+    out.push(1);
+    match srid {
+        Some(srid) => {
+            write_u32(out, kind | EWKB_SRID);
+            write_u32(out, srid);
+        }
+        None => write_u32(out, kind),
+    }
+    match geometry {
+        Geometry::Point(point) => write_position(out, point),
+        Geometry::LineString(points) => write_positions(out, points.iter()),
+        Geometry::Polygon(fence) => write_polygon(out, fence),
+        Geometry::MultiPolygon(fence) => {
+            write_u32(out, fence.polygons().len() as u32);
+            for polygon in fence.polygons() {
+                // Each polygon of the multipolygon is a WKB geometry with its own header.
+                out.push(1);
+                write_u32(out, WKB_POLYGON);
+                write_polygon(out, polygon);
+            }
+        }
+    }
+}
+
+/// Returns the geometry as WKB bytes.
+pub fn write_wkb(geometry: &Geometry) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_geometry(&mut out, geometry, None);
+    out
+}
+
+/// Returns the geometry as EWKB bytes, with the given SRID (4326 for latitude / longitude).
+pub fn write_ewkb(geometry: &Geometry, srid: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_geometry(&mut out, geometry, Some(srid));
+    out
+}
+
+/// Converts the hex string handed out by the database to WKB bytes.
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, GeofenceError> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix("\\x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(parse_error(format!("'{}' is not a hex string", hex)));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|pos| {
+            u8::from_str_radix(&hex[pos..pos + 2], 16)
+                .map_err(|_| parse_error(format!("'{}' is not a hex string", hex)))
+        })
+        .collect()
+}
+
+/// Converts the WKB bytes to an upper case hex string, as written by PostGIS.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
@@ -0,0 +1,244 @@
+// Copyright © 2020 Delhi Durai and Rajeswari
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+///
+/// Reads and writes fence geometries as WKT (well known text), the text format used by
+/// PostGIS and most spatial databases, for example POLYGON ((3 -2, 4 4, -2 3, 3 -2)).
+/// WKT positions are "x y", which is longitude followed by latitude, so the axis order is swapped
+/// when reading and when writing. The SRID=4326; prefix of EWKT is accepted and ignored, and so
+/// are the Z and M values of the positions.
+///
+use coordinates::*;
+use error::GeofenceError;
+use fence::Fence;
+use multipolygon::MultiPolygonFence;
+use polygon::{open_ring, PolygonFence};
+
+/// Geometry read from or written to WKT and WKB.
+/// Polygons and multipolygons are fences, while points and linestrings are positions and tracks.
+#[derive(Debug, Clone)]
+pub enum Geometry {
+    Point(Coordinates),
+    LineString(Vec<Coordinates>),
+    Polygon(PolygonFence),
+    MultiPolygon(MultiPolygonFence),
+}
+
+impl Geometry {
+    /// Returns the fence of a Polygon or MultiPolygon geometry.
+    pub fn into_fence(self) -> Option<Fence> {
+        match self {
+            Geometry::Polygon(fence) => Some(fence.into()),
+            Geometry::MultiPolygon(fence) => Some(fence.into()),
+            _ => None,
+        }
+    }
+
+    /// Returns the geometry of the fence, triangular fences are polygons, and so are rectangular
+    /// fences unless they cross the antimeridian, where they are split in to a multipolygon.
+    /// Returns GeofenceError::InvalidGeometry for circular and corridor fences, which have no
+    /// WKT geometry.
+    pub fn from_fence(fence: &Fence) -> Result<Geometry, GeofenceError> {
+        match fence {
+            Fence::Polygon(fence) => Ok(Geometry::Polygon(fence.clone())),
+            Fence::MultiPolygon(fence) => Ok(Geometry::MultiPolygon(fence.clone())),
+            Fence::Triangle(fence) => Ok(Geometry::Polygon(PolygonFence::new(
+                fence.vertices().to_vec(),
+            )?)),
+            Fence::Circle(_) => Err(GeofenceError::InvalidGeometry(
+                "a circle fence cannot be written as a WKT or WKB geometry".to_string(),
+            )),
+            Fence::Corridor(_) => Err(GeofenceError::InvalidGeometry(
+                "a corridor fence cannot be written as a WKT or WKB geometry".to_string(),
+            )),
+            Fence::Rectangle(fence) => {
+                let mut polygons = fence.to_polygons()?;
+                if polygons.len() == 1 {
+                    return Ok(Geometry::Polygon(polygons.remove(0)));
+                }
+                Ok(Geometry::MultiPolygon(MultiPolygonFence::new(polygons)?))
+            }
+        }
+    }
+}
+
+impl From<PolygonFence> for Geometry {
+    fn from(fence: PolygonFence) -> Geometry {
+        Geometry::Polygon(fence)
+    }
+}
+
+impl From<MultiPolygonFence> for Geometry {
+    fn from(fence: MultiPolygonFence) -> Geometry {
+        Geometry::MultiPolygon(fence)
+    }
+}
+
+/// Reads the WKT text one token at a time.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, expected: &str) -> GeofenceError {
+        GeofenceError::Parse(format!(
+            "WKT: expected {} at position {} of '{}'",
+            expected, self.pos, self.text
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consumes the given character if it is the next one.
+    fn consume(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.text[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), GeofenceError> {
+        if self.consume(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", c)))
+        }
+    }
+
+    /// Reads the next word, such as the geometry type, in upper case.
+    fn word(&mut self) -> String {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_ascii_uppercase()
+    }
+
+    /// Reads the next number, or returns None without consuming anything if there is none.
+    fn number(&mut self) -> Option<f64> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(rest.len());
+        let number = rest[..len].parse().ok()?;
+        self.pos += len;
+        Some(number)
+    }
+
+    /// Reads a position "lon lat", any Z and M values after them are skipped.
+    fn position(&mut self) -> Result<Coordinates, GeofenceError> {
+        let lon = self.number().ok_or_else(|| self.error("a longitude"))?;
+        let lat = self.number().ok_or_else(|| self.error("a latitude"))?;
+        while self.number().is_some() {}
+        Ok(Coordinates::new(lat, lon))
+    }
+
+    /// Reads a list of items in parentheses, separated by commas.
+    fn list<T, F>(&mut self, mut item: F) -> Result<Vec<T>, GeofenceError>
+    where
+        F: FnMut(&mut Parser<'a>) -> Result<T, GeofenceError>,
+    {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.consume(',') {
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    /// Reads the rings of a polygon, the first ring is the outer ring and the rest are holes.
+    fn polygon(&mut self) -> Result<PolygonFence, GeofenceError> {
+        let mut rings = self.list(|parser| parser.list(Parser::position).map(open_ring))?;
+        let exterior = rings.remove(0);
+        PolygonFence::with_holes(exterior, rings)
+    }
+}
+
+/// Reads the WKT (or EWKT) text as a geometry.
+/// Returns GeofenceError::Parse if the text is not valid WKT for a POINT, LINESTRING, POLYGON
+/// or MULTIPOLYGON, and GeofenceError::InvalidGeometry if the rings cannot form a fence.
+pub fn read_wkt(text: &str) -> Result<Geometry, GeofenceError> {
+    let mut parser = Parser { text, pos: 0 };
+    if parser.word() == "SRID" {
+        parser.pos = text.find(';').ok_or_else(|| parser.error("';'"))? + 1;
+    } else {
+        parser.pos = 0;
+    }
+    let kind = parser.word();
+    match parser.word().as_str() {
+        "" | "Z" | "M" | "ZM" => {}
+        "EMPTY" => return Err(parser.error("coordinates, an empty geometry cannot be used")),
+        _ => return Err(parser.error("'(' after the geometry type")),
+    }
+    let geometry = match kind.as_str() {
+        "POINT" => {
+            parser.expect('(')?;
+            let point = parser.position()?;
+            parser.expect(')')?;
+            Geometry::Point(point)
+        }
+        "LINESTRING" => Geometry::LineString(parser.list(Parser::position)?),
+        "POLYGON" => Geometry::Polygon(parser.polygon()?),
+        "MULTIPOLYGON" => {
+            Geometry::MultiPolygon(MultiPolygonFence::new(parser.list(Parser::polygon)?)?)
+        }
+        _ => {
+            return Err(GeofenceError::Parse(format!(
+                "WKT: geometry type '{}' is not supported",
+                kind
+            )))
+        }
+    };
+    parser.skip_whitespace();
+    if parser.pos != text.len() {
+        return Err(parser.error("the end of the text"));
+    }
+    Ok(geometry)
+}
+
+/// Returns the positions as a WKT list, "(lon lat, lon lat, ...)".
+fn positions<'a, I>(points: I) -> String
+where
+    I: Iterator<Item = &'a Coordinates>,
+{
+    let points: Vec<String> = points
+        .map(|point| format!("{} {}", point.lon, point.lat))
+        .collect();
+    format!("({})", points.join(", "))
+}
+
+/// Returns the ring as a WKT list, which repeats the first position at the end.
+fn ring(ring: &[Coordinates]) -> String {
+    positions(ring.iter().chain(ring.first()))
+}
+
+fn polygon(fence: &PolygonFence) -> String {
+    let rings: Vec<String> = fence.rings().map(ring).collect();
+    format!("({})", rings.join(", "))
+}
+
+/// Returns the geometry as WKT text.
+pub fn write_wkt(geometry: &Geometry) -> String {
+    match geometry {
+        Geometry::Point(point) => format!("POINT ({} {})", point.lon, point.lat),
+        Geometry::LineString(points) => format!("LINESTRING {}", positions(points.iter())),
+        Geometry::Polygon(fence) => format!("POLYGON {}", polygon(fence)),
+        Geometry::MultiPolygon(fence) => {
+            let polygons: Vec<String> = fence.polygons().iter().map(polygon).collect();
+            format!("MULTIPOLYGON ({})", polygons.join(", "))
+        }
+    }
+}
@@ -1,6 +1,7 @@
 ///
 /// Test case for testing the geo fence functionality
 ///
+extern crate geofencing;
 
 // Test case to validate on point for Polygon
 #[test]
@@ -10,17 +11,18 @@ fn test_polygon_contains() {
         geofencing::coordinates::Shape::Polygon,
         filename,
         3.0,
-        3.0
-    ));
-    assert_eq!(
-        geofencing::geofencer::contains(
-            geofencing::coordinates::Shape::Polygon,
-            filename,
-            5.0,
-            5.0
-        ),
-        false
-    );
+        3.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Polygon,
+        filename,
+        5.0,
+        5.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
 }
 // Test case to validate on point for Polygon with actual Latitude and Longitude
 #[test]
@@ -30,142 +32,3409 @@ fn test_polygon_contains_withactual_latandlong() {
         geofencing::coordinates::Shape::Polygon,
         filename,
         55.746768,
-        37.625605
-    ));
+        37.625605,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
 }
 
 // Test case for negative scenario
 #[test]
-#[should_panic]
 fn test_polygon_withincorrect_filename() {
+    use geofencing::error::GeofenceError;
+
     let filename = "geofence_wrongfile.json";
-    assert!(geofencing::geofencer::contains(
+    for shape in [
         geofencing::coordinates::Shape::Polygon,
+        geofencing::coordinates::Shape::Circle,
+        geofencing::coordinates::Shape::Triangle,
+    ] {
+        match geofencing::geofencer::contains(
+            shape,
+            filename,
+            55.746768,
+            37.625605,
+            &geofencing::reporter::NoopReporter,
+        ) {
+            Err(error @ GeofenceError::Io(_)) => {
+                assert!(error.to_string().starts_with("I/O error: "))
+            }
+            other => panic!("expected an io error, got {:?}", other),
+        }
+    }
+}
+// Test case to validate on point for triangle
+#[test]
+fn test_triangle_contains() {
+    let filename = "triangle_geofence.json";
+    assert!(geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Triangle,
         filename,
-        55.746768,
-        37.625605
-    ));
+        3.0,
+        3.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Triangle,
+        filename,
+        5.0,
+        7.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+}
 
+// Test case to validate on point for Circle
+#[test]
+#[should_panic]
+fn test_circle_contains() {
+    let filename = "circle_geofence.json";
     assert!(geofencing::geofencer::contains(
         geofencing::coordinates::Shape::Circle,
         filename,
-        55.746768,
-        37.625605
-    ));
+        3.0,
+        3.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Circle,
+        filename,
+        7.0,
+        7.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Circle,
+        filename,
+        2.0,
+        1.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+}
+// Negative Test case to validate on point for Circle
+#[test]
+fn test_circle_with_incorrect_input() {
+    use geofencing::error::GeofenceError;
 
-    assert!(geofencing::geofencer::contains(
+    let filename = "triangle_geofence.json";
+    match geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Circle,
+        filename,
+        3.0,
+        3.0,
+        &geofencing::reporter::NoopReporter,
+    ) {
+        Err(GeofenceError::ShapeMismatch { found, .. }) => assert_eq!(found, "Triangle"),
+        other => panic!("expected a shape mismatch, got {:?}", other),
+    }
+}
+// Negative Test case to validate on point for Circle
+#[test]
+fn test_polygon_with_incorrect_input() {
+    use geofencing::error::GeofenceError;
+
+    let filename = "circle_geofence.json";
+    match geofencing::geofencer::contains(
+        geofencing::coordinates::Shape::Polygon,
+        filename,
+        3.0,
+        3.0,
+        &geofencing::reporter::NoopReporter,
+    ) {
+        Err(GeofenceError::ShapeMismatch { found, .. }) => assert_eq!(found, "Circle"),
+        other => panic!("expected a shape mismatch, got {:?}", other),
+    }
+}
+// Negative Test case to validate on point for Circle
+#[test]
+fn test_triangle_with_incorrect_input() {
+    use geofencing::error::GeofenceError;
+
+    let filename = "circle_moving_tracker1.json";
+    match geofencing::geofencer::execute(
         geofencing::coordinates::Shape::Triangle,
         filename,
-        55.746768,
-        37.625605
-    ));
+        &geofencing::reporter::NoopReporter,
+    ) {
+        Err(GeofenceError::ShapeMismatch { found, .. }) => assert_eq!(found, "Circle"),
+        other => panic!("expected a shape mismatch, got {:?}", other),
+    }
 }
-// Test case to validate on point for triangle
+
 #[test]
-fn test_triangle_contains() {
-    let filename = "triangle_geofence.json";
-    assert_eq!(
-        geofencing::geofencer::contains(
-            geofencing::coordinates::Shape::Triangle,
-            filename,
-            3.0,
-            3.0
+fn test_polygon_moving_target() {
+    let filename = "polygon_moving_tracker1.json";
+    assert!(!geofencing::geofencer::execute(
+        geofencing::coordinates::Shape::Polygon,
+        filename,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap()
+    .is_empty());
+}
+
+#[test]
+fn test_circle_moving_target() {
+    let filename = "circle_moving_tracker1.json";
+    assert!(!geofencing::geofencer::execute(
+        geofencing::coordinates::Shape::Circle,
+        filename,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap()
+    .is_empty());
+}
+
+#[test]
+fn test_triangle_moving_target() {
+    let filename = "triangle_moving_tracker1.json";
+    assert!(!geofencing::geofencer::execute(
+        geofencing::coordinates::Shape::Triangle,
+        filename,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap()
+    .is_empty());
+}
+
+// Test case to validate fences of different shapes through the Geofence trait
+#[test]
+fn test_geofence_trait_heterogeneous_fences() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{CircleCoordinates, Coordinates};
+    use geofencing::fence::Geofence;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::triangle::TriangleFence;
+
+    let fences: Vec<Box<dyn Geofence>> = vec![
+        Box::new(CircleFence::new(CircleCoordinates::new(1.0, 1.0, 6.0)).unwrap()),
+        Box::new(TriangleFence::new(
+            Coordinates::new(0.0, 0.0),
+            Coordinates::new(10.0, 0.0),
+            Coordinates::new(5.0, 5.0),
+        )),
+        Box::new(
+            PolygonFence::new(vec![
+                Coordinates::new(0.0, 0.0),
+                Coordinates::new(4.0, 0.0),
+                Coordinates::new(4.0, 4.0),
+                Coordinates::new(0.0, 4.0),
+            ])
+            .unwrap(),
         ),
-        true
+    ];
+    let point = Coordinates::new(3.0, 2.0);
+    assert!(fences.iter().all(|fence| fence.contains(&point)));
+    let far_away = Coordinates::new(50.0, 50.0);
+    assert!(fences.iter().all(|fence| !fence.contains(&far_away)));
+    assert!(fences.iter().all(|fence| fence.distance_to(&point) == 0.0));
+}
+
+// Test case to validate area, bounding box and distance of the fences
+#[test]
+fn test_geofence_trait_measurements() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{BoundingBox, CircleCoordinates, Coordinates};
+    use geofencing::fence::Geofence;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::triangle::TriangleFence;
+
+    let square = PolygonFence::new(vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(4.0, 0.0),
+        Coordinates::new(4.0, 4.0),
+        Coordinates::new(0.0, 4.0),
+    ])
+    .unwrap();
+    assert_eq!(square.area(), 16.0);
+    assert_eq!(square.bounding_box(), BoundingBox::new(0.0, 0.0, 4.0, 4.0));
+    assert_eq!(square.distance_to(&Coordinates::new(7.0, 2.0)), 3.0);
+
+    let triangle = TriangleFence::new(
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(5.0, 5.0),
     );
+    assert_eq!(triangle.area(), 25.0);
+    assert_eq!(triangle.distance_to(&Coordinates::new(5.0, -2.0)), 2.0);
+
+    let circle = CircleFence::new(CircleCoordinates::new(1.0, 1.0, 6.0)).unwrap();
     assert_eq!(
-        geofencing::geofencer::contains(
-            geofencing::coordinates::Shape::Triangle,
-            filename,
-            5.0,
-            7.0
-        ),
-        false
+        circle.bounding_box(),
+        BoundingBox::new(-5.0, -5.0, 7.0, 7.0)
     );
+    assert_eq!(circle.distance_to(&Coordinates::new(1.0, 10.0)), 3.0);
 }
 
-// Test case to validate on point for Circle
+// Test case to validate loading the fence once from any reader and querying it in memory
 #[test]
-#[should_panic]
-fn test_circle_contains() {
-    let filename = "circle_geofence.json";
+fn test_fence_loaded_from_reader() {
+    use geofencing::coordinates::{Coordinates, Shape};
+    use geofencing::fence::Geofence;
+    use geofencing::loader::read_tracker;
+    use geofencing::polygon::PolygonFence;
+
+    let json = r#"{
+        "_comment": "in memory fence",
+        "shape": "Polygon",
+        "vehicle": "truck",
+        "shape_coordinate": [
+            {"lat": 0.0, "lon": 0.0}, {"lat": 4.0, "lon": 0.0},
+            {"lat": 4.0, "lon": 4.0}, {"lat": 0.0, "lon": 4.0}
+        ],
+        "moving_coordinate": []
+    }"#;
+    let tracker = read_tracker(json.as_bytes(), Shape::Polygon).unwrap();
+    let fence = PolygonFence::new(tracker.shape_coordinate).unwrap();
+    assert!(fence.contains(&Coordinates::new(1.0, 2.0)));
+    assert!(!fence.contains(&Coordinates::new(5.0, 2.0)));
+}
+
+// Test case to validate loading the fence from a path outside of the data folder
+#[test]
+fn test_fence_loaded_from_path() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::geofencer::track;
+    use geofencing::loader::read_circle_tracker_file;
+    use std::convert::TryFrom;
+    use std::path::Path;
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/circle_moving_tracker1.json");
+    let tracker = read_circle_tracker_file(&path).unwrap();
+    let fence = CircleFence::try_from(tracker.shape_coordinate).unwrap();
+    assert!(fence.contains(&Coordinates::new(1.0, 1.0)));
+    let evaluations = track(
+        &fence,
+        "circle_moving_tracker1.json",
+        &tracker.vehicle,
+        &tracker.moving_coordinate,
+        &geofencing::reporter::NoopReporter,
+    );
+    assert_eq!(evaluations.len(), tracker.moving_coordinate.len());
+}
+
+// Negative Test case to validate fences that cannot be created from the coordinates
+#[test]
+fn test_invalid_geometry() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::error::GeofenceError;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::triangle::TriangleFence;
+
+    let points = vec![Coordinates::new(0.0, 0.0), Coordinates::new(4.0, 0.0)];
+    match PolygonFence::new(points.clone()) {
+        Err(GeofenceError::InvalidGeometry(_)) => {}
+        other => panic!("expected invalid geometry, got {:?}", other),
+    }
+    match TriangleFence::from_points(&points) {
+        Err(GeofenceError::InvalidGeometry(_)) => {}
+        other => panic!("expected invalid geometry, got {:?}", other),
+    }
+}
+
+// Negative Test case to validate malformed json
+#[test]
+fn test_malformed_json() {
+    use geofencing::coordinates::Shape;
+    use geofencing::error::GeofenceError;
+    use geofencing::loader::read_tracker;
+
+    match read_tracker("{ \"shape\": ".as_bytes(), Shape::Polygon) {
+        Err(GeofenceError::Json(_)) => {}
+        other => panic!("expected a json error, got {:?}", other),
+    }
+}
+
+// Test case to validate the geodesic distances against known values
+#[test]
+fn test_geodesic_distance() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::geodesy::{haversine, vincenty};
+
+    let origin = Coordinates::new(0.0, 0.0);
+    let one_degree_east = Coordinates::new(0.0, 1.0);
+    assert!((haversine(&origin, &one_degree_east) - 111_195.08).abs() < 1.0);
+    assert!((vincenty(&origin, &one_degree_east).unwrap() - 111_319.49).abs() < 1.0);
+    assert_eq!(vincenty(&origin, &origin), Some(0.0));
+}
+
+// Test case to validate on point for Circle with the radius in meters
+#[test]
+fn test_circle_contains_in_meters() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{Coordinates, Shape};
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{Model, Unit};
+
+    let filename = "circle_geofence_meters.json";
+    // 0.0033 degrees of latitude is about 367 meters, 0.0058 degrees is about 645 meters.
+    assert!(geofencing::geofencer::contains(
+        Shape::Circle,
+        filename,
+        45.5185,
+        -122.6784,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        Shape::Circle,
+        filename,
+        45.5210,
+        -122.6784,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+
+    // Far from the equator a degree of longitude is much shorter than a degree of latitude.
+    let fence = CircleFence::geodesic(
+        &Coordinates::new(60.0, 10.0),
+        1.0,
+        Unit::Kilometers,
+        Model::Sphere,
+    )
+    .unwrap();
+    assert!(fence.contains(&Coordinates::new(60.0, 10.017)));
+    assert!(!fence.contains(&Coordinates::new(60.017, 10.0)));
+    let bbox = fence.bounding_box();
+    assert!((bbox.max_lon - bbox.min_lon) > 1.9 * (bbox.max_lat - bbox.min_lat));
+    assert!((fence.distance_to(&Coordinates::new(60.0, 10.0 + 0.036)) - 1000.0).abs() < 20.0);
+}
+
+// Test case to reject circle fences whose radius is not a positive number
+#[test]
+fn test_circle_radius() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::error::GeofenceError;
+    use geofencing::geodesy::{Model, Unit};
+    use geofencing::loader::read_fence_json;
+
+    let center = Coordinates::new(45.5, -122.7);
+    for rad in &[-100.0, 0.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            CircleFence::geodesic(&center, *rad, Unit::Meters, Model::Sphere),
+            Err(GeofenceError::InvalidGeometry(_))
+        ));
+        assert!(matches!(
+            CircleFence::from_center(&center, *rad),
+            Err(GeofenceError::InvalidGeometry(_))
+        ));
+    }
+    assert!(CircleFence::from_center(&center, 0.5).is_ok());
+
+    let file = r#"{"_comment": "", "shape": "Circle", "vehicle": "van",
+        "shape_coordinate": {"lat": 45.5, "lon": -122.7, "rad": -100.0, "unit": "meters"},
+        "moving_coordinate": []}"#;
+    let error = read_fence_json(file.as_bytes(), None)
+        .unwrap()
+        .to_fence()
+        .unwrap_err();
+    assert!(matches!(error, GeofenceError::InvalidGeometry(_)));
+    assert!(error.to_string().contains("radius"));
+}
+
+// Test case to validate points whose horizontal line passes through a vertex of the Polygon
+#[test]
+fn test_polygon_locate_through_vertex() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::polygon::PolygonFence;
+
+    let diamond = PolygonFence::new(vec![
+        Coordinates::new(0.0, 2.0),
+        Coordinates::new(2.0, 0.0),
+        Coordinates::new(4.0, 2.0),
+        Coordinates::new(2.0, 4.0),
+    ])
+    .unwrap();
+    assert_eq!(
+        diamond.locate(&Coordinates::new(1.0, 2.0)),
+        Location::Inside
+    );
+    assert_eq!(
+        diamond.locate(&Coordinates::new(3.9, 2.0)),
+        Location::Inside
+    );
+    assert_eq!(
+        diamond.locate(&Coordinates::new(-1.0, 2.0)),
+        Location::Outside
+    );
     assert_eq!(
-        geofencing::geofencer::contains(geofencing::coordinates::Shape::Circle, filename, 3.0, 3.0),
-        true
+        diamond.locate(&Coordinates::new(5.0, 2.0)),
+        Location::Outside
     );
     assert_eq!(
-        geofencing::geofencer::contains(geofencing::coordinates::Shape::Circle, filename, 7.0, 7.0),
-        false
+        diamond.locate(&Coordinates::new(2.0, 5.0)),
+        Location::Outside
     );
     assert_eq!(
-        geofencing::geofencer::contains(geofencing::coordinates::Shape::Circle, filename, 2.0, 1.0),
-        false
+        diamond.locate(&Coordinates::new(0.0, 2.0)),
+        Location::OnBoundary
     );
 }
-// Negative Test case to validate on point for Circle
+
+// Test case to validate points on and along the horizontal edges of the Polygon
 #[test]
-#[should_panic]
-fn test_circle_with_incorrect_input() {
-    let filename = "triangle_geofence.json";
+fn test_polygon_locate_on_boundary() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::polygon::PolygonFence;
+
+    // Concave polygon shaped like the letter U, with horizontal edges at longitude 0, 1 and 4.
+    let fence = PolygonFence::new(vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(6.0, 0.0),
+        Coordinates::new(6.0, 4.0),
+        Coordinates::new(4.0, 4.0),
+        Coordinates::new(4.0, 1.0),
+        Coordinates::new(2.0, 1.0),
+        Coordinates::new(2.0, 4.0),
+        Coordinates::new(0.0, 4.0),
+    ])
+    .unwrap();
+    assert_eq!(
+        fence.locate(&Coordinates::new(3.0, 0.0)),
+        Location::OnBoundary
+    );
+    assert_eq!(
+        fence.locate(&Coordinates::new(3.0, 1.0)),
+        Location::OnBoundary
+    );
+    assert_eq!(
+        fence.locate(&Coordinates::new(4.0, 4.0)),
+        Location::OnBoundary
+    );
+    assert_eq!(fence.locate(&Coordinates::new(1.0, 1.0)), Location::Inside);
+    assert_eq!(
+        fence.locate(&Coordinates::new(5.0, 4.0)),
+        Location::OnBoundary
+    );
+    assert_eq!(fence.locate(&Coordinates::new(3.0, 2.0)), Location::Outside);
+    assert_eq!(fence.locate(&Coordinates::new(7.0, 0.0)), Location::Outside);
+    assert_eq!(
+        fence.locate(&Coordinates::new(-1.0, 4.0)),
+        Location::Outside
+    );
+    assert_eq!(fence.locate(&Coordinates::new(1.0, 3.0)), Location::Inside);
+    assert_eq!(fence.locate(&Coordinates::new(5.0, 3.0)), Location::Inside);
+    // A clockwise ring gives the same answers as the counter clockwise one.
+    let mut points = fence.points().to_vec();
+    points.reverse();
+    let reversed = PolygonFence::new(points).unwrap();
+    assert_eq!(
+        reversed.locate(&Coordinates::new(1.0, 3.0)),
+        Location::Inside
+    );
+    assert_eq!(
+        reversed.locate(&Coordinates::new(3.0, 2.0)),
+        Location::Outside
+    );
     assert_eq!(
-        geofencing::geofencer::contains(geofencing::coordinates::Shape::Circle, filename, 3.0, 3.0),
-        true
+        reversed.locate(&Coordinates::new(3.0, 1.0)),
+        Location::OnBoundary
     );
 }
-// Negative Test case to validate on point for Circle
+
+// Test case to validate on point for triangle with real latitude and longitude decimals
 #[test]
-#[should_panic]
-fn test_polygon_with_incorrect_input() {
-    let filename = "circle_geofence.json";
+fn test_triangle_locate_with_actual_latandlong() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::triangle::TriangleFence;
+
+    let fence = TriangleFence::new(
+        Coordinates::new(45.512_794, -122.679_565),
+        Coordinates::new(45.523_064, -122.676_483),
+        Coordinates::new(45.515_232, -122.662_092),
+    );
     assert_eq!(
-        geofencing::geofencer::contains(
-            geofencing::coordinates::Shape::Polygon,
-            filename,
-            3.0,
-            3.0
-        ),
-        true
+        fence.locate(&Coordinates::new(45.517, -122.673)),
+        Location::Inside
+    );
+    assert_eq!(
+        fence.locate(&Coordinates::new(45.53, -122.673)),
+        Location::Outside
+    );
+    // The middle of a side is on the border, even though it cannot be represented exactly.
+    let middle = Coordinates::new(
+        (45.512_794 + 45.523_064) / 2.0,
+        (-122.679_565 + -122.676_483) / 2.0,
+    );
+    assert_eq!(fence.locate(&middle), Location::OnBoundary);
+    assert_eq!(
+        fence.locate(&Coordinates::new(45.512_794, -122.679_565)),
+        Location::OnBoundary
     );
 }
-// Negative Test case to validate on point for Circle
+
+// Test case to validate the configurable tolerance of the triangle
 #[test]
-#[should_panic]
-fn test_triangle_with_incorrect_input() {
-    let filename = "circle_moving_tracker1.json";
+fn test_triangle_tolerance() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::triangle::TriangleFence;
+
+    let fence = TriangleFence::new(
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(5.0, 5.0),
+    );
+    let near_side = Coordinates::new(5.0, -0.05);
+    assert_eq!(fence.locate(&near_side), Location::Outside);
+    let fence = fence.with_tolerance(0.1);
+    assert_eq!(fence.locate(&near_side), Location::OnBoundary);
     assert_eq!(
-        geofencing::geofencer::contains(
-            geofencing::coordinates::Shape::Triangle,
-            filename,
-            3.0,
-            3.0
-        ),
-        true
+        fence.locate(&Coordinates::new(5.0, 0.05)),
+        Location::OnBoundary
+    );
+    assert_eq!(fence.locate(&Coordinates::new(5.0, 1.0)), Location::Inside);
+    assert_eq!(
+        fence.locate(&Coordinates::new(5.0, -1.0)),
+        Location::Outside
     );
 }
 
+// Test case to validate on point for Polygon with a hole
 #[test]
-fn test_polygon_moving_target() {
-    let filename = "polygon_moving_tracker1.json";
-    assert_ne!(
-        geofencing::geofencer::execute(geofencing::coordinates::Shape::Polygon, filename, false),
-        ""
+fn test_polygon_with_holes_contains() {
+    use geofencing::coordinates::Shape;
+
+    let filename = "polygon_geofence_holes.json";
+    assert!(geofencing::geofencer::contains(
+        Shape::Polygon,
+        filename,
+        2.0,
+        5.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        Shape::Polygon,
+        filename,
+        5.0,
+        5.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        Shape::Polygon,
+        filename,
+        11.0,
+        5.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::execute(
+        Shape::Polygon,
+        filename,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap()
+    .is_empty());
+}
+
+// Test case to validate that the fill rules treat holes consistently
+#[test]
+fn test_polygon_fill_rules() {
+    use geofencing::coordinates::{Coordinates, FillRule, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::polygon::PolygonFence;
+
+    let outer = vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ];
+    // Hole going in the same (counter clockwise) direction as the outer ring.
+    let same_direction = vec![
+        Coordinates::new(4.0, 4.0),
+        Coordinates::new(6.0, 4.0),
+        Coordinates::new(6.0, 6.0),
+        Coordinates::new(4.0, 6.0),
+    ];
+    let mut opposite_direction = same_direction.clone();
+    opposite_direction.reverse();
+    let in_hole = Coordinates::new(5.0, 5.0);
+
+    let even_odd = PolygonFence::with_holes(outer.clone(), vec![same_direction.clone()]).unwrap();
+    assert_eq!(even_odd.locate(&in_hole), Location::Outside);
+    assert_eq!(even_odd.area(), 96.0);
+    let non_zero = PolygonFence::with_holes(outer.clone(), vec![same_direction])
+        .unwrap()
+        .with_fill_rule(FillRule::NonZero);
+    assert_eq!(non_zero.locate(&in_hole), Location::Inside);
+    let non_zero = PolygonFence::with_holes(outer, vec![opposite_direction])
+        .unwrap()
+        .with_fill_rule(FillRule::NonZero);
+    assert_eq!(non_zero.locate(&in_hole), Location::Outside);
+    assert_eq!(
+        non_zero.locate(&Coordinates::new(4.0, 5.0)),
+        Location::OnBoundary
     );
+    assert_eq!(non_zero.distance_to(&in_hole), 1.0);
 }
 
+// Test case to validate on point for MultiPolygon
 #[test]
-fn test_circle_moving_target() {
-    let filename = "circle_moving_tracker1.json";
-    assert_ne!(
-        geofencing::geofencer::execute(geofencing::coordinates::Shape::Circle, filename, false),
-        ""
+fn test_multipolygon_contains() {
+    use geofencing::coordinates::Shape;
+
+    let filename = "multipolygon_geofence.json";
+    assert!(geofencing::geofencer::contains(
+        Shape::MultiPolygon,
+        filename,
+        3.0,
+        3.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(geofencing::geofencer::contains(
+        Shape::MultiPolygon,
+        filename,
+        12.0,
+        1.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        Shape::MultiPolygon,
+        filename,
+        1.5,
+        1.5,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::contains(
+        Shape::MultiPolygon,
+        filename,
+        7.0,
+        2.0,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap());
+    assert!(!geofencing::geofencer::execute(
+        Shape::MultiPolygon,
+        filename,
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap()
+    .is_empty());
+}
+
+// Test case to validate the Enter and Dwell events of a vehicle moving in to a fence
+#[test]
+fn test_tracker_events() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{CircleCoordinates, Coordinates};
+    use geofencing::polygon::PolygonFence;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(120));
+    tracker.add_fence(
+        "depot",
+        PolygonFence::new(vec![
+            Coordinates::new(0.0, 0.0),
+            Coordinates::new(4.0, 0.0),
+            Coordinates::new(4.0, 4.0),
+            Coordinates::new(0.0, 4.0),
+        ])
+        .unwrap(),
     );
+    tracker.add_fence(
+        "customer",
+        CircleFence::new(CircleCoordinates::new(10.0, 10.0, 1.0)).unwrap(),
+    );
+    let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(1_600_000_000 + seconds);
+
+    assert!(tracker
+        .update("van", &Coordinates::new(-1.0, 2.0), at(0))
+        .is_empty());
+    let events = tracker.update("van", &Coordinates::new(1.0, 2.0), at(60));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].fence_id, "depot");
+    assert_eq!(events[0].kind, EventKind::Enter);
+    assert!(tracker.is_inside("van", "depot"));
+    assert!(tracker
+        .update("van", &Coordinates::new(2.0, 2.0), at(120))
+        .is_empty());
+    let events = tracker.update("van", &Coordinates::new(3.0, 2.0), at(200));
+    assert_eq!(events[0].kind, EventKind::Dwell(Duration::from_secs(140)));
+    // Dwell is only reported once per visit.
+    assert!(tracker
+        .update("van", &Coordinates::new(3.0, 3.0), at(400))
+        .is_empty());
 }
 
+// Test case to validate that each vehicle has its own state, and that a move from one fence to
+// another is an Exit followed by an Enter
 #[test]
-fn test_triangle_moving_target() {
-    let filename = "triangle_moving_tracker1.json";
-    assert_ne!(
-        geofencing::geofencer::execute(geofencing::coordinates::Shape::Triangle, filename, false),
-        ""
+fn test_tracker_events_per_vehicle() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{CircleCoordinates, Coordinates};
+    use geofencing::polygon::PolygonFence;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(120));
+    tracker.add_fence(
+        "depot",
+        PolygonFence::new(vec![
+            Coordinates::new(0.0, 0.0),
+            Coordinates::new(4.0, 0.0),
+            Coordinates::new(4.0, 4.0),
+            Coordinates::new(0.0, 4.0),
+        ])
+        .unwrap(),
+    );
+    tracker.add_fence(
+        "customer",
+        CircleFence::new(CircleCoordinates::new(10.0, 10.0, 1.0)).unwrap(),
+    );
+    let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(1_600_000_000 + seconds);
+
+    let events = tracker.update("van", &Coordinates::new(1.0, 2.0), at(0));
+    assert_eq!(events[0].kind, EventKind::Enter);
+    // Another vehicle has its own state.
+    let events = tracker.update("bus", &Coordinates::new(10.0, 10.5), at(400));
+    assert_eq!(events[0].fence_id, "customer");
+    assert_eq!(events[0].kind, EventKind::Enter);
+    assert!(tracker.is_inside("van", "depot"));
+    assert!(!tracker.is_inside("bus", "depot"));
+    let events = tracker.update("van", &Coordinates::new(10.0, 10.0), at(500));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].kind, EventKind::Exit);
+    assert_eq!(events[1].kind, EventKind::Enter);
+    assert!(!tracker.is_inside("van", "depot"));
+}
+
+// Test case to remove a fence from the tracker, along with the vehicles inside it
+#[test]
+fn test_tracker_remove_fence() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{CircleCoordinates, Coordinates};
+    use geofencing::polygon::PolygonFence;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(120));
+    tracker.add_fence(
+        "depot",
+        PolygonFence::new(vec![
+            Coordinates::new(0.0, 0.0),
+            Coordinates::new(4.0, 0.0),
+            Coordinates::new(4.0, 4.0),
+            Coordinates::new(0.0, 4.0),
+        ])
+        .unwrap(),
+    );
+    tracker.add_fence(
+        "customer",
+        CircleFence::new(CircleCoordinates::new(10.0, 10.0, 1.0)).unwrap(),
+    );
+    let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(1_600_000_000 + seconds);
+
+    let events = tracker.update("bus", &Coordinates::new(10.0, 10.5), at(0));
+    assert_eq!(events[0].kind, EventKind::Enter);
+    assert!(tracker.is_inside("bus", "customer"));
+    assert!(tracker.remove_fence("customer"));
+    assert!(!tracker.is_inside("bus", "customer"));
+    assert!(!tracker.remove_fence("customer"));
+}
+
+// Test case to validate tracking of fixes with timestamps, where the fix of an out of sequence
+// upload is ignored
+#[test]
+fn test_tracker_with_timed_fixes() {
+    use geofencing::coordinates::Shape;
+    use geofencing::loader::{data_path, read_tracker_file};
+    use geofencing::polygon::PolygonFence;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::time::Duration;
+
+    let tracker_file = read_tracker_file(
+        data_path("polygon_moving_tracker_timed.json"),
+        Shape::Polygon,
+    )
+    .unwrap();
+    assert_eq!(tracker_file.moving_coordinate[0].speed, Some(12.5));
+    assert_eq!(tracker_file.moving_coordinate[3].heading, None);
+
+    let fence = PolygonFence::new(tracker_file.shape_coordinate.clone()).unwrap();
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(300));
+    tracker.add_fence("depot", fence.clone());
+    // As uploaded, the last fix is older than the one before it and is ignored.
+    let kinds: Vec<EventKind> = tracker_file
+        .moving_coordinate
+        .iter()
+        .flat_map(|fix| tracker.update_fix(&tracker_file.vehicle, fix))
+        .map(|event| event.kind)
+        .collect();
+    assert_eq!(kinds, vec![EventKind::Enter, EventKind::Exit]);
+}
+
+// Test case to validate tracking of timed fixes once they are sorted by time
+#[test]
+fn test_tracker_with_sorted_fixes() {
+    use geofencing::coordinates::{sort_by_time, Shape};
+    use geofencing::loader::{data_path, read_tracker_file};
+    use geofencing::polygon::PolygonFence;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::time::Duration;
+
+    let mut tracker_file = read_tracker_file(
+        data_path("polygon_moving_tracker_timed.json"),
+        Shape::Polygon,
+    )
+    .unwrap();
+    let fence = PolygonFence::new(tracker_file.shape_coordinate.clone()).unwrap();
+    // Once sorted, the vehicle is seen dwelling in the fence before it exits.
+    sort_by_time(&mut tracker_file.moving_coordinate);
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(300));
+    tracker.add_fence("depot", fence);
+    let kinds: Vec<EventKind> = tracker_file
+        .moving_coordinate
+        .iter()
+        .flat_map(|fix| tracker.update_fix(&tracker_file.vehicle, fix))
+        .map(|event| event.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::Enter,
+            EventKind::Dwell(Duration::from_secs(340)),
+            EventKind::Exit
+        ]
+    );
+    assert!(!geofencing::geofencer::execute(
+        Shape::Polygon,
+        "polygon_moving_tracker_timed.json",
+        &geofencing::reporter::NoopReporter
+    )
+    .unwrap()
+    .is_empty());
+}
+
+// Test case to query an index of many fences for the fences that contain a coordinate
+#[test]
+fn test_fence_index() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::index::FenceIndex;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::triangle::TriangleFence;
+
+    let mut index = FenceIndex::new();
+    for row in 0..20 {
+        for col in 0..20 {
+            let (lat, lon) = (row as f64 * 10.0, col as f64 * 10.0);
+            let fence = PolygonFence::new(vec![
+                Coordinates::new(lat, lon),
+                Coordinates::new(lat + 5.0, lon),
+                Coordinates::new(lat + 5.0, lon + 5.0),
+                Coordinates::new(lat, lon + 5.0),
+            ])
+            .unwrap();
+            index.insert(&format!("square-{}-{}", row, col), fence);
+        }
+    }
+    index.insert(
+        "circle",
+        CircleFence::from_center(&Coordinates::new(12.0, 12.0), 2.0).unwrap(),
     );
+    index.insert(
+        "triangle",
+        TriangleFence::new(
+            Coordinates::new(10.0, 10.0),
+            Coordinates::new(15.0, 10.0),
+            Coordinates::new(10.0, 15.0),
+        ),
+    );
+    assert_eq!(index.len(), 402);
+
+    let mut found = index.query_point(&Coordinates::new(13.0, 13.0));
+    found.sort();
+    assert_eq!(found, vec!["circle", "square-1-1"]);
+    // Inside the bounding box of the triangle, but not inside the triangle.
+    assert_eq!(
+        index.query_point(&Coordinates::new(7.0, 7.0)),
+        Vec::<&str>::new()
+    );
+}
+
+// Test case to query an index of many fences for the fences whose bounding box meets a box
+#[test]
+fn test_fence_index_query_bbox() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{BoundingBox, Coordinates};
+    use geofencing::index::FenceIndex;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::triangle::TriangleFence;
+
+    let mut index = FenceIndex::new();
+    for row in 0..20 {
+        for col in 0..20 {
+            let (lat, lon) = (row as f64 * 10.0, col as f64 * 10.0);
+            let fence = PolygonFence::new(vec![
+                Coordinates::new(lat, lon),
+                Coordinates::new(lat + 5.0, lon),
+                Coordinates::new(lat + 5.0, lon + 5.0),
+                Coordinates::new(lat, lon + 5.0),
+            ])
+            .unwrap();
+            index.insert(&format!("square-{}-{}", row, col), fence);
+        }
+    }
+    index.insert(
+        "circle",
+        CircleFence::from_center(&Coordinates::new(12.0, 12.0), 2.0).unwrap(),
+    );
+    index.insert(
+        "triangle",
+        TriangleFence::new(
+            Coordinates::new(10.0, 10.0),
+            Coordinates::new(15.0, 10.0),
+            Coordinates::new(10.0, 15.0),
+        ),
+    );
+
+    let mut found = index.query_bbox(&BoundingBox::new(9.0, 9.0, 11.0, 11.0));
+    found.sort();
+    assert_eq!(found, vec!["circle", "square-1-1", "triangle"]);
+}
+
+// Test case to replace and remove the fences of an index by their id
+#[test]
+fn test_fence_index_replace_and_remove() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::index::FenceIndex;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::triangle::TriangleFence;
+
+    let mut index = FenceIndex::new();
+    for row in 0..20 {
+        for col in 0..20 {
+            let (lat, lon) = (row as f64 * 10.0, col as f64 * 10.0);
+            let fence = PolygonFence::new(vec![
+                Coordinates::new(lat, lon),
+                Coordinates::new(lat + 5.0, lon),
+                Coordinates::new(lat + 5.0, lon + 5.0),
+                Coordinates::new(lat, lon + 5.0),
+            ])
+            .unwrap();
+            index.insert(&format!("square-{}-{}", row, col), fence);
+        }
+    }
+    index.insert(
+        "circle",
+        CircleFence::from_center(&Coordinates::new(12.0, 12.0), 2.0).unwrap(),
+    );
+    index.insert(
+        "triangle",
+        TriangleFence::new(
+            Coordinates::new(10.0, 10.0),
+            Coordinates::new(15.0, 10.0),
+            Coordinates::new(10.0, 15.0),
+        ),
+    );
+
+    // Replacing a fence keeps a single entry for its id.
+    assert!(index
+        .insert(
+            "circle",
+            CircleFence::from_center(&Coordinates::new(100.0, 100.0), 1.0).unwrap()
+        )
+        .is_some());
+    assert_eq!(index.len(), 402);
+    assert_eq!(
+        index.query_point(&Coordinates::new(99.5, 99.5)),
+        vec!["circle"]
+    );
+    assert_eq!(
+        index.query_point(&Coordinates::new(13.0, 13.0)),
+        vec!["square-1-1"]
+    );
+    assert!(index.remove("circle").is_some());
+    assert!(index.get("circle").is_none());
+    assert!(index.query_point(&Coordinates::new(99.5, 99.5)).is_empty());
+}
+
+// Test case to verify that the bounding box of a geodesic circle on the ellipsoid encloses the
+// whole circle
+#[test]
+fn test_geodesic_circle_bounding_box() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{Model, Unit};
+
+    // A degree of latitude is shorter on the ellipsoid at the equator than on the sphere.
+    let equator = CircleFence::geodesic(
+        &Coordinates::new(0.0, 0.0),
+        100.0,
+        Unit::Kilometers,
+        Model::Ellipsoid,
+    )
+    .unwrap();
+    let north = Coordinates::new(0.903, 0.0);
+    assert!(equator.contains(&north));
+    assert!(equator.bounding_box().contains(&north));
+}
+
+// Test case to verify that the bounding box of a geodesic circle at high latitude encloses the
+// whole circle
+#[test]
+fn test_geodesic_circle_bounding_box_high_latitude() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{self, Model, Unit};
+
+    // At high latitude the circle is widest north of its center.
+    let arctic = CircleFence::geodesic(
+        &Coordinates::new(80.0, 0.0),
+        1000.0,
+        Unit::Kilometers,
+        Model::Sphere,
+    )
+    .unwrap();
+    let bbox = arctic.bounding_box();
+    for degree in 0..360 {
+        let point = geodesy::destination(
+            &Coordinates::new(80.0, 0.0),
+            (degree as f64).to_radians(),
+            999_999.0,
+        );
+        assert!(bbox.contains(&point), "{:?} is out of {:?}", point, bbox);
+    }
+    assert!(bbox.max_lon > 64.0);
+}
+
+// Test case to verify that the bounding box of a geodesic circle across the antimeridian or over
+// a pole spans every longitude
+#[test]
+fn test_geodesic_circle_bounding_box_full_longitude() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{Model, Unit};
+
+    // Circles across the antimeridian or over a pole span every longitude.
+    let dateline = CircleFence::geodesic(
+        &Coordinates::new(0.0, 179.9),
+        50.0,
+        Unit::Kilometers,
+        Model::Sphere,
+    )
+    .unwrap();
+    let bbox = dateline.bounding_box();
+    assert_eq!((bbox.min_lon, bbox.max_lon), (-180.0, 180.0));
+    let pole = CircleFence::geodesic(
+        &Coordinates::new(89.5, 0.0),
+        100.0,
+        Unit::Kilometers,
+        Model::Sphere,
+    )
+    .unwrap();
+    let bbox = pole.bounding_box();
+    assert_eq!(
+        (bbox.min_lon, bbox.max_lon, bbox.max_lat),
+        (-180.0, 180.0, 90.0)
+    );
+}
+
+// Test case to verify that the index does not miss the geodesic circles that contain a coordinate
+#[test]
+fn test_fence_index_geodesic_circles() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::geodesy::{Model, Unit};
+    use geofencing::index::FenceIndex;
+
+    let equator = CircleFence::geodesic(
+        &Coordinates::new(0.0, 0.0),
+        100.0,
+        Unit::Kilometers,
+        Model::Ellipsoid,
+    )
+    .unwrap();
+    let north = Coordinates::new(0.903, 0.0);
+    let dateline = CircleFence::geodesic(
+        &Coordinates::new(0.0, 179.9),
+        50.0,
+        Unit::Kilometers,
+        Model::Sphere,
+    )
+    .unwrap();
+    let pole = CircleFence::geodesic(
+        &Coordinates::new(89.5, 0.0),
+        100.0,
+        Unit::Kilometers,
+        Model::Sphere,
+    )
+    .unwrap();
+
+    let mut index = FenceIndex::new();
+    index.insert("equator", equator);
+    index.insert("dateline", dateline);
+    index.insert("pole", pole);
+    assert_eq!(index.query_point(&north), vec!["equator"]);
+    assert_eq!(
+        index.query_point(&Coordinates::new(0.0, -179.9)),
+        vec!["dateline"]
+    );
+    assert_eq!(
+        index.query_point(&Coordinates::new(89.9, 120.0)),
+        vec!["pole"]
+    );
+}
+
+// Test case to read fences and tracks from GeoJSON, in [lon, lat] order
+#[test]
+fn test_geojson_read() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::geojson::*;
+    use geofencing::loader::data_path;
+
+    let geojson = read_geojson_file(data_path("fences.geojson")).unwrap();
+    let ids: Vec<&str> = geojson.fences.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, vec!["depot", "warehouses", "office"]);
+    let depot = &geojson.fences[0].1;
+    // The polygon spans longitudes 77 to 78 and latitudes 12 to 12.5.
+    assert_eq!(
+        depot.locate(&Coordinates::new(12.1, 77.5)),
+        Location::Inside
+    );
+    assert_eq!(
+        depot.locate(&Coordinates::new(77.5, 12.1)),
+        Location::Outside
+    );
+    assert_eq!(
+        depot.locate(&Coordinates::new(12.25, 77.5)),
+        Location::Outside
+    );
+    assert_eq!(geojson.fences[1].1.shape().to_string(), "MultiPolygon");
+    let office = &geojson.fences[2].1;
+    assert!(office.contains(&Coordinates::new(12.974, 77.5946)));
+    assert!(!office.contains(&Coordinates::new(12.9716, 77.6)));
+
+    let (vehicle, track) = &geojson.tracks[0];
+    assert_eq!(vehicle, "van");
+    assert_eq!(track.len(), 3);
+    assert_eq!((track[1].lat, track[1].lon), (12.25, 77.2));
+    assert_eq!(track[1].altitude, Some(920.0));
+    assert_eq!(track[2].timestamp, Some(1600000120.0));
+}
+
+// Test case to write the fences and tracks read from GeoJSON, and to read them back
+#[test]
+fn test_geojson_round_trip() {
+    use geofencing::fence::Geofence;
+    use geofencing::geojson::*;
+    use geofencing::loader::data_path;
+
+    let geojson = read_geojson_file(data_path("fences.geojson")).unwrap();
+    let track = &geojson.tracks[0].1;
+
+    // Writing the document and reading it back gives the same fences and tracks.
+    let mut written = Vec::new();
+    write_geojson(&mut written, &geojson.to_geojson()).unwrap();
+    let read_back = read_geojson(written.as_slice()).unwrap();
+    assert_eq!(read_back.fences.len(), 3);
+    for ((id, fence), (read_id, read_fence)) in geojson.fences.iter().zip(&read_back.fences) {
+        assert_eq!(id, read_id);
+        assert_eq!(fence.area(), read_fence.area());
+        for fix in track {
+            assert_eq!(
+                fence.locate(&fix.coordinates()),
+                read_fence.locate(&fix.coordinates())
+            );
+        }
+    }
+    assert_eq!(read_back.tracks[0].1, *track);
+}
+
+// Test case to write a triangle as a GeoJSON polygon and to read it back as a triangle
+#[test]
+fn test_geojson_triangle() {
+    use geofencing::coordinates::{Coordinates, Shape};
+    use geofencing::fence::Fence;
+    use geofencing::geojson::*;
+
+    let triangle = Fence::Triangle(geofencing::triangle::TriangleFence::new(
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(2.0, 0.0),
+        Coordinates::new(0.0, 3.0),
+    ));
+    let feature = fence_to_feature("yard", &triangle);
+    assert_eq!(feature["geometry"]["coordinates"][0][2][0], 3.0);
+    let read_back = read_geojson(feature.to_string().as_bytes()).unwrap();
+    assert_eq!(read_back.fences[0].0, "yard");
+    assert!(matches!(read_back.fences[0].1.shape(), Shape::Triangle));
+}
+
+// Test case to write the evaluations of a track as GeoJSON
+#[test]
+fn test_geojson_evaluations() {
+    use geofencing::geojson::*;
+    use geofencing::loader::data_path;
+
+    let geojson = read_geojson_file(data_path("fences.geojson")).unwrap();
+    let depot = &geojson.fences[0].1;
+    let (vehicle, track) = &geojson.tracks[0];
+
+    let results = evaluations_to_geojson(&geofencing::evaluation::evaluate(
+        depot, "depot", vehicle, track,
+    ));
+    let locations: Vec<&str> = results["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|feature| feature["properties"]["location"].as_str().unwrap())
+        .collect();
+    assert_eq!(locations, vec!["outside", "inside", "outside"]);
+    assert_eq!(results["features"][0]["properties"]["distance"], 0.5);
+    assert_eq!(results["features"][0]["geometry"]["coordinates"][0], 76.5);
+}
+
+// Test case to reject malformed GeoJSON positions and geometries as parse errors
+#[test]
+fn test_geojson_malformed() {
+    use geofencing::geojson::*;
+
+    // A position whose longitude is not a number is not read from the next entries.
+    let bad = r#"{"type": "Feature", "geometry": {"type": "LineString",
+        "coordinates": [["a", 1.0, 2.0], [1.0, 2.0]]}, "properties": {}}"#;
+    assert!(matches!(
+        read_geojson(bad.as_bytes()),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+    // Nor is a position without a latitude, and both are reported the same way.
+    let short = r#"{"type": "Point", "coordinates": [1.0]}"#;
+    assert!(matches!(
+        read_geojson(short.as_bytes()),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+    let unknown = r#"{"type": "GeometryCollection", "geometries": []}"#;
+    assert!(matches!(
+        read_geojson(unknown.as_bytes()),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+}
+
+// Test case to write the fences and tracks of the json test data as WKT and WKB, and read them back
+#[test]
+fn test_wkt_wkb_round_trip() {
+    use geofencing::coordinates::{Coordinates, Shape};
+    use geofencing::loader::*;
+    use geofencing::multipolygon::MultiPolygonFence;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::wkb::*;
+    use geofencing::wkt::*;
+
+    let round_trip = |geometry: &Geometry| {
+        let wkt = read_wkt(&write_wkt(geometry)).unwrap();
+        let wkb = read_wkb(&write_wkb(geometry)).unwrap();
+        let (ewkb, srid) =
+            read_ewkb(&from_hex(&to_hex(&write_ewkb(geometry, 4326))).unwrap()).unwrap();
+        assert_eq!(srid, Some(4326));
+        for read_back in &[wkt, wkb, ewkb] {
+            assert_eq!(format!("{:?}", read_back), format!("{:?}", geometry));
+        }
+    };
+
+    for file in &[
+        "polygon_geofence.json",
+        "polygon_geofence_holes.json",
+        "polygon_moving_tracker1.json",
+    ] {
+        let u = read_tracker_file(data_path(file), Shape::Polygon).unwrap();
+        let fence = PolygonFence::with_holes(u.shape_coordinate, u.interior_coordinate).unwrap();
+        round_trip(&Geometry::Polygon(fence));
+        let track: Vec<Coordinates> = u
+            .moving_coordinate
+            .iter()
+            .map(|fix| fix.coordinates())
+            .collect();
+        if !track.is_empty() {
+            round_trip(&Geometry::LineString(track));
+        }
+    }
+    let u = read_multipolygon_tracker_file(data_path("multipolygon_geofence.json")).unwrap();
+    round_trip(&Geometry::MultiPolygon(
+        MultiPolygonFence::from_coordinates(u.shape_coordinate).unwrap(),
+    ));
+    let u = read_circle_tracker_file(data_path("circle_geofence.json")).unwrap();
+    round_trip(&Geometry::Point(Coordinates::new(
+        u.shape_coordinate.lat,
+        u.shape_coordinate.lon,
+    )));
+}
+
+// Test case to write a polygon as WKT, longitude first and with closed rings, and to read it back
+// with a SRID and a Z coordinate
+#[test]
+fn test_wkt_axis_order() {
+    use geofencing::coordinates::Shape;
+    use geofencing::loader::*;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::wkt::*;
+
+    let u = read_tracker_file(data_path("polygon_geofence.json"), Shape::Polygon).unwrap();
+    let wkt = write_wkt(&Geometry::Polygon(
+        PolygonFence::new(u.shape_coordinate.clone()).unwrap(),
+    ));
+    assert!(wkt.starts_with("POLYGON ((3 -2, 4 4, "));
+    assert!(wkt.ends_with(", 3 -2))"));
+    match read_wkt("SRID=4326;polygon z ((3 -2 10, 4 4 10, -2 3 10, -1 -1 10, 3 -2 10))").unwrap() {
+        Geometry::Polygon(fence) => assert_eq!(fence.points(), &u.shape_coordinate[..]),
+        other => panic!("expected a polygon, found {:?}", other),
+    }
+}
+
+// Test case to read the EWKB hex handed out by PostGIS for SRID=4326;POINT(1 2), and to reject it
+// when it is cut short
+#[test]
+fn test_ewkb_hex() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::wkb::*;
+    use geofencing::wkt::*;
+
+    let (point, srid) =
+        read_ewkb(&from_hex("0101000020E6100000000000000000F03F0000000000000040").unwrap())
+            .unwrap();
+    assert_eq!(srid, Some(4326));
+    assert!(matches!(point, Geometry::Point(ref p) if *p == Coordinates::new(2.0, 1.0)));
+    assert!(read_wkb(&from_hex("0101000020E6100000000000000000F03F").unwrap()).is_err());
+}
+
+// Test case to reject multipolygons nested in each other instead of reading them without end
+#[test]
+fn test_wkb_nested() {
+    use geofencing::wkb::*;
+
+    let nested: Vec<u8> = (0..100_000)
+        .flat_map(|_| vec![1, 6, 0, 0, 0, 1, 0, 0, 0])
+        .collect();
+    assert!(matches!(
+        read_wkb(&nested),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+}
+
+// Test case to tell malformed WKT from WKT that describes an invalid polygon
+#[test]
+fn test_wkt_malformed() {
+    use geofencing::wkt::*;
+
+    assert!(matches!(
+        read_wkt("POLYGON ((1 1, 2 2))"),
+        Err(geofencing::error::GeofenceError::InvalidGeometry(_))
+    ));
+    assert!(matches!(
+        read_wkt("POLYGON ((1 1, 2 2, 3 1)"),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+    assert!(matches!(
+        read_wkt("CIRCLE (1 1)"),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+}
+
+// Test case to read fences and tracks from KML, with the placemark names as fence ids
+#[test]
+fn test_kml() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::kml::*;
+    use geofencing::loader::data_path;
+
+    let kml = read_kml_file(data_path("fences.kml")).unwrap();
+    let ids: Vec<&str> = kml.fences.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, vec!["depot", "warehouses", "office"]);
+    let depot = &kml.fences[0].1;
+    assert_eq!(
+        depot.locate(&Coordinates::new(12.1, 77.5)),
+        Location::Inside
+    );
+    // Inside the inner boundary.
+    assert_eq!(
+        depot.locate(&Coordinates::new(12.25, 77.5)),
+        Location::Outside
+    );
+    assert_eq!(kml.fences[1].1.shape().to_string(), "MultiPolygon");
+    assert!(kml.fences[1].1.contains(&Coordinates::new(13.1, 80.6)));
+    let office = &kml.fences[2].1;
+    assert!(office.contains(&Coordinates::new(12.974, 77.5946)));
+    assert!(!office.contains(&Coordinates::new(12.9716, 77.6)));
+
+    let (vehicle, track) = &kml.tracks[0];
+    assert_eq!(vehicle, "van");
+    assert_eq!((track[1].lat, track[1].lon), (12.25, 77.2));
+    assert_eq!(track[1].altitude, Some(920.0));
+}
+
+// Test case to reject KML with short coordinates, unclosed elements or an unknown model
+#[test]
+fn test_kml_malformed() {
+    use geofencing::kml::*;
+
+    assert!(matches!(
+        read_kml_str(
+            "<kml><Placemark><Point><coordinates>1</coordinates></Point></Placemark></kml>"
+        ),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+    assert!(read_kml_str("<kml><Placemark>").is_err());
+    // An unknown model is an error, just like an unknown unit.
+    assert!(matches!(
+        read_kml_str(
+            r#"<kml><Placemark><Point><coordinates>77.6,12.9</coordinates></Point>
+            <ExtendedData><Data name="radius"><value>500</value></Data>
+            <Data name="model"><value>elipsoid</value></Data></ExtendedData></Placemark></kml>"#
+        ),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+}
+
+// Test case to read the KML document from a zipped KMZ file
+#[cfg(feature = "kmz")]
+#[test]
+fn test_kmz() {
+    use geofencing::kml::*;
+    use geofencing::loader::data_path;
+
+    let kmz = read_kmz_file(data_path("fences.kmz")).unwrap();
+    let kml = read_kml_file(data_path("fences.kml")).unwrap();
+    assert_eq!(format!("{:?}", kmz), format!("{:?}", kml));
+    assert!(read_kmz_file(data_path("fences.kml")).is_err());
+}
+
+// Test case to parse RFC 3339 timestamps, with and without an offset
+#[test]
+fn test_parse_timestamp() {
+    use geofencing::coordinates::parse_timestamp;
+
+    assert_eq!(
+        parse_timestamp("2020-01-01T00:00:00Z").unwrap(),
+        1577836800.0
+    );
+    assert_eq!(
+        parse_timestamp("2020-05-01T15:45:30.5+05:30").unwrap(),
+        parse_timestamp("2020-05-01 10:15:30.5").unwrap()
+    );
+    assert_eq!(parse_timestamp("1969-12-31T23:00:00-01:00").unwrap(), 0.0);
+    assert!(parse_timestamp("2020-13-01T00:00:00Z").is_err());
+    assert!(parse_timestamp("yesterday").is_err());
+}
+
+// Test case to read the waypoints and the track fixes of a GPX file
+#[test]
+fn test_gpx() {
+    use geofencing::gpx::*;
+    use geofencing::loader::data_path;
+
+    let gpx = read_gpx_file(data_path("drive.gpx")).unwrap();
+    assert_eq!(gpx.waypoints.len(), 1);
+    assert_eq!(gpx.waypoints[0].0, "depot gate");
+    assert_eq!(gpx.waypoints[0].1.altitude, Some(12.0));
+    let (vehicle, fixes) = &gpx.tracks[0];
+    assert_eq!(vehicle, "car");
+    // Both track segments are joined in to one track.
+    assert_eq!(fixes.len(), 4);
+    assert_eq!((fixes[0].lat, fixes[0].lon), (-5.0, 0.0));
+    assert_eq!(fixes[0].altitude, Some(10.5));
+    assert_eq!(fixes[1].speed, Some(13.9));
+    assert_eq!(fixes[1].heading, Some(45.0));
+    assert_eq!(fixes[2].timestamp, Some(1588327320.0));
+    assert_eq!(fixes[3].timestamp, Some(1588327380.5));
+}
+
+// Test case to replay a GPX drive against the fence of a json file
+#[test]
+fn test_gpx_evaluations() {
+    use geofencing::coordinates::Shape;
+    use geofencing::geofencer::{execute, execute_gpx};
+    use geofencing::gpx::*;
+    use geofencing::loader::data_path;
+
+    let gpx = read_gpx_file(data_path("drive.gpx")).unwrap();
+    let fixes = &gpx.tracks[0].1;
+    let evaluations = execute_gpx(
+        Shape::Polygon,
+        "polygon_geofence.json",
+        "drive.gpx",
+        &geofencing::reporter::NoopReporter,
+    )
+    .unwrap();
+    let inside: Vec<bool> = evaluations
+        .iter()
+        .map(|result| result.is_inside())
+        .collect();
+    // The waypoint at the depot gate is evaluated after the track.
+    assert_eq!(inside, vec![false, true, true, false, true]);
+    assert_eq!(evaluations[4].vehicle, "depot gate");
+    assert_eq!(evaluations[4].position, gpx.waypoints[0].1);
+    assert_eq!(evaluations[0].vehicle, "car");
+    assert_eq!(evaluations[0].fence_id, "polygon_geofence.json");
+    assert_eq!(evaluations[3].position, fixes[3]);
+    assert!(execute(
+        Shape::Polygon,
+        "polygon_geofence.json",
+        &geofencing::reporter::NoopReporter
+    )
+    .is_ok());
+    assert!(execute_gpx(
+        Shape::Circle,
+        "circle_geofence.json",
+        "drive.gpx",
+        &geofencing::reporter::NoopReporter
+    )
+    .is_ok());
+}
+
+// Test case to reject a GPX track point without a longitude
+#[test]
+fn test_gpx_malformed() {
+    use geofencing::gpx::*;
+
+    assert!(matches!(
+        read_gpx_str("<gpx><trk><trkseg><trkpt lat=\"1\"/></trkseg></trk></gpx>"),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+}
+
+// Test case to parse RMC and GGA sentences, and to reject a wrong or missing checksum
+#[test]
+fn test_nmea() {
+    use geofencing::nmea::*;
+
+    let fix =
+        parse_sentence("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A")
+            .unwrap()
+            .unwrap();
+    assert!((fix.lat - 48.1173).abs() < 1e-9);
+    assert!((fix.lon - 11.516_666_666).abs() < 1e-9);
+    assert!((fix.speed.unwrap() - 11.523_545_6).abs() < 1e-6);
+    assert_eq!(fix.heading, Some(84.4));
+    assert_eq!(fix.timestamp, Some(764426119.0));
+    let fix = parse_sentence("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47")
+        .unwrap()
+        .unwrap();
+    assert_eq!(fix.altitude, Some(545.4));
+    assert_eq!(fix.timestamp, None);
+    assert!(parse_sentence("$GPGSV,1,1,00*79").unwrap().is_none());
+    assert!(parse_sentence("$GPRMC,123519,V,,,,,,,230394,,*33")
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        parse_sentence("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6B"),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+    // Sentences without a checksum are only accepted when asked for.
+    let sentence = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,";
+    assert!(parse_sentence(sentence).is_err());
+    assert!(NmeaParser::new()
+        .with_optional_checksum(true)
+        .parse(sentence)
+        .unwrap()
+        .is_some());
+}
+
+// Test case to convert NMEA positions, and to reject them beyond the range of a latitude or a
+// longitude, or with 60 minutes or more
+#[test]
+fn test_nmea_dms_to_decimal() {
+    use geofencing::nmea::*;
+
+    assert_eq!(dms_to_decimal("3345.000", "S").unwrap(), -33.75);
+    assert_eq!(dms_to_decimal("15112.600", "W").unwrap(), -151.21);
+    assert!(dms_to_decimal("9100.000", "N").is_err());
+    assert!(dms_to_decimal("18030.000", "E").is_err());
+    assert!(dms_to_decimal("4860.000", "N").is_err());
+    assert!(dms_to_decimal("17959.999", "W").is_ok());
+}
+
+// Test case to drive the tracker with a stream of NMEA sentences
+#[test]
+fn test_nmea_track() {
+    use geofencing::coordinates::Shape;
+    use geofencing::geofencer::read_fence;
+    use geofencing::loader::data_path;
+    use geofencing::nmea::*;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::time::Duration;
+
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(300));
+    tracker.add_fence(
+        "depot",
+        read_fence(Shape::Polygon, "polygon_geofence.json").unwrap(),
+    );
+    let file = File::open(data_path("drive.nmea")).unwrap();
+    let events = track_nmea(BufReader::new(file), "car", &mut tracker).unwrap();
+    let kinds: Vec<EventKind> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::Enter,
+            EventKind::Dwell(Duration::from_secs(360)),
+            EventKind::Exit
+        ]
+    );
+    // The exit is reported by a GGA sentence, timed with the date of the RMC sentences.
+    assert_eq!(events[2].position.timestamp, Some(1588327680.0));
+    assert_eq!(events[2].position.altitude, Some(12.5));
+}
+
+// Test case to date the GGA sentences of a NMEA stream that starts before the date is known,
+// and that goes past midnight
+#[test]
+fn test_nmea_gga_first() {
+    use geofencing::loader::data_path;
+    use geofencing::nmea::*;
+    use std::fs;
+
+    let text = fs::read_to_string(data_path("drive_gga_first.nmea")).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut parser = NmeaParser::new();
+    assert!(parser.parse_dated(lines[0]).unwrap().is_empty());
+    assert!(parser.parse_dated(lines[1]).unwrap().is_empty());
+    // The RMC sentence brings the date of 31 May 2020, for the GGA fixes held before it.
+    let times: Vec<Option<f64>> = parser
+        .parse_dated(lines[2])
+        .unwrap()
+        .iter()
+        .map(|fix| fix.timestamp)
+        .collect();
+    assert_eq!(
+        times,
+        vec![Some(1590969570.0), Some(1590969590.0), Some(1590969599.0)]
+    );
+    // After midnight the GGA fixes are on 1 June.
+    let fixes = parser.parse_dated(lines[3]).unwrap();
+    assert_eq!(fixes[0].timestamp, Some(1590969900.0));
+    assert!(parser.parse_dated(lines[4]).is_err());
+    assert!(parser.finish().is_empty());
+}
+
+// Test case to track a NMEA stream that starts with GGA sentences
+#[test]
+fn test_nmea_gga_first_track() {
+    use geofencing::coordinates::Shape;
+    use geofencing::geofencer::read_fence;
+    use geofencing::loader::data_path;
+    use geofencing::nmea::*;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::fs;
+    use std::io::BufReader;
+    use std::time::Duration;
+
+    let text = fs::read_to_string(data_path("drive_gga_first.nmea")).unwrap();
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(300));
+    tracker.add_fence(
+        "depot",
+        read_fence(Shape::Polygon, "polygon_geofence.json").unwrap(),
+    );
+    let events = track_nmea(BufReader::new(text.as_bytes()), "car", &mut tracker).unwrap();
+    let kinds: Vec<EventKind> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::Enter,
+            EventKind::Dwell(Duration::from_secs(301)),
+            EventKind::Exit
+        ]
+    );
+    // The sentence without a checksum at 00:05:30 is skipped, the exit is at 00:06:00.
+    assert_eq!(events[2].position.timestamp, Some(1590969960.0));
+}
+
+// Test case for a fix without a timestamp, which does not hold back the dated fixes that follow it
+#[test]
+fn test_nmea_undated_fix() {
+    use geofencing::coordinates::Shape;
+    use geofencing::geofencer::read_fence;
+    use geofencing::loader::data_path;
+    use geofencing::nmea::*;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::fs;
+
+    let text = fs::read_to_string(data_path("drive_gga_first.nmea")).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut tracker = Tracker::new();
+    tracker.add_fence(
+        "depot",
+        read_fence(Shape::Polygon, "polygon_geofence.json").unwrap(),
+    );
+    let undated = parse_sentence(lines[0]).unwrap().unwrap();
+    assert!(tracker.update_fix("car", &undated).is_empty());
+    let dated = NmeaParser::new().parse(lines[2]).unwrap().unwrap();
+    assert_eq!(tracker.update_fix("car", &dated)[0].kind, EventKind::Enter);
+}
+
+// Test case to read tracks from a CSV file with mapped column names
+#[test]
+fn test_csv_loader() {
+    use geofencing::csv_loader::*;
+    use geofencing::loader::data_path;
+
+    let columns: TrackColumns = serde_json::from_str(
+        r#"{"vehicle": "device_id", "timestamp": "ts", "lat": "latitude", "lon": "longitude"}"#,
+    )
+    .unwrap();
+    assert_eq!(columns.speed, "speed");
+    let tracks = read_tracks_file(data_path("telematics.csv"), &columns).unwrap();
+    let vehicles: Vec<&str> = tracks.iter().map(|(vehicle, _)| vehicle.as_str()).collect();
+    assert_eq!(vehicles, vec!["car", "van"]);
+    assert_eq!(tracks[0].1.len(), 4);
+    assert_eq!(tracks[0].1[1].timestamp, Some(1588327260.0));
+    assert_eq!(tracks[0].1[1].speed, Some(12.5));
+    assert_eq!(tracks[1].1[0].timestamp, Some(1588327200.0));
+    assert_eq!(tracks[1].1[0].speed, None);
+}
+
+// Test case to read fences from CSV files, with a WKT column or with a row per vertex
+#[test]
+fn test_csv_fences() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::csv_loader::*;
+    use geofencing::fence::Geofence;
+    use geofencing::loader::data_path;
+
+    let fences = read_fences_file(data_path("fences.csv"), &FenceColumns::default()).unwrap();
+    assert_eq!(fences[0].0, "depot");
+    assert_eq!(fences[1].1.shape().to_string(), "MultiPolygon");
+    let vertex_columns = FenceColumns {
+        delimiter: ';',
+        ..FenceColumns::default()
+    };
+    let vertices = read_fences_file(data_path("fence_vertices.csv"), &vertex_columns).unwrap();
+    assert_eq!(vertices.len(), 2);
+    assert_eq!(vertices[0].0, "depot");
+    assert_eq!(vertices[0].1.area(), fences[0].1.area());
+    // The vertex that closes the ring of the depot is not a duplicate vertex.
+    assert!(vertices[0].1.validate().is_empty());
+    assert!(vertices[1].1.contains(&Coordinates::new(11.0, 11.5)));
+}
+
+// Test case to stream the rows of a CSV file through the tracker one at a time
+#[test]
+fn test_csv_track() {
+    use geofencing::csv_loader::*;
+    use geofencing::loader::data_path;
+    use geofencing::tracker::{EventKind, Tracker};
+    use std::fs::File;
+    use std::time::Duration;
+
+    let columns: TrackColumns = serde_json::from_str(
+        r#"{"vehicle": "device_id", "timestamp": "ts", "lat": "latitude", "lon": "longitude"}"#,
+    )
+    .unwrap();
+    let fences = read_fences_file(data_path("fences.csv"), &FenceColumns::default()).unwrap();
+    let mut tracker = Tracker::new().with_dwell_threshold(Duration::from_secs(300));
+    tracker.add_fence("depot", fences[0].1.clone());
+    let file = File::open(data_path("telematics.csv")).unwrap();
+    let events: Vec<(String, EventKind)> = track_csv(file, &columns, &mut tracker)
+        .unwrap()
+        .into_iter()
+        .map(|event| (event.vehicle, event.kind))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            ("car".to_string(), EventKind::Enter),
+            ("van".to_string(), EventKind::Enter),
+            (
+                "car".to_string(),
+                EventKind::Dwell(Duration::from_secs(360))
+            ),
+            ("car".to_string(), EventKind::Exit),
+        ]
+    );
+}
+
+// Test case to evaluate the rows of a CSV file against the fence of a json file
+#[test]
+fn test_csv_evaluations() {
+    use geofencing::coordinates::Shape;
+    use geofencing::csv_loader::*;
+    use geofencing::geofencer::execute_csv;
+
+    let columns: TrackColumns = serde_json::from_str(
+        r#"{"vehicle": "device_id", "timestamp": "ts", "lat": "latitude", "lon": "longitude"}"#,
+    )
+    .unwrap();
+    let evaluations = execute_csv(
+        Shape::Polygon,
+        "polygon_geofence.json",
+        "telematics.csv",
+        &columns,
+        &geofencing::reporter::NoopReporter,
+    )
+    .unwrap();
+    assert_eq!(
+        evaluations
+            .iter()
+            .filter(|result| result.is_inside())
+            .count(),
+        3
+    );
+    assert_eq!(
+        evaluations
+            .iter()
+            .filter(|result| result.vehicle == "van")
+            .count(),
+        2
+    );
+    // The rows are evaluated in the order of the file.
+    let vehicles: Vec<&str> = evaluations
+        .iter()
+        .map(|result| result.vehicle.as_str())
+        .collect();
+    assert_eq!(vehicles, vec!["car", "van", "car", "van", "car", "car"]);
+}
+
+// Test case to reject CSV rows with a missing column or a bad value, with the line of the row
+#[test]
+fn test_csv_malformed() {
+    use geofencing::csv_loader::*;
+
+    assert!(matches!(
+        read_tracks(
+            "vehicle,lat\ncar,1.0\n".as_bytes(),
+            &TrackColumns::default()
+        ),
+        Err(geofencing::error::GeofenceError::Parse(_))
+    ));
+    match read_tracks(
+        "vehicle,lat,lon\ncar,1.0,2.0\ncar,north,2.0\n".as_bytes(),
+        &TrackColumns::default(),
+    ) {
+        Err(error) => assert!(error.to_string().contains("line 3")),
+        Ok(_) => panic!("expected an error for the bad latitude"),
+    }
+}
+
+// Test case to validate the structured evaluations returned by execute, and their json form
+#[test]
+fn test_execute_evaluations() {
+    use geofencing::coordinates::{Location, Shape};
+    use geofencing::evaluation::Evaluation;
+    use geofencing::geofencer::execute;
+
+    let evaluations: Vec<Evaluation> = execute(
+        Shape::Polygon,
+        "polygon_geofence_holes.json",
+        &geofencing::reporter::NoopReporter,
+    )
+    .unwrap();
+    let locations: Vec<Location> = evaluations.iter().map(|result| result.location).collect();
+    assert_eq!(
+        locations,
+        vec![
+            Location::Outside,
+            Location::Inside,
+            Location::Outside,
+            Location::Inside,
+            Location::Outside
+        ]
+    );
+    assert!(evaluations
+        .iter()
+        .all(|result| result.fence_id == "polygon_geofence_holes.json"));
+    // Positions inside the fence are a negative distance from its border, positions in the
+    // hole a positive distance.
+    assert_eq!(evaluations[1].distance, -2.0);
+    assert_eq!(evaluations[2].distance, 1.0);
+    assert_eq!(
+        evaluations[1].to_string(),
+        format!(
+            "The {} positioned at latitude 2, longitude 5, is inside the fence",
+            evaluations[1].vehicle
+        )
+    );
+
+    let json = serde_json::to_value(&evaluations).unwrap();
+    assert_eq!(json[2]["location"], "outside");
+    assert_eq!(json[2]["position"]["lat"], evaluations[2].position.lat);
+    assert!(json[2]["position"].get("speed").is_none());
+    assert_eq!(json[3]["location"], "inside");
+}
+
+// Test case to validate that the execute and contains functions print only through the reporter
+#[test]
+fn test_reporter() {
+    use geofencing::coordinates::Shape;
+    use geofencing::geofencer::{contains, execute};
+    use geofencing::reporter::{Colour, Reporter};
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct RecordingReporter {
+        lines: RefCell<Vec<String>>,
+    }
+
+    impl Reporter for RecordingReporter {
+        fn display(&self, text: &str, _colour: Colour) {
+            self.lines.borrow_mut().push(text.to_string());
+        }
+        fn display_bold(&self, text: &str, colour: Colour) {
+            self.display(text, colour);
+        }
+        fn display_underline(&self, text: &str, colour: Colour) {
+            self.display(text, colour);
+        }
+    }
+
+    let reporter = RecordingReporter::default();
+    let evaluations = execute(Shape::Polygon, "polygon_geofence_holes.json", &reporter).unwrap();
+    let lines = reporter.lines.borrow();
+    assert_eq!(
+        lines[0],
+        "Created Polygon Fence, with the below coordinates"
+    );
+    // The fence, the heading of the evaluations and a line per evaluation.
+    assert_eq!(lines.len(), 3 + evaluations.len());
+    assert_eq!(
+        lines.last().unwrap(),
+        &evaluations.last().unwrap().to_string()
+    );
+    drop(lines);
+
+    reporter.lines.borrow_mut().clear();
+    assert!(contains(
+        Shape::Polygon,
+        "polygon_geofence_holes.json",
+        2.0,
+        5.0,
+        &reporter
+    )
+    .unwrap());
+    assert!(reporter
+        .lines
+        .borrow()
+        .last()
+        .unwrap()
+        .contains("is inside the fence"));
+}
+
+// Test case to validate the delay between the lines of the reporters
+#[test]
+fn test_reporter_delay() {
+    use geofencing::reporter::{NoopReporter, PlainReporter, Reporter};
+    use std::time::Duration;
+
+    assert_eq!(NoopReporter.delay(), None);
+    let plain = PlainReporter::new().with_delay(Duration::from_millis(1));
+    assert_eq!(plain.delay(), Some(Duration::from_millis(1)));
+}
+
+// Test case to check a position with the geofence command, with the evaluations as json
+#[test]
+fn test_geofence_command_check() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = geofence(&[
+        "check",
+        "data/polygon_geofence_holes.json",
+        "2",
+        "5",
+        "--shape",
+        "polygon",
+        "--format",
+        "json",
+    ]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["location"], "inside");
+    assert_eq!(json[0]["fence_id"], "polygon_geofence_holes");
+}
+
+// Test case to track a GPX drive with the geofence command, with the evaluations as CSV
+#[test]
+fn test_geofence_command_track() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = geofence(&[
+        "track",
+        "data/polygon_geofence.json",
+        "data/drive.gpx",
+        "--shape",
+        "polygon",
+        "--format",
+        "csv",
+    ]);
+    assert!(output.status.success());
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        csv.lines().next(),
+        Some("vehicle,fence_id,lat,lon,timestamp,location,distance")
+    );
+    assert!(csv
+        .lines()
+        .any(|line| line.starts_with("car,polygon_geofence,") && line.contains(",inside,")));
+}
+
+// Test case to validate the fences of a KML file with the geofence command
+#[test]
+fn test_geofence_command_validate() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = geofence(&["validate", "data/fences.kml"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("office: valid Circle fence"));
+}
+
+// Test case to convert the fences of a CSV file to GeoJSON with the geofence command
+#[test]
+fn test_geofence_command_convert() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = geofence(&["convert", "data/fences.csv", "--to", "geojson"]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["type"], "FeatureCollection");
+}
+
+// Test case to validate that errors reading the files exit with 1, and wrong arguments with 2
+#[test]
+fn test_geofence_command_exit_codes() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    assert_eq!(
+        geofence(&["validate", "data/missing.kml"]).status.code(),
+        Some(1)
+    );
+    assert_eq!(
+        geofence(&["check", "data/fences.kml", "north", "5"])
+            .status
+            .code(),
+        Some(2)
+    );
+    assert_eq!(
+        geofence(&[
+            "check",
+            "data/circle_geofence.json",
+            "1",
+            "1",
+            "--shape",
+            "polygon"
+        ])
+        .status
+        .code(),
+        Some(1)
+    );
+    assert_eq!(geofence(&["fly"]).status.code(), Some(2));
+}
+
+// Test case to validate that a command that fails on its arguments leaves the output file as it was
+#[test]
+fn test_geofence_command_output() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = std::env::temp_dir().join(format!("geofence-output-{}.txt", std::process::id()));
+    std::fs::write(&output, "earlier results").unwrap();
+    let path = output.to_str().unwrap();
+    assert_eq!(
+        geofence(&[
+            "chek",
+            "data/polygon_geofence.json",
+            "1",
+            "1",
+            "--output",
+            path
+        ])
+        .status
+        .code(),
+        Some(2)
+    );
+    assert_eq!(
+        geofence(&[
+            "check",
+            "data/polygon_geofence.json",
+            "north",
+            "1",
+            "--output",
+            path
+        ])
+        .status
+        .code(),
+        Some(2)
+    );
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "earlier results");
+    assert!(geofence(&[
+        "check",
+        "data/polygon_geofence.json",
+        "1",
+        "1",
+        "--output",
+        path
+    ])
+    .status
+    .success());
+    assert!(std::fs::read_to_string(&output).unwrap().contains("inside"));
+    std::fs::remove_file(&output).unwrap();
+}
+
+// Test case to validate that the shape of the fence is taken from the json file
+#[test]
+fn test_detect_shape() {
+    use geofencing::coordinates::Shape;
+    use geofencing::geofencer::{contains, detect_shape, execute, read_fence};
+    use geofencing::reporter::NoopReporter;
+
+    assert!(matches!(
+        detect_shape("circle_geofence.json"),
+        Ok(Shape::Circle)
+    ));
+    assert!(matches!(
+        detect_shape("multipolygon_geofence.json"),
+        Ok(Shape::MultiPolygon)
+    ));
+    assert!(contains(None, "triangle_geofence.json", 3.0, 3.0, &NoopReporter).unwrap());
+    assert!(!contains(None, "polygon_geofence_holes.json", 5.0, 5.0, &NoopReporter).unwrap());
+    assert_eq!(
+        execute(None, "circle_moving_tracker1.json", &NoopReporter).unwrap(),
+        execute(Shape::Circle, "circle_moving_tracker1.json", &NoopReporter).unwrap()
+    );
+    assert!(matches!(
+        read_fence(None, "triangle_geofence_2.json")
+            .unwrap()
+            .shape(),
+        Shape::Triangle
+    ));
+}
+
+// Test case to validate that the shape field is the tag of the enum, whatever its case
+#[test]
+fn test_fence_file_shape() {
+    use geofencing::coordinates::{Coordinates, Shape};
+    use geofencing::error::GeofenceError;
+    use geofencing::fence::Geofence;
+    use geofencing::loader::{read_fence_json, FenceFile};
+
+    let json = r#"{"_comment": "", "vehicle": "van", "shape": "circle",
+        "shape_coordinate": {"lat": 1.0, "lon": 1.0, "rad": 2.0},
+        "moving_coordinate": [{"lat": 1.5, "lon": 1.0}]}"#;
+    let file = read_fence_json(json.as_bytes(), None).unwrap();
+    assert!(matches!(file.shape(), Shape::Circle));
+    assert_eq!(file.vehicle(), "van");
+    assert_eq!(file.moving_coordinate().len(), 1);
+    match &file {
+        FenceFile::Circle(u) => assert_eq!(u.shape, "Circle"),
+        other => panic!("expected a circle, got {:?}", other),
+    }
+    assert!(file
+        .to_fence()
+        .unwrap()
+        .contains(&Coordinates::new(1.5, 1.0)));
+
+    match read_fence_json(json.as_bytes(), Some(Shape::Polygon)) {
+        Err(GeofenceError::ShapeMismatch { found, .. }) => assert_eq!(found, "circle"),
+        other => panic!("expected a shape mismatch, got {:?}", other),
+    }
+}
+
+// Test case to reject fence files with the wrong coordinates for their shape, an unknown shape or
+// no shape
+#[test]
+fn test_fence_file_malformed() {
+    use geofencing::error::GeofenceError;
+    use geofencing::loader::read_fence_json;
+
+    // The coordinates of a polygon in a file that says it is a circle.
+    let malformed = r#"{"_comment": "", "vehicle": "van", "shape": "Circle",
+        "shape_coordinate": [{"lat": 1.0, "lon": 1.0}], "moving_coordinate": []}"#;
+    match read_fence_json(malformed.as_bytes(), None) {
+        Err(GeofenceError::Parse(reason)) => assert!(reason.contains("Circle fence file")),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+    let unknown = r#"{"shape": "Hexagon", "shape_coordinate": []}"#;
+    match read_fence_json(unknown.as_bytes(), None) {
+        Err(GeofenceError::Parse(reason)) => assert!(reason.contains("Hexagon")),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+    assert!(matches!(
+        read_fence_json(r#"{"vehicle": "van"}"#.as_bytes(), None),
+        Err(GeofenceError::Parse(_))
+    ));
+}
+
+// Test case to validate the crossings of a fence between two consecutive fixes
+#[test]
+fn test_segment_crossings() {
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::{intersects, segment_crossings, CrossingKind};
+    use geofencing::polygon::PolygonFence;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let square = PolygonFence::new(vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ])
+    .unwrap();
+
+    // Both fixes are outside the square, but the segment cuts through its corner.
+    let from = Fix::new(8.0, -1.0).with_timestamp(0.0);
+    let to = Fix::new(11.0, 2.0).with_timestamp(60.0);
+    let crossings = segment_crossings(&square, &from, &to);
+    assert_eq!(crossings.len(), 2);
+    assert_eq!(crossings[0].kind, CrossingKind::Enter);
+    assert!(close(crossings[0].point.lat, 9.0) && close(crossings[0].point.lon, 0.0));
+    assert!(close(crossings[0].timestamp.unwrap(), 20.0));
+    assert_eq!(crossings[1].kind, CrossingKind::Exit);
+    assert!(close(crossings[1].point.lat, 10.0) && close(crossings[1].point.lon, 1.0));
+    assert!(close(crossings[1].timestamp.unwrap(), 40.0));
+    assert!(intersects(&square, &from, &to));
+    assert!(!intersects(
+        &square,
+        &Fix::new(12.0, -1.0),
+        &Fix::new(12.0, 11.0)
+    ));
+    assert!(segment_crossings(&square, &Fix::new(1.0, 1.0), &Fix::new(2.0, 2.0)).is_empty());
+    assert!(intersects(
+        &square,
+        &Fix::new(1.0, 1.0),
+        &Fix::new(2.0, 2.0)
+    ));
+}
+
+// Test case to validate the crossings of a fence along a track, with and without timestamps
+#[test]
+fn test_track_crossings() {
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::{track_crossings, CrossingKind};
+    use geofencing::polygon::PolygonFence;
+
+    let square = PolygonFence::new(vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ])
+    .unwrap();
+    let from = Fix::new(8.0, -1.0).with_timestamp(0.0);
+    let to = Fix::new(11.0, 2.0).with_timestamp(60.0);
+    let track = [
+        from.clone(),
+        to.clone(),
+        Fix::new(5.0, 5.0),
+        Fix::new(5.0, 20.0),
+    ];
+    let kinds: Vec<CrossingKind> = track_crossings(&square, &track)
+        .iter()
+        .map(|crossing| crossing.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            CrossingKind::Enter,
+            CrossingKind::Exit,
+            CrossingKind::Enter,
+            CrossingKind::Exit
+        ]
+    );
+    // Fixes without a timestamp give crossings without a time.
+    assert_eq!(track_crossings(&square, &track)[2].timestamp, None);
+}
+
+// Test case for a segment that starts on the border, which enters the fence when it moves in to it,
+// and exits the fence when it moves out of it
+#[test]
+fn test_segment_crossings_from_border() {
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::{segment_crossings, track_crossings, CrossingKind};
+    use geofencing::polygon::PolygonFence;
+
+    let square = PolygonFence::new(vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ])
+    .unwrap();
+    let crossings = segment_crossings(&square, &Fix::new(0.0, 5.0), &Fix::new(2.0, 5.0));
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Enter);
+    assert_eq!(crossings[0].fraction, 0.0);
+    let crossings = segment_crossings(&square, &Fix::new(0.0, 5.0), &Fix::new(-2.0, 5.0));
+    assert_eq!(crossings[0].kind, CrossingKind::Exit);
+    // Along a track the fix on the border was reached from outside, which was the Enter.
+    let kinds: Vec<CrossingKind> = track_crossings(
+        &square,
+        &[Fix::new(-2.0, 5.0), Fix::new(0.0, 5.0), Fix::new(2.0, 5.0)],
+    )
+    .iter()
+    .map(|crossing| crossing.kind)
+    .collect();
+    assert_eq!(kinds, vec![CrossingKind::Enter]);
+}
+
+// Test case for segments between fixes either side of the antimeridian, which go the shorter way
+// across 180
+#[test]
+fn test_segment_crossings_antimeridian() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::{segment_crossings, CrossingKind};
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{self, Model, Unit};
+    use geofencing::polygon::PolygonFence;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let dateline = PolygonFence::new(vec![
+        Coordinates::new(-10.0, 170.0),
+        Coordinates::new(10.0, 170.0),
+        Coordinates::new(10.0, 179.5),
+        Coordinates::new(-10.0, 179.5),
+    ])
+    .unwrap();
+    let crossings = segment_crossings(&dateline, &Fix::new(0.0, 179.0), &Fix::new(0.0, -179.0));
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Exit);
+    assert!(close(crossings[0].fraction, 0.25));
+    assert!(close(crossings[0].point.lon, 179.5));
+    // And back in from the other side of 180.
+    let crossings = segment_crossings(&dateline, &Fix::new(0.0, -179.0), &Fix::new(0.0, 179.0));
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Enter);
+    assert!(close(crossings[0].fraction, 0.75));
+    // A circle in meters on the antimeridian, which the segment goes straight through.
+    let center = Coordinates::new(0.0, 179.999);
+    let buoy = CircleFence::geodesic(&center, 1000.0, Unit::Meters, Model::Sphere).unwrap();
+    let (west, east) = (
+        Coordinates::new(0.0, 179.98),
+        Coordinates::new(0.0, -179.98),
+    );
+    assert_eq!(buoy.segment_intersections(&west, &east).len(), 2);
+    let crossings = segment_crossings(&buoy, &Fix::new(0.0, 179.98), &Fix::new(0.0, -179.98));
+    let kinds: Vec<CrossingKind> = crossings.iter().map(|crossing| crossing.kind).collect();
+    assert_eq!(kinds, vec![CrossingKind::Enter, CrossingKind::Exit]);
+    assert!((crossings[0].fraction - 0.25).abs() < 0.001);
+    assert!((crossings[1].fraction - 0.7).abs() < 0.001);
+    for crossing in &crossings {
+        let distance = geodesy::distance(&center, &crossing.point, Model::Sphere);
+        assert!((distance - 1000.0).abs() < 1.0, "{}", distance);
+    }
+}
+
+// Test case to validate the crossings of a triangle and of a circle in degrees
+#[test]
+fn test_segment_crossings_triangle_and_circle() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::segment_crossings;
+    use geofencing::triangle::TriangleFence;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let triangle = TriangleFence::new(
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(0.0, 10.0),
+        Coordinates::new(10.0, 0.0),
+    );
+    let crossings = segment_crossings(&triangle, &Fix::new(5.0, -1.0), &Fix::new(5.0, 11.0));
+    assert_eq!(crossings.len(), 2);
+    assert!(close(crossings[0].fraction, 1.0 / 12.0));
+    assert!(close(crossings[1].point.lon, 5.0));
+
+    let circle = CircleFence::from_center(&Coordinates::new(0.0, 0.0), 1.0).unwrap();
+    let crossings = segment_crossings(&circle, &Fix::new(-2.0, 0.5), &Fix::new(2.0, 0.5));
+    assert_eq!(crossings.len(), 2);
+    assert!(close(crossings[0].point.lat, -(0.75_f64.sqrt())));
+    assert!(close(crossings[1].point.lat, 0.75_f64.sqrt()));
+}
+
+// Test case to validate that the crossings of a circle in meters are on the circle, up to the
+// projection
+#[test]
+fn test_segment_crossings_geodesic_circle() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::segment_crossings;
+    use geofencing::geodesy::{self, Model, Unit};
+
+    let center = Coordinates::new(45.0, -122.0);
+    let depot = CircleFence::geodesic(&center, 500.0, Unit::Meters, Model::Sphere).unwrap();
+    let crossings = segment_crossings(&depot, &Fix::new(45.0, -122.01), &Fix::new(45.001, -121.99));
+    assert_eq!(crossings.len(), 2);
+    for crossing in &crossings {
+        let distance = geodesy::distance(&center, &crossing.point, Model::Sphere);
+        assert!((distance - 500.0).abs() < 1.0, "{}", distance);
+    }
+}
+
+// Test case to find the fences of the tracker that a segment crosses
+#[test]
+fn test_tracker_segment_crossings() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::geodesy::{Model, Unit};
+    use geofencing::polygon::PolygonFence;
+    use geofencing::tracker::Tracker;
+
+    let square = PolygonFence::new(vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ])
+    .unwrap();
+    let from = Fix::new(8.0, -1.0).with_timestamp(0.0);
+    let to = Fix::new(11.0, 2.0).with_timestamp(60.0);
+    let center = Coordinates::new(45.0, -122.0);
+    let depot = CircleFence::geodesic(&center, 500.0, Unit::Meters, Model::Sphere).unwrap();
+    let mut tracker = Tracker::new();
+    tracker.add_fence("square", square);
+    tracker.add_fence("depot", depot);
+    let intersected = tracker.segment_crossings(&from, &to);
+    assert_eq!(intersected.len(), 1);
+    assert_eq!(intersected[0].0, "square");
+    assert_eq!(intersected[0].1.len(), 2);
+}
+
+// Test case to validate the signed distance to the border of the fences and the nearest point
+#[test]
+fn test_boundary_distance() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::multipolygon::MultiPolygonFence;
+    use geofencing::polygon::PolygonFence;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let square = PolygonFence::with_holes(
+        vec![
+            Coordinates::new(0.0, 0.0),
+            Coordinates::new(10.0, 0.0),
+            Coordinates::new(10.0, 10.0),
+            Coordinates::new(0.0, 10.0),
+        ],
+        vec![vec![
+            Coordinates::new(4.0, 4.0),
+            Coordinates::new(6.0, 4.0),
+            Coordinates::new(6.0, 6.0),
+            Coordinates::new(4.0, 6.0),
+        ]],
+    )
+    .unwrap();
+    let inside = square.boundary_distance(&Coordinates::new(1.0, 2.0));
+    assert!(close(inside.distance, -1.0));
+    assert_eq!(inside.nearest, Coordinates::new(0.0, 2.0));
+    let outside = square.boundary_distance(&Coordinates::new(13.0, 14.0));
+    assert!(close(outside.distance, 5.0));
+    assert_eq!(outside.nearest, Coordinates::new(10.0, 10.0));
+    // In the hole the point is outside the fence, and the border of the hole is the nearest.
+    let hole = square.boundary_distance(&Coordinates::new(5.0, 4.5));
+    assert!(close(hole.distance, 0.5));
+    assert_eq!(hole.nearest, Coordinates::new(5.0, 4.0));
+    assert_eq!(
+        square
+            .boundary_distance(&Coordinates::new(0.0, 3.0))
+            .distance
+            .abs(),
+        0.0
+    );
+
+    let multipolygon = MultiPolygonFence::new(vec![square.clone()]).unwrap();
+    assert_eq!(
+        multipolygon.boundary_distance(&Coordinates::new(13.0, 14.0)),
+        outside
+    );
+}
+
+// Test case to validate the signed distance to the border of a triangle and of a circle
+#[test]
+fn test_boundary_distance_triangle_and_circle() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::triangle::TriangleFence;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let triangle = TriangleFence::new(
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(0.0, 10.0),
+        Coordinates::new(10.0, 0.0),
+    );
+    let inside = triangle.boundary_distance(&Coordinates::new(1.0, 5.0));
+    assert!(close(inside.distance, -1.0));
+    assert!(close(inside.nearest.lat, 0.0) && close(inside.nearest.lon, 5.0));
+
+    let circle = CircleFence::from_center(&Coordinates::new(0.0, 0.0), 5.0).unwrap();
+    let outside = circle.boundary_distance(&Coordinates::new(6.0, 8.0));
+    assert!(close(outside.distance, 5.0));
+    assert!(close(outside.nearest.lat, 3.0) && close(outside.nearest.lon, 4.0));
+    assert!(close(
+        circle
+            .boundary_distance(&Coordinates::new(0.0, 0.0))
+            .distance,
+        -5.0
+    ));
+}
+
+// Test case to validate the signed distance to the border of geographic fences, in meters
+#[test]
+fn test_boundary_distance_meters() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{Model, Unit};
+    use geofencing::polygon::PolygonFence;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let center = Coordinates::new(45.0, -122.0);
+    let depot = CircleFence::geodesic(&center, 500.0, Unit::Meters, Model::Sphere).unwrap();
+    let truck = Coordinates::new(45.0, -121.99);
+    let boundary = depot.boundary_distance(&truck);
+    let meters = depot.boundary_distance_meters(&truck, Model::Sphere);
+    assert!(boundary.distance > 280.0 && boundary.distance < 290.0);
+    assert!((meters.distance - boundary.distance).abs() < 1.0);
+    assert!(close(boundary.nearest.lat, 45.0));
+    assert!(depot.boundary_distance(&center).distance == -500.0);
+
+    let yard = PolygonFence::new(vec![
+        Coordinates::new(45.0, -122.0),
+        Coordinates::new(45.01, -122.0),
+        Coordinates::new(45.01, -121.99),
+        Coordinates::new(45.0, -121.99),
+    ])
+    .unwrap();
+    // A thousandth of a degree of latitude is about 111 meters.
+    let meters = yard.boundary_distance_meters(&Coordinates::new(44.999, -121.995), Model::Sphere);
+    assert!((meters.distance - 111.2).abs() < 0.5, "{}", meters.distance);
+    let meters = yard.boundary_distance_meters(&Coordinates::new(45.001, -121.995), Model::Sphere);
+    assert!((meters.distance + 111.2).abs() < 0.5, "{}", meters.distance);
+}
+
+// Test case to validate the signed distance to the border of the fence of a json file
+#[test]
+fn test_boundary_distance_file() {
+    use geofencing::geofencer::boundary_distance;
+
+    let close = |value: f64, expected: f64| (value - expected).abs() < 1e-9;
+    let from_file = boundary_distance(None, "polygon_geofence_holes.json", 2.0, 5.0).unwrap();
+    assert!(close(from_file.distance, -2.0));
+}
+
+// Test case to take the fill rule of a multipolygon from its polygons
+#[test]
+fn test_multipolygon_fill_rule() {
+    use geofencing::coordinates::{Coordinates, FillRule};
+    use geofencing::error::GeofenceError;
+    use geofencing::fence::Geofence;
+    use geofencing::multipolygon::MultiPolygonFence;
+    use geofencing::polygon::PolygonFence;
+
+    // The hole winds the same way as the outer ring, so it is only cut out with EvenOdd.
+    let square = PolygonFence::with_holes(
+        vec![
+            Coordinates::new(0.0, 0.0),
+            Coordinates::new(10.0, 0.0),
+            Coordinates::new(10.0, 10.0),
+            Coordinates::new(0.0, 10.0),
+        ],
+        vec![vec![
+            Coordinates::new(4.0, 4.0),
+            Coordinates::new(6.0, 4.0),
+            Coordinates::new(6.0, 6.0),
+            Coordinates::new(4.0, 6.0),
+        ]],
+    )
+    .unwrap();
+    let far = PolygonFence::new(vec![
+        Coordinates::new(20.0, 20.0),
+        Coordinates::new(21.0, 20.0),
+        Coordinates::new(21.0, 21.0),
+    ])
+    .unwrap();
+    let hole = Coordinates::new(5.0, 5.0);
+
+    let non_zero = MultiPolygonFence::new(vec![
+        square.clone().with_fill_rule(FillRule::NonZero),
+        far.clone().with_fill_rule(FillRule::NonZero),
+    ])
+    .unwrap();
+    assert_eq!(non_zero.fill_rule(), FillRule::NonZero);
+    assert!(non_zero.contains(&hole));
+
+    let even_odd = non_zero.with_fill_rule(FillRule::EvenOdd);
+    assert!(even_odd
+        .polygons()
+        .iter()
+        .all(|polygon| polygon.fill_rule() == FillRule::EvenOdd));
+    assert!(!even_odd.contains(&hole));
+
+    let mixed = MultiPolygonFence::new(vec![square.with_fill_rule(FillRule::NonZero), far]);
+    assert!(matches!(mixed, Err(GeofenceError::InvalidGeometry(_))));
+}
+
+// Test case to validate the polygons of a multipolygon that overlap each other
+#[test]
+fn test_multipolygon_overlap() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::multipolygon::MultiPolygonFence;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::validity::Defect;
+
+    let square = |lat: f64, lon: f64, side: f64| {
+        vec![
+            Coordinates::new(lat, lon),
+            Coordinates::new(lat + side, lon),
+            Coordinates::new(lat + side, lon + side),
+            Coordinates::new(lat, lon + side),
+        ]
+    };
+    let polygon = |ring| PolygonFence::new(ring).unwrap();
+
+    // With the even odd fill rule the area the squares share is not in the fence.
+    let overlapping = MultiPolygonFence::new(vec![
+        polygon(square(0.0, 0.0, 4.0)),
+        polygon(square(2.0, 2.0, 4.0)),
+    ])
+    .unwrap();
+    assert!(!overlapping.contains(&Coordinates::new(3.0, 3.0)));
+    let defects = overlapping.validate();
+    assert_eq!(defects, vec![Defect::PolygonOverlap { ring: 1, other: 0 }]);
+    assert_eq!(
+        defects[0].to_string(),
+        "ring 1: the polygon overlaps the polygon of ring 0"
+    );
+    // A polygon inside another one, without crossing it.
+    let inside = MultiPolygonFence::new(vec![
+        polygon(square(0.0, 0.0, 4.0)),
+        polygon(square(1.0, 1.0, 1.0)),
+    ])
+    .unwrap();
+    assert_eq!(
+        inside.validate(),
+        vec![Defect::PolygonOverlap { ring: 1, other: 0 }]
+    );
+}
+
+// Test case for squares that share an edge, and an island in the hole of a lake, which only touch
+#[test]
+fn test_multipolygon_touching() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::multipolygon::MultiPolygonFence;
+    use geofencing::polygon::PolygonFence;
+
+    let square = |lat: f64, lon: f64, side: f64| {
+        vec![
+            Coordinates::new(lat, lon),
+            Coordinates::new(lat + side, lon),
+            Coordinates::new(lat + side, lon + side),
+            Coordinates::new(lat, lon + side),
+        ]
+    };
+    let polygon = |ring| PolygonFence::new(ring).unwrap();
+
+    let neighbours = MultiPolygonFence::new(vec![
+        polygon(square(0.0, 0.0, 2.0)),
+        polygon(square(0.0, 2.0, 2.0)),
+    ])
+    .unwrap();
+    assert!(neighbours.validate().is_empty());
+    let mut lake = square(4.0, 4.0, 2.0);
+    lake.reverse();
+    let island = MultiPolygonFence::new(vec![
+        PolygonFence::with_holes(square(0.0, 0.0, 10.0), vec![lake]).unwrap(),
+        polygon(square(4.5, 4.5, 1.0)),
+    ])
+    .unwrap();
+    assert!(island.validate().is_empty());
+    assert!(island.contains(&Coordinates::new(5.0, 5.0)));
+}
+
+// Test case to find the nearest point of the border of a geodesic circle on the antimeridian
+#[test]
+fn test_boundary_distance_antimeridian() {
+    use geofencing::circle::CircleFence;
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy::{Model, Unit};
+
+    let circle = CircleFence::geodesic(
+        &Coordinates::new(0.0, 179.999),
+        1000.0,
+        Unit::Meters,
+        Model::Sphere,
+    )
+    .unwrap();
+    // The point is about 222 meters east of the center, across 180, so the nearest point of the
+    // border is east of it too.
+    let boundary = circle.boundary_distance(&Coordinates::new(0.0, -179.999));
+    assert!((boundary.distance + 777.6).abs() < 0.1);
+    assert!(boundary.nearest.lat.abs() < 1e-9);
+    assert!((boundary.nearest.lon + 179.992).abs() < 0.0001);
+}
+
+// Test case to validate the defects of polygon fences and their repair
+#[test]
+fn test_polygon_validity() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::{Fence, Geofence};
+    use geofencing::loader::read_fence_json_file;
+    use geofencing::validity::{Defect, Validation};
+
+    let file = read_fence_json_file("data/polygon_geofence_bowtie.json", None).unwrap();
+    let fence = file.to_fence().unwrap();
+    assert_eq!(
+        fence.validate(),
+        vec![
+            Defect::ClosedRing { ring: 0 },
+            Defect::DuplicateVertex {
+                ring: 0,
+                vertex: 4,
+                repeats: 3
+            },
+            Defect::SelfIntersection {
+                ring: 0,
+                edge1: 0,
+                edge2: 2,
+                point: Coordinates::new(2.0, 2.0)
+            },
+        ]
+    );
+    assert!(file.to_fence_with(Validation::Reject).is_err());
+
+    // The bow-tie is split in to its two triangles.
+    let repaired = file.to_fence_with(Validation::Repair).unwrap();
+    match &repaired {
+        Fence::MultiPolygon(multipolygon) => assert_eq!(multipolygon.polygons().len(), 2),
+        other => panic!("expected a multipolygon, found {:?}", other),
+    }
+    assert!(repaired.validate().is_empty());
+    assert_eq!(repaired.area(), 8.0);
+    for (lat, lon) in &[(3.0, 2.0), (1.0, 2.0), (2.0, 3.0), (5.0, 2.0)] {
+        let point = Coordinates::new(*lat, *lon);
+        assert_eq!(repaired.contains(&point), fence.contains(&point));
+    }
+}
+
+// Test case for a hole that winds the same way as the outer ring, and a hole outside of the fence
+#[test]
+fn test_polygon_validity_holes() {
+    use geofencing::coordinates::{Coordinates, FillRule};
+    use geofencing::fence::Geofence;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::validity::Defect;
+
+    let square = vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ];
+    let hole = vec![
+        Coordinates::new(4.0, 4.0),
+        Coordinates::new(6.0, 4.0),
+        Coordinates::new(6.0, 6.0),
+        Coordinates::new(4.0, 6.0),
+    ];
+    let outside = vec![
+        Coordinates::new(20.0, 20.0),
+        Coordinates::new(21.0, 20.0),
+        Coordinates::new(21.0, 21.0),
+    ];
+    let polygon = PolygonFence::with_holes(square.clone(), vec![hole, outside])
+        .unwrap()
+        .with_fill_rule(FillRule::NonZero);
+    assert_eq!(
+        polygon.validate(),
+        vec![
+            Defect::HoleOrientation { ring: 1 },
+            Defect::HoleOutside { ring: 2, vertex: 0 },
+            Defect::HoleOrientation { ring: 2 },
+        ]
+    );
+    assert!(polygon.contains(&Coordinates::new(5.0, 5.0)));
+    let repaired = polygon.repair().unwrap();
+    assert!(repaired.validate().is_empty());
+    assert_eq!(repaired.polygons()[0].holes().len(), 1);
+    assert!(!repaired.contains(&Coordinates::new(5.0, 5.0)));
+    assert!(PolygonFence::new(square.clone())
+        .unwrap()
+        .validate()
+        .is_empty());
+}
+
+// Test case for a hole with all its vertices inside the fence whose edge goes out through the notch
+// of the outer ring
+#[test]
+fn test_polygon_validity_ring_intersection() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::validity::Defect;
+
+    let notched = vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(0.0, 10.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(10.0, 6.0),
+        Coordinates::new(5.0, 5.0),
+        Coordinates::new(10.0, 4.0),
+        Coordinates::new(10.0, 0.0),
+    ];
+    let across = vec![
+        Coordinates::new(7.0, 2.0),
+        Coordinates::new(7.0, 8.0),
+        Coordinates::new(8.0, 8.0),
+        Coordinates::new(8.0, 2.0),
+    ];
+    let polygon = PolygonFence::with_holes(notched, vec![across]).unwrap();
+    let defects = polygon.validate();
+    // Both long edges of the hole cross both sides of the notch.
+    assert_eq!(defects.len(), 4);
+    assert!(matches!(
+        defects[0],
+        Defect::RingIntersection {
+            ring: 1,
+            edge: 0,
+            other: 0,
+            other_edge: 3,
+            ..
+        }
+    ));
+    assert_eq!(
+        defects[0].to_string(),
+        "ring 1: the edge starting at vertex 0 crosses the edge starting at vertex 3 of ring 0 \
+         at latitude 7, longitude 5.4"
+    );
+    // Repairing drops the hole instead of leaving a fence that still crosses it.
+    let repaired = polygon.repair().unwrap();
+    assert!(repaired.validate().is_empty());
+    assert!(repaired.polygons()[0].holes().is_empty());
+}
+
+// Test case for holes that overlap each other, or that are nested in each other
+#[test]
+fn test_polygon_validity_hole_overlap() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::polygon::PolygonFence;
+    use geofencing::validity::Defect;
+
+    let square = vec![
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(10.0, 0.0),
+        Coordinates::new(10.0, 10.0),
+        Coordinates::new(0.0, 10.0),
+    ];
+    let hole1 = vec![
+        Coordinates::new(2.0, 2.0),
+        Coordinates::new(2.0, 5.0),
+        Coordinates::new(5.0, 5.0),
+        Coordinates::new(5.0, 2.0),
+    ];
+    let hole2 = vec![
+        Coordinates::new(4.0, 4.0),
+        Coordinates::new(4.0, 7.0),
+        Coordinates::new(7.0, 7.0),
+        Coordinates::new(7.0, 4.0),
+    ];
+    let nested = vec![
+        Coordinates::new(2.5, 2.5),
+        Coordinates::new(2.5, 3.5),
+        Coordinates::new(3.5, 3.5),
+        Coordinates::new(3.5, 2.5),
+    ];
+    let polygon =
+        PolygonFence::with_holes(square.clone(), vec![hole1.clone(), hole2.clone()]).unwrap();
+    let defects = polygon.validate();
+    assert_eq!(defects.len(), 2);
+    assert!(defects.iter().all(|defect| matches!(
+        defect,
+        Defect::RingIntersection {
+            ring: 2,
+            other: 1,
+            ..
+        }
+    )));
+    let repaired = polygon.repair().unwrap();
+    assert!(repaired.validate().is_empty());
+    assert_eq!(repaired.polygons()[0].holes().len(), 1);
+    let polygon = PolygonFence::with_holes(square.clone(), vec![hole1, nested]).unwrap();
+    assert_eq!(
+        polygon.validate(),
+        vec![Defect::HoleOverlap { ring: 2, other: 1 }]
+    );
+    assert!(PolygonFence::with_holes(square, vec![hole2])
+        .unwrap()
+        .validate()
+        .is_empty());
+}
+
+// Test case for a triangle without an area, which can not be repaired
+#[test]
+fn test_polygon_validity_zero_area() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Fence;
+    use geofencing::triangle::TriangleFence;
+    use geofencing::validity::{Defect, Validation};
+
+    let line = TriangleFence::new(
+        Coordinates::new(0.0, 0.0),
+        Coordinates::new(1.0, 1.0),
+        Coordinates::new(2.0, 2.0),
+    );
+    assert_eq!(line.validate(), vec![Defect::ZeroArea { ring: 0 }]);
+    assert!(Fence::from(line).repair().is_err());
+    assert_eq!("Repair".parse::<Validation>().unwrap(), Validation::Repair);
+}
+
+// Test case to validate and repair the fence of a json file with the geofence command
+#[test]
+fn test_validate_command() {
+    use std::process::Command;
+
+    let geofence = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geofence"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = geofence(&["validate", "data/polygon_geofence_bowtie.json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("polygon_geofence_bowtie: invalid Polygon fence"));
+    assert!(text.contains("ring 0: vertex 4 repeats vertex 3"));
+    let output = geofence(&[
+        "validate",
+        "data/polygon_geofence_bowtie.json",
+        "--validate",
+        "repair",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "polygon_geofence_bowtie: valid MultiPolygon fence\n"
+    );
+    let output = geofence(&[
+        "check",
+        "data/polygon_geofence_bowtie.json",
+        "1",
+        "2",
+        "--validate",
+        "reject",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+// Test case to validate the corridor fence along a planned route
+#[test]
+fn test_corridor() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::corridor::CorridorFence;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy;
+
+    let route = CorridorFence::new(
+        vec![
+            Coordinates::new(45.5, -122.7),
+            Coordinates::new(45.5, -122.6),
+            Coordinates::new(45.55, -122.55),
+        ],
+        200.0,
+    )
+    .unwrap();
+    assert!(CorridorFence::new(vec![Coordinates::new(45.5, -122.7)], 200.0).is_err());
+    assert!(CorridorFence::new(route.path().to_vec(), 0.0).is_err());
+
+    // 100 and 300 meters north of the first segment of the route, which is a great circle arc
+    // that bulges about a meter north of the parallel.
+    let near = geodesy::destination(&Coordinates::new(45.5, -122.65), 0.0, 100.0);
+    let far = geodesy::destination(&Coordinates::new(45.5, -122.65), 0.0, 300.0);
+    assert!((route.distance_to_centerline(&near) - 100.0).abs() < 2.0);
+    assert_eq!(route.locate(&near), Location::Inside);
+    assert_eq!(route.locate(&far), Location::Outside);
+    assert!((route.distance_to(&far) - 100.0).abs() < 2.0);
+    let boundary = route.boundary_distance(&near);
+    assert!((boundary.distance + 100.0).abs() < 2.0);
+    assert!((route.distance_to_centerline(&boundary.nearest) - 200.0).abs() < 0.5);
+    // Beyond the end of the route the corridor is round.
+    let past_end = geodesy::destination(&Coordinates::new(45.5, -122.7), -1.5, 150.0);
+    assert!(route.contains(&past_end));
+    assert!(route.bounding_box().contains(&far));
+}
+
+// Test case for a long east west leg that bulges more than 12 degrees north of its ends, where the
+// index still finds the points of the corridor
+#[test]
+fn test_corridor_long_leg() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::corridor::CorridorFence;
+    use geofencing::fence::Geofence;
+    use geofencing::geodesy;
+    use geofencing::index::FenceIndex;
+
+    let start = Coordinates::new(50.0, -60.0);
+    let end = Coordinates::new(50.0, 60.0);
+    let leg = CorridorFence::new(vec![start.clone(), end.clone()], 10_000.0).unwrap();
+    let middle = geodesy::destination(
+        &start,
+        geodesy::bearing(&start, &end),
+        geodesy::haversine(&start, &end) / 2.0,
+    );
+    let up = geodesy::destination(&middle, 0.0, 9_000.0);
+    assert!(middle.lat > 62.0 && middle.lon.abs() < 1e-6);
+    assert!(leg.contains(&up));
+    assert!(leg.bounding_box().contains(&up));
+    let mut index = FenceIndex::new();
+    index.insert("leg", leg);
+    assert_eq!(index.query_point(&up), vec!["leg"]);
+}
+
+// Test case for a truck that leaves the route between two fixes
+#[test]
+fn test_corridor_crossing() {
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::corridor::CorridorFence;
+    use geofencing::crossing::{segment_crossings, CrossingKind};
+    use geofencing::geodesy;
+
+    let route = CorridorFence::new(
+        vec![
+            Coordinates::new(45.5, -122.7),
+            Coordinates::new(45.5, -122.6),
+            Coordinates::new(45.55, -122.55),
+        ],
+        200.0,
+    )
+    .unwrap();
+    let near = geodesy::destination(&Coordinates::new(45.5, -122.65), 0.0, 100.0);
+    let far = geodesy::destination(&Coordinates::new(45.5, -122.65), 0.0, 300.0);
+    let crossings = segment_crossings(
+        &route,
+        &Fix::new(near.lat, near.lon).with_timestamp(0.0),
+        &Fix::new(far.lat, far.lon).with_timestamp(10.0),
+    );
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Exit);
+    assert!((crossings[0].fraction - 0.5).abs() < 0.01);
+    assert!((route.distance_to_centerline(&crossings[0].point) - 200.0).abs() < 2.0);
+}
+
+// Test case to evaluate the corridor fence of a json file
+#[test]
+fn test_corridor_files() {
+    use geofencing::coordinates::{Location, Shape};
+    use geofencing::geofencer::{contains, execute};
+    use geofencing::reporter::NoopReporter;
+
+    assert_eq!("corridor".parse::<Shape>().unwrap().to_string(), "Corridor");
+    assert!(contains(None, "corridor_geofence.json", 45.5, -122.65, &NoopReporter).unwrap());
+    let evaluations = execute(
+        Shape::Corridor,
+        "corridor_moving_tracker1.json",
+        &NoopReporter,
+    )
+    .unwrap();
+    let inside: Vec<bool> = evaluations
+        .iter()
+        .map(|evaluation| evaluation.location == Location::Inside)
+        .collect();
+    assert_eq!(inside, vec![true, true, false, true, true]);
+    assert!(contains(
+        Shape::Circle,
+        "corridor_geofence.json",
+        45.5,
+        -122.65,
+        &NoopReporter
+    )
+    .is_err());
+}
+
+// Test case to write a corridor fence to GeoJSON, as a LineString with the half width, and read it
+// back
+#[test]
+fn test_corridor_geojson() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::corridor::CorridorFence;
+    use geofencing::fence::Fence;
+    use geofencing::geofencer::read_fence;
+    use geofencing::geojson::{fences_to_geojson, read_geojson};
+
+    let route = CorridorFence::new(
+        vec![
+            Coordinates::new(45.5, -122.7),
+            Coordinates::new(45.5, -122.6),
+            Coordinates::new(45.55, -122.55),
+        ],
+        200.0,
+    )
+    .unwrap();
+    let fence = read_fence(None, "corridor_geofence.json").unwrap();
+    let geojson = fences_to_geojson(&[("route".to_string(), fence)]);
+    let read = read_geojson(geojson.to_string().as_bytes()).unwrap();
+    match &read.fences[0].1 {
+        Fence::Corridor(corridor) => {
+            assert_eq!(corridor.half_width(), 200.0);
+            assert_eq!(corridor.path(), route.path());
+        }
+        other => panic!("expected a corridor, found {:?}", other),
+    }
+    assert!(read.tracks.is_empty());
+}
+
+// Test case to find a corridor that goes across the antimeridian in the index, and to cross it
+#[test]
+fn test_corridor_antimeridian() {
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::corridor::CorridorFence;
+    use geofencing::crossing::{segment_crossings, CrossingKind};
+    use geofencing::fence::Geofence;
+    use geofencing::index::FenceIndex;
+
+    let dateline = CorridorFence::new(
+        vec![Coordinates::new(0.0, 179.9), Coordinates::new(0.0, -179.9)],
+        200.0,
+    )
+    .unwrap();
+    let bbox = dateline.bounding_box();
+    assert_eq!((bbox.min_lon, bbox.max_lon), (-180.0, 180.0));
+    assert!(dateline.contains(&Coordinates::new(0.0, 180.0)));
+    let mut index = FenceIndex::new();
+    index.insert("dateline", dateline.clone());
+    assert_eq!(
+        index.query_point(&Coordinates::new(0.0, 180.0)),
+        vec!["dateline"]
+    );
+    assert_eq!(
+        index.query_point(&Coordinates::new(0.0, -179.95)),
+        vec!["dateline"]
+    );
+    assert!(index.query_point(&Coordinates::new(0.0, 0.0)).is_empty());
+
+    // Going south across the corridor east of 180, about 1.1 km either side of its path.
+    let crossings = segment_crossings(
+        &dateline,
+        &Fix::new(0.01, -179.95),
+        &Fix::new(-0.01, -179.95),
+    );
+    let kinds: Vec<CrossingKind> = crossings.iter().map(|crossing| crossing.kind).collect();
+    assert_eq!(kinds, vec![CrossingKind::Enter, CrossingKind::Exit]);
+    assert!((crossings[0].fraction - 0.41).abs() < 0.01);
+    assert!((crossings[1].fraction - 0.59).abs() < 0.01);
+}
+
+// Test case to verify rectangular fences
+#[test]
+fn test_rectangle() {
+    use geofencing::coordinates::{Coordinates, Location};
+    use geofencing::fence::Geofence;
+    use geofencing::rectangle::RectangleFence;
+
+    let rectangle = RectangleFence::new(-2.0, 3.0, 4.0, 6.0).unwrap();
+    assert!(RectangleFence::new(4.0, 3.0, -2.0, 6.0).is_err());
+    assert!(RectangleFence::new(-2.0, 3.0, 95.0, 6.0).is_err());
+    assert!(RectangleFence::new(-2.0, 3.0, 4.0, 190.0).is_err());
+    assert!(!rectangle.crosses_antimeridian());
+    assert_eq!(
+        rectangle.locate(&Coordinates::new(1.0, 4.0)),
+        Location::Inside
+    );
+    assert_eq!(
+        rectangle.locate(&Coordinates::new(4.0, 5.0)),
+        Location::OnBoundary
+    );
+    assert_eq!(
+        rectangle.locate(&Coordinates::new(1.0, 7.0)),
+        Location::Outside
+    );
+    assert_eq!(rectangle.area(), 18.0);
+    assert_eq!(rectangle.distance_to(&Coordinates::new(1.0, 8.0)), 2.0);
+    let boundary = rectangle.boundary_distance(&Coordinates::new(1.0, 5.5));
+    assert_eq!(boundary.distance, -0.5);
+    assert_eq!(boundary.nearest, Coordinates::new(1.0, 6.0));
+}
+
+// Test case for a box from 177 east to 178 west, 5 degrees of longitude across the antimeridian
+#[test]
+fn test_rectangle_antimeridian() {
+    use geofencing::coordinates::Coordinates;
+    use geofencing::fence::Geofence;
+    use geofencing::rectangle::RectangleFence;
+
+    let fiji = RectangleFence::new(-20.0, 177.0, -15.0, -178.0).unwrap();
+    assert!(fiji.crosses_antimeridian());
+    assert_eq!(fiji.lon_span(), 5.0);
+    assert_eq!(fiji.area(), 25.0);
+    assert!(fiji.contains(&Coordinates::new(-17.5, 179.0)));
+    assert!(fiji.contains(&Coordinates::new(-17.5, 180.0)));
+    assert!(fiji.contains(&Coordinates::new(-17.5, -179.0)));
+    assert!(!fiji.contains(&Coordinates::new(-17.5, 0.0)));
+    assert!(!fiji.contains(&Coordinates::new(-17.5, -170.0)));
+    assert!(!fiji.contains(&Coordinates::new(-21.0, 179.0)));
+    // 1 degree east of the eastern side, going across 180 to the other side would be 357.
+    assert_eq!(fiji.distance_to(&Coordinates::new(-17.5, -177.0)), 1.0);
+    assert_eq!(fiji.distance_to(&Coordinates::new(-17.5, 175.0)), 2.0);
+    let bbox = fiji.bounding_box();
+    assert_eq!((bbox.min_lon, bbox.max_lon), (-180.0, 180.0));
+}
+
+// Test case to validate the crossings of a box across the antimeridian
+#[test]
+fn test_rectangle_crossings() {
+    use geofencing::coordinates::{Coordinates, Fix};
+    use geofencing::crossing::{segment_crossings, CrossingKind};
+    use geofencing::fence::Geofence;
+    use geofencing::rectangle::RectangleFence;
+
+    let fiji = RectangleFence::new(-20.0, 177.0, -15.0, -178.0).unwrap();
+    // The ship enters the box through its western side.
+    let crossings = segment_crossings(
+        &fiji,
+        &Fix::new(-17.5, 176.0).with_timestamp(0.0),
+        &Fix::new(-17.5, 178.0).with_timestamp(10.0),
+    );
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Enter);
+    assert_eq!(crossings[0].timestamp, Some(5.0));
+    // Across 180 inside the box there is no side to cross, the long way round would cross both.
+    let west_of_180 = Coordinates::new(-17.5, 179.0);
+    let east_of_180 = Coordinates::new(-17.5, -179.0);
+    assert!(fiji
+        .segment_intersections(&west_of_180, &east_of_180)
+        .is_empty());
+    assert!(fiji
+        .segment_intersections(&east_of_180, &west_of_180)
+        .is_empty());
+    assert!(segment_crossings(
+        &fiji,
+        &Fix::new(-17.5, 179.0).with_timestamp(0.0),
+        &Fix::new(-17.5, -179.0).with_timestamp(10.0),
+    )
+    .is_empty());
+    // The ship leaves through the eastern side, after going across 180.
+    let fractions = fiji.segment_intersections(&west_of_180, &Coordinates::new(-17.5, -177.0));
+    assert_eq!(fractions, vec![0.75]);
+    let crossings = segment_crossings(
+        &fiji,
+        &Fix::new(-17.5, 179.0).with_timestamp(0.0),
+        &Fix::new(-17.5, -177.0).with_timestamp(10.0),
+    );
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Exit);
+    assert_eq!(crossings[0].point.lon, -178.0);
+}
+
+// Test case to evaluate the rectangle fences of json files
+#[test]
+fn test_rectangle_files() {
+    use geofencing::coordinates::{Location, Shape};
+    use geofencing::geofencer::{contains, execute};
+    use geofencing::reporter::NoopReporter;
+
+    assert_eq!(
+        "rectangle".parse::<Shape>().unwrap().to_string(),
+        "Rectangle"
+    );
+    assert!(contains(None, "rectangle_geofence.json", 1.0, 4.0, &NoopReporter).unwrap());
+    let evaluations = execute(
+        Shape::Rectangle,
+        "rectangle_geofence_antimeridian.json",
+        &NoopReporter,
+    )
+    .unwrap();
+    let inside: Vec<bool> = evaluations
+        .iter()
+        .map(|evaluation| evaluation.location == Location::Inside)
+        .collect();
+    assert_eq!(inside, vec![false, true, true, true, false]);
+}
+
+// Test case for a GeoJSON round trip through the bbox, which keeps the box across the antimeridian
+#[test]
+fn test_rectangle_geojson() {
+    use geofencing::fence::Fence;
+    use geofencing::geofencer::read_fence;
+    use geofencing::geojson::{fences_to_geojson, read_geojson};
+    use geofencing::wkt::Geometry;
+
+    let fence = read_fence(None, "rectangle_geofence_antimeridian.json").unwrap();
+    let geojson = fences_to_geojson(&[("fiji".to_string(), fence.clone())]);
+    assert_eq!(geojson["features"][0]["geometry"]["type"], "MultiPolygon");
+    let read = read_geojson(geojson.to_string().as_bytes()).unwrap();
+    match &read.fences[0].1 {
+        Fence::Rectangle(rectangle) => {
+            assert_eq!(rectangle.min_lon(), 177.0);
+            assert_eq!(rectangle.max_lon(), -178.0);
+        }
+        other => panic!("expected a rectangle, found {:?}", other),
+    }
+    match Geometry::from_fence(&fence).unwrap() {
+        Geometry::MultiPolygon(multipolygon) => assert_eq!(multipolygon.polygons().len(), 2),
+        other => panic!("expected a multipolygon, found {:?}", other),
+    }
+}
+
+// Test case to check a position in a box across the antimeridian with the geofence command
+#[test]
+fn test_rectangle_command() {
+    use std::process::Command;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_geofence"))
+        .args([
+            "check",
+            "data/rectangle_geofence_antimeridian.json",
+            "-17.5",
+            "-179.5",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("is inside the fence"));
 }